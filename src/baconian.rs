@@ -0,0 +1,134 @@
+// src/baconian.rs
+//
+// Francis Bacon's biliteral cipher. Each letter becomes five A/B symbols, the binary
+// digits of its place in the alphabet. Bacon's own alphabet has 24 letters, with I/J
+// and U/V sharing codes; the modern variant gives all 26 letters their own. The symbols
+// can be hidden in any carrier text by writing A letters in one typeface and B letters
+// in another, here lowercase and uppercase.
+
+use crate::ceasar::{letter_to_number, numbers_to_string, string_to_numbers};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Variant {
+    Bacon24,
+    Full26,
+}
+
+impl Variant {
+    // Accepts "24" or "26", defaulting to Bacon's 24 letters when empty.
+    pub fn parse(s: &str) -> Result<Variant, String> {
+        match s.trim() {
+            "" | "24" => Ok(Variant::Bacon24),
+            "26" => Ok(Variant::Full26),
+            _ => Err(format!("Unknown Baconian variant '{}', use 24 or 26", s)),
+        }
+    }
+
+    fn index(self, n: u8) -> u8 {
+        match self {
+            Variant::Full26 => n,
+            // J shares I's code, and V shares U's, closing up the gaps after them.
+            Variant::Bacon24 => n - (n > 8) as u8 - (n > 20) as u8,
+        }
+    }
+
+    fn letter(self, index: u8) -> Option<u8> {
+        match self {
+            Variant::Full26 => (index < 26).then_some(index),
+            Variant::Bacon24 if index < 24 => {
+                Some(index + (index >= 9) as u8 + (index >= 20) as u8)
+            }
+            Variant::Bacon24 => None,
+        }
+    }
+}
+
+fn symbols(variant: Variant, n: u8) -> [bool; 5] {
+    let index = variant.index(n);
+    std::array::from_fn(|bit| index >> (4 - bit) & 1 == 1)
+}
+
+// The A/B groups, five to a letter, separated by spaces.
+pub fn bacon_encode(plaintext: &str, variant: Variant) -> String {
+    string_to_numbers(plaintext)
+        .iter()
+        .map(|&n| {
+            symbols(variant, n)
+                .iter()
+                .map(|&b| if b { 'B' } else { 'A' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn decode_bits(bits: &[bool], variant: Variant) -> Result<String, String> {
+    let numbers = bits
+        .chunks_exact(5)
+        .map(|group| {
+            let index = group.iter().fold(0, |acc, &b| acc << 1 | b as u8);
+            variant
+                .letter(index)
+                .ok_or(format!("Group {} is not a letter in this variant", index))
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+    Ok(numbers_to_string(&numbers))
+}
+
+// Reads A and B in either case, ignoring everything else. A trailing partial group is
+// dropped.
+pub fn bacon_decode(ciphertext: &str, variant: Variant) -> Result<String, String> {
+    let bits: Vec<bool> = ciphertext
+        .chars()
+        .filter_map(|c| match c.to_ascii_uppercase() {
+            'A' => Some(false),
+            'B' => Some(true),
+            _ => None,
+        })
+        .collect();
+    decode_bits(&bits, variant)
+}
+
+// Writes the carrier in lowercase, with the letters that carry a B in uppercase. The
+// carrier needs five letters for each letter of the message; any left over stay
+// lowercase and read back as padding.
+pub fn bacon_hide(message: &str, carrier: &str, variant: Variant) -> Result<String, String> {
+    let bits: Vec<bool> = string_to_numbers(message)
+        .iter()
+        .flat_map(|&n| symbols(variant, n))
+        .collect();
+    let available = carrier
+        .chars()
+        .filter(|c| letter_to_number(*c).is_some())
+        .count();
+    if available < bits.len() {
+        return Err(format!(
+            "The carrier has {} letters but the message needs {}",
+            available,
+            bits.len()
+        ));
+    }
+    let mut bits = bits.into_iter();
+    Ok(carrier
+        .chars()
+        .map(|c| {
+            if letter_to_number(c).is_none() {
+                c
+            } else if bits.next() == Some(true) {
+                c.to_ascii_uppercase()
+            } else {
+                c.to_ascii_lowercase()
+            }
+        })
+        .collect())
+}
+
+// Reads the case of every letter of a carrier text as A (lower) or B (upper).
+pub fn bacon_reveal(carrier: &str, variant: Variant) -> Result<String, String> {
+    let bits: Vec<bool> = carrier
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.is_ascii_uppercase())
+        .collect();
+    decode_bits(&bits, variant)
+}
@@ -0,0 +1,474 @@
+// src/bigint.rs
+//
+// Arbitrary-precision unsigned integers for the number-theory routines, large enough
+// for RSA-sized exercises. Numbers are stored as 64-bit limbs, least significant first,
+// with no leading zero limbs, so that every value has one representation. Products
+// and quotients use the schoolbook methods, with Knuth's Algorithm D for division.
+
+use crate::rng::Rng;
+use crate::Unsigned;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Rem, Shl, Shr, Sub};
+use std::str::FromStr;
+
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    fn from_limbs(mut limbs: Vec<u64>) -> BigUint {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigUint { limbs }
+    }
+
+    pub fn from_u128(n: u128) -> BigUint {
+        BigUint::from_limbs(vec![n as u64, (n >> 64) as u64])
+    }
+
+    pub fn to_u128(&self) -> Option<u128> {
+        match self.limbs[..] {
+            [] => Some(0),
+            [low] => Some(low as u128),
+            [low, high] => Some((high as u128) << 64 | low as u128),
+            _ => None,
+        }
+    }
+
+    // The number of bits up to and including the highest set bit.
+    pub fn bits(&self) -> u64 {
+        match self.limbs.last() {
+            None => 0,
+            Some(top) => 64 * self.limbs.len() as u64 - top.leading_zeros() as u64,
+        }
+    }
+
+    pub fn bit(&self, i: u64) -> bool {
+        self.limbs
+            .get((i / 64) as usize)
+            .is_some_and(|limb| limb >> (i % 64) & 1 == 1)
+    }
+
+    pub fn is_even(&self) -> bool {
+        !self.bit(0)
+    }
+
+    // The remainder on division by a single limb, without building the quotient.
+    pub fn rem_u64(&self, divisor: u64) -> u64 {
+        self.limbs.iter().rev().fold(0u128, |remainder, &limb| {
+            (remainder << 64 | limb as u128) % divisor as u128
+        }) as u64
+    }
+
+    // The integer square root, the largest r with r * r <= self, by Newton's method
+    // from above.
+    pub fn sqrt(&self) -> BigUint {
+        if self.limbs.is_empty() {
+            return BigUint::default();
+        }
+        let mut x = BigUint::from(1) << self.bits().div_ceil(2);
+        loop {
+            let next = (x.clone() + self.clone() / x.clone()) >> 1;
+            if next >= x {
+                return x;
+            }
+            x = next;
+        }
+    }
+
+    // The base-2 logarithm, from the top 64 bits, which is as precise as an f64 gets.
+    // Negative infinity for zero, as for the primitive floats.
+    pub fn log2(&self) -> f64 {
+        let shift = self.bits().saturating_sub(64);
+        let top = (self.clone() >> shift).limbs.first().copied().unwrap_or(0);
+        shift as f64 + (top as f64).log2()
+    }
+
+    // A uniform number below 2^bits.
+    pub fn random_bits(bits: u64, rng: &mut Rng) -> BigUint {
+        let mut limbs: Vec<u64> = (0..bits.div_ceil(64)).map(|_| rng.next_u64()).collect();
+        if !bits.is_multiple_of(64) {
+            *limbs.last_mut().unwrap() >>= 64 - bits % 64;
+        }
+        BigUint::from_limbs(limbs)
+    }
+
+    // A uniform number in 0..n, n > 0, drawing bits until one falls below n.
+    pub fn random_below(n: &BigUint, rng: &mut Rng) -> BigUint {
+        assert!(!n.is_zero(), "No number is below zero");
+        loop {
+            let x = BigUint::random_bits(n.bits(), rng);
+            if x < *n {
+                return x;
+            }
+        }
+    }
+
+    // Big-endian bytes without leading zeros, as RSA exercises write numbers.
+    pub fn from_bytes_be(bytes: &[u8]) -> BigUint {
+        let limbs = bytes
+            .rchunks(8)
+            .map(|chunk| chunk.iter().fold(0u64, |acc, &b| acc << 8 | b as u64))
+            .collect();
+        BigUint::from_limbs(limbs)
+    }
+
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        self.limbs
+            .iter()
+            .rev()
+            .flat_map(|limb| limb.to_be_bytes())
+            .skip_while(|&b| b == 0)
+            .collect()
+    }
+
+    fn mul_small_add(&self, factor: u64, addend: u64) -> BigUint {
+        let mut limbs = Vec::with_capacity(self.limbs.len() + 1);
+        let mut carry = addend as u128;
+        for &limb in &self.limbs {
+            let product = limb as u128 * factor as u128 + carry;
+            limbs.push(product as u64);
+            carry = product >> 64;
+        }
+        limbs.push(carry as u64);
+        BigUint::from_limbs(limbs)
+    }
+
+    fn div_rem_small(&self, divisor: u64) -> (BigUint, u64) {
+        let mut quotient = vec![0; self.limbs.len()];
+        let mut remainder = 0u128;
+        for i in (0..self.limbs.len()).rev() {
+            let current = remainder << 64 | self.limbs[i] as u128;
+            quotient[i] = (current / divisor as u128) as u64;
+            remainder = current % divisor as u128;
+        }
+        (BigUint::from_limbs(quotient), remainder as u64)
+    }
+
+    pub fn div_rem(&self, divisor: &BigUint) -> (BigUint, BigUint) {
+        assert!(!divisor.limbs.is_empty(), "Division by zero");
+        if self < divisor {
+            return (BigUint::default(), self.clone());
+        }
+        if divisor.limbs.len() == 1 {
+            let (quotient, remainder) = self.div_rem_small(divisor.limbs[0]);
+            return (quotient, BigUint::from_u64(remainder).unwrap());
+        }
+
+        // Algorithm D: normalize so the divisor's top limb has its high bit set, which
+        // keeps each estimated quotient limb at most two above the true one.
+        let shift = divisor.limbs.last().unwrap().leading_zeros() as u64;
+        let v = (divisor.clone() << shift).limbs;
+        let mut u = (self.clone() << shift).limbs;
+        u.push(0);
+        if u.len() == self.limbs.len() {
+            u.push(0);
+        }
+        let n = v.len();
+        let m = u.len() - n;
+        let base = 1u128 << 64;
+        let mut quotient = vec![0u64; m];
+        for j in (0..m).rev() {
+            let numerator = (u[j + n] as u128) << 64 | u[j + n - 1] as u128;
+            let mut qhat = numerator / v[n - 1] as u128;
+            let mut rhat = numerator % v[n - 1] as u128;
+            while qhat >= base || qhat * v[n - 2] as u128 > (rhat << 64 | u[j + n - 2] as u128) {
+                qhat -= 1;
+                rhat += v[n - 1] as u128;
+                if rhat >= base {
+                    break;
+                }
+            }
+
+            // Subtract qhat times the divisor from the current window of u.
+            let mut carry = 0u128;
+            let mut borrow = 0u64;
+            for i in 0..n {
+                let product = qhat * v[i] as u128 + carry;
+                carry = product >> 64;
+                let (t, b1) = u[i + j].overflowing_sub(product as u64);
+                let (t, b2) = t.overflowing_sub(borrow);
+                u[i + j] = t;
+                borrow = b1 as u64 + b2 as u64;
+            }
+            let (t, b1) = u[j + n].overflowing_sub(carry as u64);
+            let (t, b2) = t.overflowing_sub(borrow);
+            u[j + n] = t;
+
+            // The estimate was one too many: add the divisor back.
+            if b1 || b2 {
+                qhat -= 1;
+                let mut carry = 0u128;
+                for i in 0..n {
+                    let sum = u[i + j] as u128 + v[i] as u128 + carry;
+                    u[i + j] = sum as u64;
+                    carry = sum >> 64;
+                }
+                u[j + n] = u[j + n].wrapping_add(carry as u64);
+            }
+            quotient[j] = qhat as u64;
+        }
+        u.truncate(n);
+        (
+            BigUint::from_limbs(quotient),
+            BigUint::from_limbs(u) >> shift,
+        )
+    }
+
+    // Parses decimal digits, or hexadecimal after "0x".
+    pub fn parse(s: &str) -> Result<BigUint, String> {
+        let error = || format!("'{}' is not a non-negative integer", s);
+        let (digits, radix) = match s.strip_prefix("0x") {
+            Some(hex) => (hex, 16),
+            None => (s, 10),
+        };
+        if digits.is_empty() {
+            return Err(error());
+        }
+        let mut n = BigUint::default();
+        for c in digits.chars() {
+            let digit = c.to_digit(radix).ok_or_else(error)?;
+            n = n.mul_small_add(radix as u64, digit as u64);
+        }
+        Ok(n)
+    }
+
+    pub fn to_hex(&self) -> String {
+        match self.limbs.split_last() {
+            None => "0".to_string(),
+            Some((top, rest)) => {
+                let mut hex = format!("{:x}", top);
+                for limb in rest.iter().rev() {
+                    hex += &format!("{:016x}", limb);
+                }
+                hex
+            }
+        }
+    }
+}
+
+impl FromStr for BigUint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<BigUint, String> {
+        BigUint::parse(s)
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(n: u64) -> BigUint {
+        BigUint::from_limbs(vec![n])
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &BigUint) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &BigUint) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: BigUint) -> BigUint {
+        let (long, short) = if self.limbs.len() >= other.limbs.len() {
+            (self.limbs, other.limbs)
+        } else {
+            (other.limbs, self.limbs)
+        };
+        let mut limbs = Vec::with_capacity(long.len() + 1);
+        let mut carry = false;
+        for (i, &a) in long.iter().enumerate() {
+            let (sum, c1) = a.overflowing_add(short.get(i).copied().unwrap_or(0));
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            limbs.push(sum);
+            carry = c1 || c2;
+        }
+        limbs.push(carry as u64);
+        BigUint::from_limbs(limbs)
+    }
+}
+
+// Panics if the result would be negative, like the primitive types in debug builds.
+impl Sub for BigUint {
+    type Output = BigUint;
+
+    fn sub(self, other: BigUint) -> BigUint {
+        assert!(self >= other, "Subtraction overflow");
+        let mut limbs = self.limbs;
+        let mut borrow = false;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let (diff, b1) = limb.overflowing_sub(other.limbs.get(i).copied().unwrap_or(0));
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = b1 || b2;
+        }
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl Mul for BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: BigUint) -> BigUint {
+        if self.limbs.is_empty() || other.limbs.is_empty() {
+            return BigUint::default();
+        }
+        let mut limbs = vec![0u64; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let t = a as u128 * b as u128 + limbs[i + j] as u128 + carry;
+                limbs[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            limbs[i + other.limbs.len()] = carry as u64;
+        }
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl Div for BigUint {
+    type Output = BigUint;
+
+    fn div(self, other: BigUint) -> BigUint {
+        self.div_rem(&other).0
+    }
+}
+
+impl Rem for BigUint {
+    type Output = BigUint;
+
+    fn rem(self, other: BigUint) -> BigUint {
+        self.div_rem(&other).1
+    }
+}
+
+impl Shl<u64> for BigUint {
+    type Output = BigUint;
+
+    fn shl(self, shift: u64) -> BigUint {
+        let (whole, part) = ((shift / 64) as usize, shift % 64);
+        let mut limbs = vec![0u64; whole];
+        let mut carry = 0u64;
+        for &limb in &self.limbs {
+            if part == 0 {
+                limbs.push(limb);
+            } else {
+                limbs.push(limb << part | carry);
+                carry = limb >> (64 - part);
+            }
+        }
+        limbs.push(carry);
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl Shr<u64> for BigUint {
+    type Output = BigUint;
+
+    fn shr(self, shift: u64) -> BigUint {
+        let (whole, part) = ((shift / 64) as usize, shift % 64);
+        if whole >= self.limbs.len() {
+            return BigUint::default();
+        }
+        let rest = &self.limbs[whole..];
+        let limbs = (0..rest.len())
+            .map(|i| {
+                if part == 0 {
+                    rest[i]
+                } else {
+                    let high = rest.get(i + 1).map_or(0, |next| next << (64 - part));
+                    rest[i] >> part | high
+                }
+            })
+            .collect();
+        BigUint::from_limbs(limbs)
+    }
+}
+
+// Decimal, produced nineteen digits at a time.
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        if self.limbs.is_empty() {
+            return write!(f, "0");
+        }
+        let mut chunks = vec![];
+        let mut rest = self.clone();
+        while !rest.limbs.is_empty() {
+            let (quotient, remainder) = rest.div_rem_small(CHUNK);
+            chunks.push(remainder);
+            rest = quotient;
+        }
+        write!(f, "{}", chunks.pop().unwrap())?;
+        for chunk in chunks.iter().rev() {
+            write!(f, "{:019}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl Unsigned for BigUint {
+    fn zero() -> Self {
+        BigUint::default()
+    }
+
+    fn one() -> Self {
+        BigUint::from(1)
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(BigUint::from(n))
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        match self.limbs[..] {
+            [] => Some(0),
+            [n] => Some(n),
+            _ => None,
+        }
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        self.clone()
+    }
+
+    fn from_biguint(n: &BigUint) -> Option<Self> {
+        Some(n.clone())
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self.clone() + other.clone())
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(self.clone() * other.clone())
+    }
+
+    fn mul_mod(&self, other: &Self, n: &Self) -> Self {
+        (self.clone() * other.clone()).div_rem(n).1
+    }
+
+    fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+}
@@ -0,0 +1,357 @@
+// src/cayley.rs
+//
+// Cayley tables mod n for addition, multiplication and exponentiation, for handouts.
+// Rows and columns run over every residue from 0, or over the units alone. Where a
+// product is the identity its two operands are inverses, so those cells are
+// highlighted, as are the identity's own row and column. A power table has bases for
+// rows and exponents 0 to n - 1 for columns; its highlighted cells, the powers equal to
+// 1, mark the multiples of each base's order.
+
+use crypto_converter::{gcd, Unsigned};
+
+// A table has n^2 cells, so the modulus is kept to what fits on a page or a screen.
+pub const MAX_MODULUS: u32 = 1_000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operation {
+    Add,
+    Multiply,
+    Power,
+}
+
+impl Operation {
+    fn apply(self, a: u32, b: u32, n: u32) -> u32 {
+        match self {
+            Operation::Add => ((a as u64 + b as u64) % n as u64) as u32,
+            Operation::Multiply => a.mul_mod(&b, &n),
+            Operation::Power => {
+                let (mut result, mut square, mut exp) = (1 % n, a % n, b);
+                while exp > 0 {
+                    if exp & 1 == 1 {
+                        result = result.mul_mod(&square, &n);
+                    }
+                    square = square.mul_mod(&square, &n);
+                    exp >>= 1;
+                }
+                result
+            }
+        }
+    }
+
+    // The identity for + and ×, and the value 1 that marks orders in a power table.
+    fn identity(self, n: u32) -> u32 {
+        match self {
+            Operation::Add => 0,
+            Operation::Multiply | Operation::Power => 1 % n,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Operation::Add => "+",
+            Operation::Multiply => "×",
+            Operation::Power => "^",
+        }
+    }
+
+    fn latex(self) -> &'static str {
+        match self {
+            Operation::Add => "$+$",
+            Operation::Multiply => "$\\times$",
+            Operation::Power => "$\\wedge$",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TableFormat {
+    Text,
+    Csv,
+    Markdown,
+    Latex,
+    Html,
+}
+
+#[derive(Clone, Debug)]
+pub struct CayleyOptions {
+    pub operation: Operation,
+    // Only the residues coprime to n, which under × form a group.
+    pub units: bool,
+    pub format: TableFormat,
+    pub highlight: bool,
+}
+
+impl Default for CayleyOptions {
+    fn default() -> Self {
+        CayleyOptions {
+            operation: Operation::Multiply,
+            units: false,
+            format: TableFormat::Text,
+            highlight: true,
+        }
+    }
+}
+
+fn yes_no(name: &str, value: &str) -> Result<bool, String> {
+    match value {
+        "yes" | "true" | "on" => Ok(true),
+        "no" | "false" | "off" => Ok(false),
+        _ => Err(format!(
+            "The {} option takes yes or no, found '{}'",
+            name, value
+        )),
+    }
+}
+
+// Parses "op=add units=yes format=latex highlight=no".
+pub fn parse_cayley_options(s: &str) -> Result<CayleyOptions, String> {
+    let mut options = CayleyOptions::default();
+    for field in s.split_whitespace() {
+        let (name, value) = field
+            .split_once('=')
+            .ok_or(format!("Expected name=value, found '{}'", field))?;
+        match name.to_ascii_lowercase().as_str() {
+            "op" => {
+                options.operation = match value {
+                    "add" | "+" => Operation::Add,
+                    "mul" | "*" => Operation::Multiply,
+                    "pow" | "^" => Operation::Power,
+                    _ => {
+                        return Err(format!(
+                            "Unknown operation '{}', use add, mul or pow",
+                            value
+                        ))
+                    }
+                }
+            }
+            "units" => options.units = yes_no(name, value)?,
+            "highlight" => options.highlight = yes_no(name, value)?,
+            "format" => {
+                options.format = match value.to_ascii_lowercase().as_str() {
+                    "text" => TableFormat::Text,
+                    "csv" => TableFormat::Csv,
+                    "markdown" | "md" => TableFormat::Markdown,
+                    "latex" | "tex" => TableFormat::Latex,
+                    "html" => TableFormat::Html,
+                    _ => {
+                        return Err(format!(
+                            "Unknown format '{}', use text, csv, markdown, latex or html",
+                            value
+                        ))
+                    }
+                }
+            }
+            _ => return Err(format!("Unknown option '{}'", name)),
+        }
+    }
+    Ok(options)
+}
+
+// A rendered entry and whether it is highlighted.
+type Cell = (String, bool);
+
+pub struct CayleyTable {
+    pub modulus: u32,
+    pub operation: Operation,
+    pub rows: Vec<u32>,
+    pub columns: Vec<u32>,
+    pub cells: Vec<Vec<u32>>,
+}
+
+impl CayleyTable {
+    pub fn new(n: u32, operation: Operation, units: bool) -> CayleyTable {
+        let rows: Vec<u32> = (0..n).filter(|&a| !units || gcd(a, n) == 1).collect();
+        let columns = if operation == Operation::Power {
+            (0..n).collect()
+        } else {
+            rows.clone()
+        };
+        let cells = rows
+            .iter()
+            .map(|&a| columns.iter().map(|&b| operation.apply(a, b, n)).collect())
+            .collect();
+        CayleyTable {
+            modulus: n,
+            operation,
+            rows,
+            columns,
+            cells,
+        }
+    }
+
+    fn is_identity_cell(&self, value: u32) -> bool {
+        value == self.operation.identity(self.modulus)
+    }
+
+    // The identity as an operand: a row or column of + or ×, or the exponent 1.
+    fn is_identity_header(&self, value: u32, column: bool) -> bool {
+        match self.operation {
+            Operation::Power => column && value == 1,
+            _ => value == self.operation.identity(self.modulus),
+        }
+    }
+
+    pub fn render(&self, format: TableFormat, highlight: bool) -> String {
+        let header: Vec<Cell> = self
+            .columns
+            .iter()
+            .map(|&b| (b.to_string(), highlight && self.is_identity_header(b, true)))
+            .collect();
+        let body: Vec<(Cell, Vec<Cell>)> = self
+            .rows
+            .iter()
+            .zip(&self.cells)
+            .map(|(&a, row)| {
+                let label = (
+                    a.to_string(),
+                    highlight && self.is_identity_header(a, false),
+                );
+                let cells = row
+                    .iter()
+                    .map(|&v| (v.to_string(), highlight && self.is_identity_cell(v)))
+                    .collect();
+                (label, cells)
+            })
+            .collect();
+        match format {
+            TableFormat::Text => self.render_text(&header, &body),
+            TableFormat::Csv => {
+                let mut lines = vec![std::iter::once(self.operation.symbol().to_string())
+                    .chain(header.iter().map(|(h, _)| h.clone()))
+                    .collect::<Vec<_>>()
+                    .join(",")];
+                for ((label, _), cells) in &body {
+                    let values: Vec<&str> = cells.iter().map(|(v, _)| v.as_str()).collect();
+                    lines.push(format!("{},{}", label, values.join(",")));
+                }
+                lines.join("\n") + "\n"
+            }
+            TableFormat::Markdown => {
+                let bold = |(v, marked): &Cell| {
+                    if *marked {
+                        format!("**{}**", v)
+                    } else {
+                        v.clone()
+                    }
+                };
+                let row = |first: String, rest: &[Cell]| {
+                    let rest: Vec<String> = rest.iter().map(bold).collect();
+                    format!("| {} | {} |", first, rest.join(" | "))
+                };
+                let mut lines = vec![
+                    row(self.operation.symbol().to_string(), &header),
+                    format!("|---|{}", "---|".repeat(header.len())),
+                ];
+                for (label, cells) in &body {
+                    lines.push(row(bold(label), cells));
+                }
+                lines.join("\n") + "\n"
+            }
+            TableFormat::Latex => {
+                let bold = |(v, marked): &Cell| {
+                    if *marked {
+                        format!("\\textbf{{{}}}", v)
+                    } else {
+                        v.clone()
+                    }
+                };
+                let row = |first: String, rest: &[Cell]| {
+                    let rest: Vec<String> = rest.iter().map(bold).collect();
+                    format!("{} & {} \\\\", first, rest.join(" & "))
+                };
+                let mut lines = vec![
+                    format!("\\begin{{tabular}}{{c|{}}}", "c".repeat(header.len())),
+                    row(self.operation.latex().to_string(), &header),
+                    "\\hline".to_string(),
+                ];
+                for (label, cells) in &body {
+                    lines.push(row(bold(label), cells));
+                }
+                lines.push("\\end{tabular}".to_string());
+                lines.join("\n") + "\n"
+            }
+            TableFormat::Html => {
+                let cell = |tag: &str, class: &str, (v, marked): &Cell| {
+                    if *marked {
+                        format!("<{} class=\"{}\">{}</{}>", tag, class, v, tag)
+                    } else {
+                        format!("<{}>{}</{}>", tag, v, tag)
+                    }
+                };
+                let mut lines = vec![
+                    "<table class=\"cayley\">".to_string(),
+                    format!(
+                        "<tr><th>{}</th>{}</tr>",
+                        self.operation.symbol(),
+                        header
+                            .iter()
+                            .map(|h| cell("th", "identity", h))
+                            .collect::<String>()
+                    ),
+                ];
+                for (label, cells) in &body {
+                    lines.push(format!(
+                        "<tr>{}{}</tr>",
+                        cell("th", "identity", label),
+                        cells
+                            .iter()
+                            .map(|c| cell("td", "inverse", c))
+                            .collect::<String>()
+                    ));
+                }
+                lines.push("</table>".to_string());
+                lines.join("\n") + "\n"
+            }
+        }
+    }
+
+    // A grid with the highlighted entries in brackets:
+    //    × |  1   2   3   4
+    //   ---+----------------
+    //    1 | [1]  2   3   4
+    fn render_text(&self, header: &[Cell], body: &[(Cell, Vec<Cell>)]) -> String {
+        let width = header
+            .iter()
+            .chain(
+                body.iter()
+                    .flat_map(|(label, cells)| cells.iter().chain([label])),
+            )
+            .map(|(v, _)| v.len())
+            .max()
+            .unwrap_or(1);
+        let cell = |(v, marked): &Cell| {
+            if *marked {
+                format!("[{:>w$}]", v, w = width)
+            } else {
+                format!(" {:>w$} ", v, w = width)
+            }
+        };
+        let mut text = format!(
+            "{} (mod {})\n",
+            match self.operation {
+                Operation::Add => "Addition Table",
+                Operation::Multiply => "Multiplication Table",
+                Operation::Power => "Power Table",
+            },
+            self.modulus
+        );
+        text += &format!(
+            "{} |{}\n",
+            cell(&(self.operation.symbol().to_string(), false)),
+            header.iter().map(cell).collect::<String>()
+        );
+        text += &format!(
+            "{}+{}\n",
+            "-".repeat(width + 3),
+            "-".repeat((width + 2) * header.len())
+        );
+        for (label, cells) in body {
+            text += &format!(
+                "{} |{}\n",
+                cell(label),
+                cells.iter().map(cell).collect::<String>()
+            );
+        }
+        text
+    }
+}
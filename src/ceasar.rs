@@ -27,3 +27,19 @@ pub fn numbers_to_string(numbers: &[u8]) -> String {
         .filter_map(|&n| number_to_letter(n))
         .collect()
 }
+
+pub fn encrypt(plaintext: &str, shift: u8) -> String {
+    let numbers = string_to_numbers(plaintext)
+        .iter()
+        .map(|&n| (n + shift % 26) % 26)
+        .collect::<Vec<u8>>();
+    numbers_to_string(&numbers)
+}
+
+pub fn decrypt(ciphertext: &str, shift: u8) -> String {
+    let numbers = string_to_numbers(ciphertext)
+        .iter()
+        .map(|&n| (n + 26 - shift % 26) % 26)
+        .collect::<Vec<u8>>();
+    numbers_to_string(&numbers)
+}
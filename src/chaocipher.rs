@@ -0,0 +1,127 @@
+// src/chaocipher.rs
+//
+// John F. Byrne's Chaocipher. Two mixed alphabets, the left (ciphertext) and right
+// (plaintext) wheels, are permuted after every letter so that the substitution never
+// repeats in a simple pattern.
+
+use crate::ceasar::{letter_to_number, number_to_letter, string_to_numbers};
+use std::fmt;
+
+// Position 0 is the zenith and 13 the nadir.
+const NADIR: usize = 13;
+
+// Both alphabets as they stood when a letter was enciphered, with the letter pair.
+#[derive(Clone, Debug)]
+pub struct ChaocipherStep {
+    pub left: String,
+    pub right: String,
+    pub plain: char,
+    pub cipher: char,
+}
+
+impl fmt::Display for ChaocipherStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {}  {} -> {}",
+            self.left, self.right, self.plain, self.cipher
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Chaocipher {
+    left: Vec<u8>,
+    right: Vec<u8>,
+}
+
+fn parse_alphabet(s: &str) -> Result<Vec<u8>, String> {
+    let alphabet = s
+        .chars()
+        .map(|c| letter_to_number(c).ok_or(format!("Invalid letter '{}' in alphabet", c)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut seen = [false; 26];
+    for &n in &alphabet {
+        seen[n as usize] = true;
+    }
+    if alphabet.len() != 26 || seen.contains(&false) {
+        return Err(format!("'{}' is not a permutation of the alphabet", s));
+    }
+    Ok(alphabet)
+}
+
+fn alphabet_string(alphabet: &[u8]) -> String {
+    alphabet
+        .iter()
+        .filter_map(|&n| number_to_letter(n))
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
+
+impl Chaocipher {
+    // Parses "left=HXUCZVAMDSLKPEFJRIGTWOBNYQ right=PTLNBQDEOYSFAVZKGJRIHWXUMC".
+    pub fn parse(key: &str) -> Result<Chaocipher, String> {
+        let mut left = None;
+        let mut right = None;
+        for field in key.split_whitespace() {
+            let (name, value) = field
+                .split_once('=')
+                .ok_or(format!("Expected name=value, found '{}'", field))?;
+            match name.to_ascii_lowercase().as_str() {
+                "left" => left = Some(parse_alphabet(value)?),
+                "right" => right = Some(parse_alphabet(value)?),
+                _ => return Err(format!("Unknown setting '{}'", name)),
+            }
+        }
+        Ok(Chaocipher {
+            left: left.ok_or("Missing left alphabet")?,
+            right: right.ok_or("Missing right alphabet")?,
+        })
+    }
+
+    // Brings the letter at `index` to the zenith of both alphabets and permutes them.
+    fn permute(&mut self, index: usize) {
+        self.left.rotate_left(index);
+        let extracted = self.left.remove(1);
+        self.left.insert(NADIR, extracted);
+
+        self.right.rotate_left(index + 1);
+        let extracted = self.right.remove(2);
+        self.right.insert(NADIR, extracted);
+    }
+
+    // Enciphers or deciphers one letter, recording the alphabets it was read from.
+    fn transform(&mut self, n: u8, decrypt: bool) -> ChaocipherStep {
+        let (from, to) = if decrypt {
+            (&self.left, &self.right)
+        } else {
+            (&self.right, &self.left)
+        };
+        let index = from.iter().position(|&c| c == n).unwrap();
+        let out = to[index];
+        let (plain, cipher) = if decrypt { (out, n) } else { (n, out) };
+        let step = ChaocipherStep {
+            left: alphabet_string(&self.left),
+            right: alphabet_string(&self.right),
+            plain: (b'A' + plain) as char,
+            cipher: (b'A' + cipher) as char,
+        };
+        self.permute(index);
+        step
+    }
+
+    // Returns every step; the output is the cipher or plain letters of the steps.
+    pub fn encrypt(&mut self, plaintext: &str) -> Vec<ChaocipherStep> {
+        string_to_numbers(plaintext)
+            .into_iter()
+            .map(|n| self.transform(n, false))
+            .collect()
+    }
+
+    pub fn decrypt(&mut self, ciphertext: &str) -> Vec<ChaocipherStep> {
+        string_to_numbers(ciphertext)
+            .into_iter()
+            .map(|n| self.transform(n, true))
+            .collect()
+    }
+}
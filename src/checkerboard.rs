@@ -0,0 +1,218 @@
+// src/checkerboard.rs
+//
+// The straddling checkerboard, a letter-to-digit encoder used as the first stage of
+// many Cold War hand ciphers. Eight frequent letters sit in the top row and get a
+// single digit; the other letters and two symbols sit in two lower rows headed by the
+// digits over the top row's blanks, and get two digits. Because no single-digit code
+// is also a row prefix, the digit stream decodes without separators.
+
+use std::fmt;
+
+// The full stop and the figure shift, which brackets digits in the message.
+const STOP: char = '.';
+const FIGURE: char = '/';
+
+#[derive(Clone, Debug)]
+pub struct StraddlingCheckerboard {
+    // The digit heading each column.
+    digits: [u8; 10],
+    // The top row, with None for the two blanks, then the two lower rows.
+    top: [Option<char>; 10],
+    rows: [[char; 10]; 2],
+    // The column digits over the blanks, which prefix the two lower rows.
+    prefixes: [u8; 2],
+}
+
+impl StraddlingCheckerboard {
+    // `top` holds eight letters and two blanks written as '_'. `rows` lists the 20
+    // cells of the lower rows; when it is None the remaining letters are used in
+    // alphabetical order followed by '.' and '/'.
+    pub fn new(
+        digits: [u8; 10],
+        top: &str,
+        rows: Option<&str>,
+    ) -> Result<StraddlingCheckerboard, String> {
+        let mut sorted = digits;
+        sorted.sort();
+        if sorted != [0, 1, 2, 3, 4, 5, 6, 7, 8, 9] {
+            return Err("The column digits must be 0-9, each once".to_string());
+        }
+        let top: Vec<Option<char>> = top
+            .chars()
+            .map(|c| (c != '_').then(|| c.to_ascii_uppercase()))
+            .collect();
+        if top.len() != 10 || top.iter().filter(|c| c.is_none()).count() != 2 {
+            return Err("The top row needs eight letters and two blanks ('_')".to_string());
+        }
+        let rows: Vec<char> = match rows {
+            Some(rows) => rows.chars().map(|c| c.to_ascii_uppercase()).collect(),
+            None => ('A'..='Z')
+                .filter(|c| !top.contains(&Some(*c)))
+                .chain([STOP, FIGURE])
+                .collect(),
+        };
+        if rows.len() != 20 {
+            return Err("The lower rows need exactly 20 cells".to_string());
+        }
+
+        let mut cells: Vec<char> = top.iter().flatten().chain(&rows).copied().collect();
+        cells.sort();
+        let mut expected: Vec<char> = ('A'..='Z').chain([STOP, FIGURE]).collect();
+        expected.sort();
+        if cells != expected {
+            return Err("The board must hold A-Z, '.' and '/' exactly once each".to_string());
+        }
+
+        let mut blanks = top.iter().enumerate().filter(|(_, c)| c.is_none());
+        let prefixes = [
+            digits[blanks.next().unwrap().0],
+            digits[blanks.next().unwrap().0],
+        ];
+        let mut board_rows = [[' '; 10]; 2];
+        for (i, &c) in rows.iter().enumerate() {
+            board_rows[i / 10][i % 10] = c;
+        }
+        Ok(StraddlingCheckerboard {
+            digits,
+            top: top.try_into().unwrap(),
+            rows: board_rows,
+            prefixes,
+        })
+    }
+
+    // Parses "top=ET_AON_RIS rows=BCDFGHJKLMPQ/UVWXYZ. digits=0123456789". Only the top
+    // row is required.
+    pub fn parse(key: &str) -> Result<StraddlingCheckerboard, String> {
+        let mut digits = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let mut top = None;
+        let mut rows = None;
+        for field in key.split_whitespace() {
+            let (name, value) = field
+                .split_once('=')
+                .ok_or(format!("Expected name=value, found '{}'", field))?;
+            match name.to_ascii_lowercase().as_str() {
+                "top" => top = Some(value),
+                "rows" => rows = Some(value),
+                "digits" => {
+                    let parsed: Vec<u8> = value
+                        .chars()
+                        .map(|c| c.to_digit(10).map(|d| d as u8))
+                        .collect::<Option<_>>()
+                        .ok_or(format!("Invalid column digits '{}'", value))?;
+                    digits = parsed
+                        .try_into()
+                        .map_err(|_| "There must be ten column digits".to_string())?;
+                }
+                _ => return Err(format!("Unknown setting '{}'", name)),
+            }
+        }
+        StraddlingCheckerboard::new(digits, top.ok_or("Missing top row")?, rows)
+    }
+
+    fn code(&self, c: char) -> Option<Vec<u8>> {
+        if let Some(column) = self.top.iter().position(|&t| t == Some(c)) {
+            return Some(vec![self.digits[column]]);
+        }
+        (0..2).find_map(|row| {
+            self.rows[row]
+                .iter()
+                .position(|&r| r == c)
+                .map(|column| vec![self.prefixes[row], self.digits[column]])
+        })
+    }
+
+    // Letters and full stops become their codes; a run of digits is enclosed in figure
+    // shifts with each digit written three times. Anything else is dropped.
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        let figure = self.code(FIGURE).unwrap();
+        let mut output = vec![];
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if let Some(d) = c.to_digit(10) {
+                output.extend(&figure);
+                output.extend([d as u8; 3]);
+                while let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+                    output.extend([d as u8; 3]);
+                    chars.next();
+                }
+                output.extend(&figure);
+            } else if let Some(code) = self.code(c.to_ascii_uppercase()).filter(|_| c != FIGURE) {
+                output.extend(code);
+            }
+        }
+        output
+    }
+
+    pub fn decode(&self, digits: &[u8]) -> Result<String, String> {
+        let mut output = String::new();
+        let mut i = 0;
+        while i < digits.len() {
+            let c = match self.prefixes.iter().position(|&p| p == digits[i]) {
+                Some(row) => {
+                    let d = *digits.get(i + 1).ok_or("The digits end inside a code")?;
+                    i += 2;
+                    let column = self.digits.iter().position(|&h| h == d).unwrap();
+                    self.rows[row][column]
+                }
+                None => {
+                    let column = self.digits.iter().position(|&h| h == digits[i]).unwrap();
+                    i += 1;
+                    self.top[column].unwrap()
+                }
+            };
+            if c != FIGURE {
+                output.push(c);
+                continue;
+            }
+            let figure = self.code(FIGURE).unwrap();
+            loop {
+                match digits.get(i..i + 3) {
+                    Some(&[a, b, c]) if a == b && b == c => {
+                        output.push((b'0' + a) as char);
+                        i += 3;
+                    }
+                    _ if digits.get(i..i + 2) == Some(&figure[..]) => {
+                        i += 2;
+                        break;
+                    }
+                    _ => return Err(format!("Unterminated number at digit {}", i + 1)),
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+impl fmt::Display for StraddlingCheckerboard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let header: Vec<String> = self.digits.iter().map(|d| d.to_string()).collect();
+        writeln!(f, "   {}", header.join(" "))?;
+        let top: Vec<String> = self
+            .top
+            .iter()
+            .map(|c| c.unwrap_or(' ').to_string())
+            .collect();
+        write!(f, "   {}", top.join(" "))?;
+        for (prefix, row) in self.prefixes.iter().zip(&self.rows) {
+            let cells: Vec<String> = row.iter().map(|c| c.to_string()).collect();
+            write!(f, "\n{}  {}", prefix, cells.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+// Reads a digit string, ignoring whitespace.
+pub fn parse_digits(text: &str) -> Result<Vec<u8>, String> {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| {
+            c.to_digit(10)
+                .map(|d| d as u8)
+                .ok_or(format!("'{}' is not a digit", c))
+        })
+        .collect()
+}
+
+pub fn digits_to_string(digits: &[u8]) -> String {
+    digits.iter().map(|&d| (b'0' + d) as char).collect()
+}
@@ -0,0 +1,115 @@
+// src/contfrac.rs
+//
+// Continued fractions, and Wiener's attack on RSA with a small private exponent. The
+// expansion of a/b is the sequence of quotients in Euclid's algorithm, and its
+// convergents h/k are the best rational approximations to a/b. Since ed = 1 + k phi(n)
+// and phi(n) is close to n, e/n is close to k/d; when d < n^(1/4)/3 (and q < p < 2q) it
+// is so close that k/d is one of the convergents of e/n, and each of them can be tried.
+// Boneh and Durfee's lattice attack reaches further, to d < n^0.292, but needs lattice
+// reduction, so only its bound is checked here.
+
+use crate::bigint::BigUint;
+use crate::Unsigned;
+
+pub const BONEH_DURFEE_EXPONENT: f64 = 0.292;
+
+// The partial quotients [a0; a1, a2, ...] of a/b, for b > 0.
+pub fn expansion<T: Unsigned>(a: T, b: T) -> Vec<T> {
+    let (mut a, mut b) = (a, b);
+    let mut quotients = vec![];
+    while !b.is_zero() {
+        quotients.push(a.clone() / b.clone());
+        let r = a % b.clone();
+        a = b;
+        b = r;
+    }
+    quotients
+}
+
+// The convergents h_i/k_i of [a0; a1, ...], each in lowest terms, from the recurrence
+// h_i = a_i h_(i-1) + h_(i-2) and the same for k, starting from 1/0 and 0/1.
+pub fn convergents<T: Unsigned>(quotients: &[T]) -> Vec<(T, T)> {
+    let (mut h, mut h_prev) = (T::one(), T::zero());
+    let (mut k, mut k_prev) = (T::zero(), T::one());
+    let mut result = vec![];
+    for a in quotients {
+        (h, h_prev) = (a.clone() * h.clone() + h_prev, h);
+        (k, k_prev) = (a.clone() * k.clone() + k_prev, k);
+        result.push((h.clone(), k.clone()));
+    }
+    result
+}
+
+// "[a0; a1, a2]"
+pub fn format_expansion<T: Unsigned>(quotients: &[T]) -> String {
+    let rest: Vec<String> = quotients.iter().skip(1).map(T::to_string).collect();
+    match quotients.first() {
+        None => "[]".to_string(),
+        Some(a0) if rest.is_empty() => format!("[{}]", a0),
+        Some(a0) => format!("[{}; {}]", a0, rest.join(", ")),
+    }
+}
+
+// What Wiener's attack recovers: the convergent k/d of e/n that worked, phi(n) and the
+// two primes.
+pub struct WienerKey {
+    pub k: BigUint,
+    pub d: BigUint,
+    pub phi: BigUint,
+    pub p: BigUint,
+    pub q: BigUint,
+}
+
+// Tries each convergent k/d of e/n. For the right one, phi = (ed - 1) / k is exact, and
+// p and q are the roots of x^2 - (n - phi + 1) x + n, so the discriminant must be a
+// perfect square whose roots multiply to n.
+pub fn wiener_attack(e: &BigUint, n: &BigUint) -> Option<WienerKey> {
+    let two = BigUint::from(2);
+    for (k, d) in convergents(&expansion(e.clone(), n.clone())) {
+        if k.is_zero() {
+            continue;
+        }
+        let (phi, remainder) = (e.clone() * d.clone() - BigUint::one()).div_rem(&k);
+        if !remainder.is_zero() || phi >= *n {
+            continue;
+        }
+        let s = n.clone() + BigUint::one() - phi.clone();
+        let square = s.clone() * s.clone();
+        let four_n = n.clone() << 2;
+        if square < four_n {
+            continue;
+        }
+        let discriminant = square - four_n;
+        let t = discriminant.sqrt();
+        if t.clone() * t.clone() != discriminant || !(s.clone() + t.clone()).is_even() {
+            continue;
+        }
+        let p = (s.clone() + t.clone()) / two.clone();
+        let q = (s - t) / two.clone();
+        if p.clone() * q.clone() == *n {
+            return Some(WienerKey { k, d, phi, p, q });
+        }
+    }
+    None
+}
+
+// Whether d < n^(1/4)/3, Wiener's bound, checked exactly as (3d)^4 < n.
+pub fn within_wiener_bound(d: &BigUint, n: &BigUint) -> bool {
+    let three_d = d.clone() * BigUint::from(3);
+    let square = three_d.clone() * three_d;
+    square.clone() * square < *n
+}
+
+// Whether d < n^0.292, Boneh and Durfee's bound. The bound is itself only asymptotic,
+// so logarithms are precise enough.
+pub fn within_boneh_durfee_bound(d: &BigUint, n: &BigUint) -> bool {
+    d.log2() < BONEH_DURFEE_EXPONENT * n.log2()
+}
+
+// log2 of the two bounds for n, for reporting them.
+pub fn small_exponent_bounds(n: &BigUint) -> (f64, f64) {
+    (
+        n.log2() / 4.0 - 3f64.log2(),
+        BONEH_DURFEE_EXPONENT * n.log2(),
+    )
+}
@@ -0,0 +1,311 @@
+// src/cribdrag.rs
+//
+// Crib dragging against a reused keystream. When two messages are enciphered with the
+// same key bytes, XORing the ciphertexts cancels the key and leaves the XOR of the two
+// plaintexts. A guessed word (the crib) placed at some position in one message then
+// recovers the key bytes there, and with them the other messages at the same position;
+// where the fragments that appear read as English, the guess is probably right. The
+// workbench keeps the key bytes accepted so far and shows every message through them.
+
+use crate::encoding::Encoding;
+use crate::ngram::NgramModel;
+use crate::xor::english_score;
+use std::io::{self, BufRead, Write};
+
+// A crib placed in one message, with what it makes of every message at that position.
+pub struct Placement {
+    pub message: usize,
+    pub position: usize,
+    pub score: f64,
+    // The fragment of each message, empty where a message ends before the position.
+    pub fragments: Vec<Vec<u8>>,
+}
+
+// How English a fragment reads, or None if it has bytes that text would not.
+fn fragment_score(fragment: &[u8]) -> Option<f64> {
+    if !fragment.iter().all(|b| (0x20..0x7f).contains(b)) {
+        return None;
+    }
+    let letters: Vec<u8> = fragment
+        .iter()
+        .filter(|b| b.is_ascii_alphabetic())
+        .map(|b| b.to_ascii_lowercase() - b'a')
+        .collect();
+    // Bigrams rather than longer n-grams, since fragments are only as long as a crib.
+    let bigrams = if letters.len() >= 2 {
+        NgramModel::english(2).score_numbers(&letters) / (letters.len() - 1) as f64
+    } else {
+        -3.0
+    };
+    Some(english_score(fragment) + bigrams)
+}
+
+fn printable(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&b| {
+            if (0x20..0x7f).contains(&b) {
+                b as char
+            } else {
+                '.'
+            }
+        })
+        .collect()
+}
+
+pub struct Workbench {
+    ciphertexts: Vec<Vec<u8>>,
+    key: Vec<Option<u8>>,
+    history: Vec<Vec<Option<u8>>>,
+}
+
+impl Workbench {
+    pub fn new(ciphertexts: Vec<Vec<u8>>) -> Result<Workbench, String> {
+        if ciphertexts.len() < 2 {
+            return Err("Crib dragging needs at least two ciphertexts".to_string());
+        }
+        let length = ciphertexts.iter().map(Vec::len).max().unwrap();
+        Ok(Workbench {
+            ciphertexts,
+            key: vec![None; length],
+            history: vec![],
+        })
+    }
+
+    // Reads one hex ciphertext per line, skipping blank lines.
+    pub fn parse(text: &str) -> Result<Workbench, String> {
+        let ciphertexts = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(number, line)| {
+                Encoding::Hex
+                    .decode_bytes(line.trim(), true)
+                    .map_err(|e| format!("Line {}: {}", number + 1, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Workbench::new(ciphertexts)
+    }
+
+    // Every placement of the crib in every message, best first. A placement is kept only
+    // if every other message it reaches comes out as printable text.
+    pub fn drag(&self, crib: &[u8]) -> Vec<Placement> {
+        let mut placements = vec![];
+        if crib.is_empty() {
+            return placements;
+        }
+        for (message, ciphertext) in self.ciphertexts.iter().enumerate() {
+            if crib.len() > ciphertext.len() {
+                continue;
+            }
+            for position in 0..=ciphertext.len() - crib.len() {
+                let key: Vec<u8> = crib
+                    .iter()
+                    .zip(&ciphertext[position..])
+                    .map(|(p, c)| p ^ c)
+                    .collect();
+                let fragments: Vec<Vec<u8>> = self
+                    .ciphertexts
+                    .iter()
+                    .map(|other| {
+                        other
+                            .iter()
+                            .skip(position)
+                            .zip(&key)
+                            .map(|(c, k)| c ^ k)
+                            .collect()
+                    })
+                    .collect();
+                let scores: Option<Vec<f64>> = fragments
+                    .iter()
+                    .enumerate()
+                    .filter(|(other, fragment)| *other != message && !fragment.is_empty())
+                    .map(|(_, fragment)| fragment_score(fragment))
+                    .collect();
+                match scores {
+                    Some(scores) if !scores.is_empty() => placements.push(Placement {
+                        message,
+                        position,
+                        score: scores.iter().sum::<f64>() / scores.len() as f64,
+                        fragments,
+                    }),
+                    _ => {}
+                }
+            }
+        }
+        placements.sort_by(|a, b| b.score.total_cmp(&a.score));
+        placements
+    }
+
+    // Takes `plaintext` as known at `position` in `message`, fixing the key bytes there.
+    pub fn place(
+        &mut self,
+        message: usize,
+        position: usize,
+        plaintext: &[u8],
+    ) -> Result<(), String> {
+        let ciphertext = self
+            .ciphertexts
+            .get(message)
+            .ok_or(format!("There is no message {}", message + 1))?;
+        if position + plaintext.len() > ciphertext.len() {
+            return Err(format!(
+                "Message {} has only {} bytes",
+                message + 1,
+                ciphertext.len()
+            ));
+        }
+        self.history.push(self.key.clone());
+        for (i, p) in plaintext.iter().enumerate() {
+            self.key[position + i] = Some(p ^ ciphertext[position + i]);
+        }
+        Ok(())
+    }
+
+    // Forgets the most recent placement, returning false if there was none.
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some(key) => {
+                self.key = key;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Each message as far as the key is known, with '_' for bytes not yet recovered and
+    // '.' for recovered bytes that are not printable.
+    pub fn plaintexts(&self) -> Vec<String> {
+        self.ciphertexts
+            .iter()
+            .map(|ciphertext| {
+                ciphertext
+                    .iter()
+                    .zip(&self.key)
+                    .map(|(c, k)| match k {
+                        Some(k) => printable(&[c ^ k]).chars().next().unwrap(),
+                        None => '_',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // The key in hex, with "??" for bytes not yet recovered.
+    pub fn key_hex(&self) -> String {
+        self.key
+            .iter()
+            .map(|k| match k {
+                Some(k) => format!("{:02x}", k),
+                None => "??".to_string(),
+            })
+            .collect()
+    }
+
+    // Runs the workbench on commands read a line at a time:
+    //   drag <crib>          lists the best placements of the crib
+    //   accept <n>           accepts placement n of the last drag
+    //   set <m> <pos> <text> takes text as known at a position of message m
+    //   undo, show, key, help, quit
+    // Messages and placements are numbered from 1 and positions from 0.
+    pub fn interactive(
+        &mut self,
+        input: impl BufRead,
+        output: &mut impl Write,
+        top: usize,
+    ) -> io::Result<()> {
+        let mut last: Vec<Placement> = vec![];
+        self.show(output)?;
+        for line in input.lines() {
+            let line = line?;
+            let (command, rest) = line
+                .trim_start()
+                .split_once(' ')
+                .unwrap_or((line.trim(), ""));
+            match command {
+                "" => continue,
+                "drag" => {
+                    last = self.drag(rest.as_bytes());
+                    last.truncate(top);
+                    if last.is_empty() {
+                        writeln!(output, "No placement gives printable text")?;
+                    }
+                    write_placements(output, &last)?;
+                }
+                "accept" => {
+                    let chosen = rest
+                        .trim()
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|n| n.checked_sub(1))
+                        .and_then(|n| last.get(n));
+                    match chosen {
+                        Some(p) => {
+                            let crib = p.fragments[p.message].clone();
+                            self.place(p.message, p.position, &crib)
+                                .map_err(io::Error::other)?;
+                            self.show(output)?;
+                        }
+                        None => writeln!(output, "Error: No placement '{}'", rest.trim())?,
+                    }
+                }
+                "set" => {
+                    let mut fields = rest.splitn(3, ' ');
+                    let message = fields.next().and_then(|m| m.parse::<usize>().ok());
+                    let position = fields.next().and_then(|p| p.parse::<usize>().ok());
+                    let result = match (message, position, fields.next()) {
+                        (Some(m), Some(p), Some(text)) if m > 0 => {
+                            self.place(m - 1, p, text.as_bytes())
+                        }
+                        _ => Err("Use: set <message> <position> <text>".to_string()),
+                    };
+                    match result {
+                        Ok(()) => self.show(output)?,
+                        Err(e) => writeln!(output, "Error: {}", e)?,
+                    }
+                }
+                "undo" => {
+                    if self.undo() {
+                        self.show(output)?;
+                    } else {
+                        writeln!(output, "Nothing to undo")?;
+                    }
+                }
+                "show" => self.show(output)?,
+                "key" => writeln!(output, "{}", self.key_hex())?,
+                "help" => writeln!(
+                    output,
+                    "drag <crib> | accept <n> | set <message> <position> <text> | undo | show | key | quit"
+                )?,
+                "quit" | "exit" => break,
+                _ => writeln!(output, "Error: Unknown command '{}'", command)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn show(&self, output: &mut impl Write) -> io::Result<()> {
+        for (i, plaintext) in self.plaintexts().iter().enumerate() {
+            writeln!(output, "{}: {}", i + 1, plaintext)?;
+        }
+        Ok(())
+    }
+}
+
+// Numbered placements, each followed by the fragment it gives in every message.
+pub fn write_placements(output: &mut impl Write, placements: &[Placement]) -> io::Result<()> {
+    for (rank, placement) in placements.iter().enumerate() {
+        writeln!(
+            output,
+            "{}. {:.3} message {} at {}",
+            rank + 1,
+            placement.score,
+            placement.message + 1,
+            placement.position
+        )?;
+        for (i, fragment) in placement.fragments.iter().enumerate() {
+            writeln!(output, "   {}: {}", i + 1, printable(fragment))?;
+        }
+    }
+    Ok(())
+}
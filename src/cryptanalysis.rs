@@ -1,102 +1,195 @@
-// src/analysis.rs
+// src/cryptanalysis.rs
 //
 
-use std::collections::HashMap;
+use crate::json::Value;
+use crate::vigenere_cracker::english_letter_frequencies;
+use std::fmt;
 
-fn index_of_coincidence(text: &str) -> f64 {
-    let mut frequency_map: HashMap<char, usize> = HashMap::new();
+// How often each letter A-Z appears, ignoring case and anything that is not a letter.
+pub fn letter_counts(text: &str) -> [usize; 26] {
+    let mut counts = [0usize; 26];
 
     for ch in text.chars() {
         if ch.is_ascii_alphabetic() {
-            *frequency_map.entry(ch.to_ascii_uppercase()).or_insert(0) += 1;
+            counts[(ch.to_ascii_uppercase() as u8 - b'A') as usize] += 1;
         }
     }
 
-    let total_chars = text.chars().filter(|ch| ch.is_ascii_alphabetic()).count() as f64;
-    let sum_of_products: f64 = frequency_map
+    counts
+}
+
+pub fn index_of_coincidence(text: &str) -> f64 {
+    index_of_coincidence_of_counts(&letter_counts(text))
+}
+
+// The index of coincidence from letter counts, for callers that already have them.
+pub fn index_of_coincidence_of_counts(counts: &[usize]) -> f64 {
+    let total_chars = counts.iter().sum::<usize>() as f64;
+    let sum_of_products: f64 = counts
         .iter()
-        .map(|(_, &count)| count as f64 * (count as f64 - 1.0))
+        .map(|&count| count as f64 * (count as f64 - 1.0))
         .sum();
 
     sum_of_products / (total_chars * (total_chars - 1.0))
 }
 
-pub fn frequency_analysis(ciphertext: &str) {
-    let text = ciphertext
-        .chars()
-        .filter(|&ch| ch.is_alphabetic() && ch != '\n')
-        .collect::<String>();
-
-    let total_chars = text.chars().count() as f64;
-    let mut frequency_map: HashMap<char, usize> = HashMap::new();
+// Pearson's chi-squared statistic of letter counts against English. Lower is closer.
+pub fn chi_squared(counts: &[usize; 26]) -> f64 {
+    let total = counts.iter().sum::<usize>() as f64;
+    counts
+        .iter()
+        .zip(english_letter_frequencies())
+        .map(|(&count, &frequency)| {
+            let expected = total * frequency;
+            (count as f64 - expected).powi(2) / expected
+        })
+        .sum()
+}
 
-    for ch in text.chars() {
-        let count = frequency_map.entry(ch).or_insert(0);
-        *count += 1;
-    }
+// Letter statistics of a ciphertext. Letters are counted without regard to case, and
+// ranks are shared by tied letters, so two equally common letters are both rank 1.
+// Letters that do not occur have no rank.
+pub struct FrequencyReport {
+    pub counts: [usize; 26],
+    pub total: usize,
+    pub frequencies: [f64; 26],
+    pub ranks: [Option<usize>; 26],
+    pub ioc: f64,
+    // Shannon entropy in bits per letter.
+    pub entropy: f64,
+    // Against English letter frequencies.
+    pub chi_squared: f64,
+}
 
-    let mut frequency_vec: Vec<(char, f64)> = frequency_map
+pub fn frequency_analysis(ciphertext: &str) -> FrequencyReport {
+    let counts = letter_counts(ciphertext);
+    let total = counts.iter().sum::<usize>();
+    let frequencies = counts.map(|count| count as f64 / total.max(1) as f64);
+    let ranks =
+        counts.map(|count| (count > 0).then(|| 1 + counts.iter().filter(|&&c| c > count).count()));
+    // Summed from +0, as an empty sum of floats is -0.
+    let entropy = frequencies
         .iter()
-        .map(|(&ch, &count)| (ch, count as f64 / total_chars))
-        .collect();
+        .filter(|&&p| p > 0.0)
+        .fold(0.0, |entropy, &p| entropy - p * p.log2());
+
+    FrequencyReport {
+        counts,
+        total,
+        frequencies,
+        ranks,
+        ioc: index_of_coincidence_of_counts(&counts),
+        entropy,
+        chi_squared: chi_squared(&counts),
+    }
+}
 
-    let highest_freq = frequency_vec
-        .iter()
-        .map(|(_, freq)| freq)
-        .cloned()
-        .fold(f64::MIN, f64::max);
+fn letter(i: usize) -> char {
+    (b'a' + i as u8) as char
+}
 
-    let most_common_letters: Vec<&char> = frequency_vec
-        .iter()
-        .filter(|(_, freq)| (freq - highest_freq).abs() < f64::EPSILON)
-        .map(|(ch, _)| ch)
-        .collect();
+impl FrequencyReport {
+    // The letters tied for the highest count, in alphabetical order.
+    pub fn most_common(&self) -> Vec<char> {
+        (0..26)
+            .filter(|&i| self.ranks[i] == Some(1))
+            .map(letter)
+            .collect()
+    }
 
-    let lowest_freq = frequency_vec
-        .iter()
-        .map(|(_, freq)| freq)
-        .cloned()
-        .fold(f64::MAX, f64::min);
+    // The letters that appear, least common first, ties in alphabetical order.
+    pub fn least_common(&self) -> Vec<char> {
+        let mut present: Vec<usize> = (0..26).filter(|&i| self.counts[i] > 0).collect();
+        present.sort_by_key(|&i| self.counts[i]);
+        present.into_iter().map(letter).collect()
+    }
 
-    let least_common_letters: Vec<&char> = frequency_vec
-        .iter()
-        .filter(|(_, freq)| (freq - lowest_freq).abs() < f64::EPSILON)
-        .map(|(ch, _)| ch)
-        .collect();
+    pub fn to_json(&self) -> String {
+        let number = |x: f64| Value::Number(x);
+        let letters = (0..26)
+            .map(|i| {
+                Value::Object(vec![
+                    ("letter".to_string(), Value::String(letter(i).to_string())),
+                    ("count".to_string(), number(self.counts[i] as f64)),
+                    ("frequency".to_string(), number(self.frequencies[i])),
+                    (
+                        "rank".to_string(),
+                        self.ranks[i].map_or(Value::Null, |rank| number(rank as f64)),
+                    ),
+                ])
+            })
+            .collect();
+        let most_common = self
+            .most_common()
+            .iter()
+            .map(|c| Value::String(c.to_string()))
+            .collect();
+        Value::Object(vec![
+            ("total".to_string(), number(self.total as f64)),
+            ("ioc".to_string(), number(self.ioc)),
+            ("entropy".to_string(), number(self.entropy)),
+            ("chi_squared".to_string(), number(self.chi_squared)),
+            ("most_common".to_string(), Value::Array(most_common)),
+            ("letters".to_string(), Value::Array(letters)),
+        ])
+        .to_string()
+    }
 
-    frequency_vec.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    // One row per letter, A to Z.
+    pub fn to_csv(&self) -> String {
+        let mut csv = "letter,count,frequency,rank\n".to_string();
+        for i in 0..26 {
+            csv += &format!(
+                "{},{},{:.5},{}\n",
+                letter(i),
+                self.counts[i],
+                self.frequencies[i],
+                self.ranks[i].map_or(String::new(), |rank| rank.to_string())
+            );
+        }
+        csv
+    }
+}
 
-    let least_common1 = frequency_vec.get(0).unwrap();
-    let least_common2 = frequency_vec.get(1).unwrap();
-    let highest_freq = frequency_vec.last().unwrap().1;
+// The original printout of the analysis.
+impl fmt::Display for FrequencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let least_common = self.least_common();
+        let frequency = |c: char| self.frequencies[(c as u8 - b'a') as usize];
+        match least_common[..] {
+            [] => return writeln!(f, "The ciphertext given below has no letters."),
+            [only] => writeln!(
+                f,
+                "For the ciphertext given below, the only letter is {} with a frequency of {:.3}.",
+                only,
+                frequency(only)
+            )?,
+            [first, second, ..] => writeln!(
+                f,
+                "For the ciphertext given below, the least common letters are {} and {} with a frequency of {:.3} and {:.3}.",
+                first,
+                second,
+                frequency(first),
+                frequency(second)
+            )?,
+        }
 
-    let most_common_letters: Vec<&char> = frequency_vec
-        .iter()
-        .filter(|(_, freq)| (freq - highest_freq).abs() < f64::EPSILON)
-        .map(|(ch, _)| ch)
-        .collect();
-
-    println!(
-        "For the ciphertext given below, the least common letters are {} and {} with a frequency of {:.3} and {:.3}.",
-        least_common1.0, least_common2.0, least_common1.1, least_common2.1
-    );
-
-    println!(
-        "There are {} letters tied for the most-frequent, with a frequency of {:.3}.",
-        most_common_letters.len(),
-        highest_freq
-    );
-    println!(
-        "One of the most frequent letters is {}.",
-        most_common_letters[0]
-    );
-    println!(
-        "The index of coincidence is {:.3}",
-        index_of_coincidence(ciphertext)
-    )
+        let most_common = self.most_common();
+        writeln!(
+            f,
+            "There are {} letters tied for the most-frequent, with a frequency of {:.3}.",
+            most_common.len(),
+            frequency(most_common[0])
+        )?;
+        writeln!(f, "One of the most frequent letters is {}.", most_common[0])?;
+        writeln!(f, "The index of coincidence is {:.3}", self.ioc)
+    }
 }
+
 use crate::ceasar::decrypt;
 
+// Not wired to a mode.
+#[allow(dead_code)]
 pub fn brute_force_caesar(ciphertext: &str) {
     println!("Brute Force Caesar Cipher Decryption:");
 
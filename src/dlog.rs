@@ -0,0 +1,295 @@
+// src/dlog.rs
+//
+// Discrete logarithms: given g and h = g^x in a cyclic group, find x. Baby-step
+// giant-step meets in the middle with a table of sqrt(m) powers for a group of order m;
+// Pollard's rho takes as long but needs no table; and Pohlig-Hellman reduces the
+// problem to the prime-power parts of m, so a group whose order has only small prime
+// factors is weak however large it is. That is the attack on Diffie-Hellman parameters
+// where p - 1 is smooth.
+
+use crate::bigint::BigUint;
+use crate::factor::prime_factors;
+use crate::number_theory::{carmichael_lambda, crt, mod_pow, order_dividing};
+use crate::rng::Rng;
+use crate::{gcd, mod_inverse, Unsigned};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+
+// A group written multiplicatively. The algorithms are told the order of g rather than
+// working it out.
+pub trait Group {
+    type Element: Clone + Eq + Hash + Debug;
+
+    fn identity(&self) -> Self::Element;
+    fn operate(&self, a: &Self::Element, b: &Self::Element) -> Self::Element;
+
+    fn power(&self, a: &Self::Element, exponent: &BigUint) -> Self::Element {
+        let mut result = self.identity();
+        for i in (0..exponent.bits()).rev() {
+            result = self.operate(&result, &result);
+            if exponent.bit(i) {
+                result = self.operate(&result, a);
+            }
+        }
+        result
+    }
+}
+
+// The units mod n under multiplication.
+pub struct MultiplicativeGroup {
+    pub modulus: BigUint,
+}
+
+impl Group for MultiplicativeGroup {
+    type Element = BigUint;
+
+    fn identity(&self) -> BigUint {
+        BigUint::one() % self.modulus.clone()
+    }
+
+    fn operate(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a.mul_mod(b, &self.modulus)
+    }
+
+    fn power(&self, a: &BigUint, exponent: &BigUint) -> BigUint {
+        mod_pow(a.clone(), exponent.clone(), self.modulus.clone())
+    }
+}
+
+// The integers mod n under addition, where the "logarithm" is a division: a reminder
+// that the problem is only hard in the right group.
+pub struct AdditiveGroup {
+    pub modulus: BigUint,
+}
+
+impl Group for AdditiveGroup {
+    type Element = BigUint;
+
+    fn identity(&self) -> BigUint {
+        BigUint::zero()
+    }
+
+    fn operate(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a.clone() + b.clone()) % self.modulus.clone()
+    }
+
+    fn power(&self, a: &BigUint, exponent: &BigUint) -> BigUint {
+        a.mul_mod(&(exponent.clone() % self.modulus.clone()), &self.modulus)
+    }
+}
+
+// g^-x, as g^(m - x) for g of order m.
+fn inverse_power<G: Group>(group: &G, g: &G::Element, x: &BigUint, order: &BigUint) -> G::Element {
+    let x = x.clone() % order.clone();
+    group.power(g, &(order.clone() - x))
+}
+
+// The most baby steps the table is allowed, which covers orders up to 2^48.
+const MAX_BABY_STEPS: u64 = 1 << 24;
+
+// x in 0..order with g^x = h, from a table of g^j for j below m = ceil(sqrt(order)) and
+// giant steps h g^(-im). None also when the order is 0 or too large for the table.
+pub fn baby_step_giant_step<G: Group>(
+    group: &G,
+    g: &G::Element,
+    h: &G::Element,
+    order: &BigUint,
+) -> Option<BigUint> {
+    if order.is_zero() {
+        return None;
+    }
+    let mut m = order.sqrt();
+    if m.clone() * m.clone() < *order {
+        m = m + BigUint::one();
+    }
+    let steps = m.to_u64().filter(|&steps| steps <= MAX_BABY_STEPS)?;
+    let mut table = HashMap::new();
+    let mut baby = group.identity();
+    for j in 0..steps {
+        table.entry(baby.clone()).or_insert(j);
+        baby = group.operate(&baby, g);
+    }
+    let giant = inverse_power(group, g, &m, order);
+    let mut gamma = h.clone();
+    for i in 0..steps {
+        if let Some(&j) = table.get(&gamma) {
+            let x = BigUint::from(i) * m.clone() + BigUint::from(j);
+            if x < *order {
+                return Some(x);
+            }
+        }
+        gamma = group.operate(&gamma, &giant);
+    }
+    None
+}
+
+// Which of the three moves Pollard's walk makes from an element.
+fn partition<E: Hash>(a: &E) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    a.hash(&mut hasher);
+    hasher.finish() % 3
+}
+
+// Pollard's rho for logarithms: a walk through elements g^a h^b that multiplies by g,
+// squares or multiplies by h depending on the element, so it soon repeats. Floyd's
+// method finds a repeat g^a1 h^b1 = g^a2 h^b2, and then x (b2 - b1) = a1 - a2 modulo the
+// order. Each of the few solutions of that is checked; a walk that gives nothing, or
+// finds no repeat within `max_steps` steps, is restarted from another random point, up
+// to `attempts` times.
+pub fn pollard_rho_log<G: Group>(
+    group: &G,
+    g: &G::Element,
+    h: &G::Element,
+    order: &BigUint,
+    attempts: usize,
+    max_steps: u64,
+    rng: &mut Rng,
+) -> Option<BigUint> {
+    let n = order.clone();
+    let add = |x: &BigUint, y: &BigUint| (x.clone() + y.clone()) % n.clone();
+    let step = |(x, a, b): &(G::Element, BigUint, BigUint)| match partition(x) {
+        0 => (group.operate(x, g), add(a, &BigUint::one()), b.clone()),
+        1 => (group.operate(x, x), add(a, a), add(b, b)),
+        _ => (group.operate(x, h), a.clone(), add(b, &BigUint::one())),
+    };
+    for _ in 0..attempts {
+        let a = BigUint::random_below(&n, rng);
+        let b = BigUint::random_below(&n, rng);
+        let start = group.operate(&group.power(g, &a), &group.power(h, &b));
+        let mut slow = (start, a, b);
+        let mut fast = step(&slow);
+        let mut steps = 0;
+        while slow.0 != fast.0 && steps < max_steps {
+            slow = step(&slow);
+            fast = step(&step(&fast));
+            steps += 1;
+        }
+        if slow.0 != fast.0 {
+            continue;
+        }
+        // x r = s (mod n), with r = b2 - b1 and s = a1 - a2.
+        let r = (fast.2 + n.clone() - slow.2) % n.clone();
+        let s = (slow.1 + n.clone() - fast.1) % n.clone();
+        let d = gcd(r.clone(), n.clone());
+        if r.is_zero() || !(s.clone() % d.clone()).is_zero() {
+            continue;
+        }
+        let reduced = n.clone() / d.clone();
+        let Some(inverse) = mod_inverse(r / d.clone(), reduced.clone()) else {
+            continue;
+        };
+        let x0 = (s / d.clone()).mul_mod(&inverse, &reduced);
+        // Only the d lifts x0 + k n/d can be the answer; d is small for a random walk.
+        let lifts = d.to_u64().unwrap_or(0).min(1 << 16);
+        for k in 0..lifts {
+            let x = x0.clone() + BigUint::from(k) * reduced.clone();
+            if group.power(g, &x) == *h {
+                return Some(x);
+            }
+        }
+    }
+    None
+}
+
+// Rho is run from up to this many starting points, for this many steps each: a repeat
+// comes after about sqrt(q) steps, so this reaches a prime order q of MAX_LOG_PRIME_BITS.
+const RHO_ATTEMPTS: usize = 4;
+const RHO_STEPS: u64 = 1 << 23;
+pub const MAX_LOG_PRIME_BITS: u64 = 44;
+
+// The logarithm modulo q for g of prime order q, with baby-step giant-step while its
+// table is small and Pollard's rho beyond, giving up past about 2^44.
+fn log_prime_order<G: Group>(
+    group: &G,
+    g: &G::Element,
+    h: &G::Element,
+    q: &BigUint,
+) -> Option<BigUint> {
+    if q.bits() <= 40 {
+        baby_step_giant_step(group, g, h, q)
+    } else {
+        pollard_rho_log(group, g, h, q, RHO_ATTEMPTS, RHO_STEPS, &mut Rng::new(1))
+    }
+}
+
+// The logarithm modulo each prime power q^e of the order of g, as (x mod q^e, q^e).
+// Raising g and h to the power order / q^e moves the problem into the subgroup of order
+// q^e, where x is found one base-q digit at a time, each by a logarithm in the subgroup
+// of order q. None if h is not a power of g, or a prime q is too large for its logarithm.
+pub fn pohlig_hellman_residues<G: Group>(
+    group: &G,
+    g: &G::Element,
+    h: &G::Element,
+    order_factors: &[(BigUint, u32)],
+) -> Option<Vec<(BigUint, BigUint)>> {
+    let order = order_factors.iter().fold(BigUint::one(), |m, (q, e)| {
+        m * (0..*e).fold(BigUint::one(), |a, _| a * q.clone())
+    });
+    let mut residues = vec![];
+    for (q, e) in order_factors {
+        let qe = (0..*e).fold(BigUint::one(), |a, _| a * q.clone());
+        let cofactor = order.clone() / qe.clone();
+        let g_q = group.power(g, &cofactor);
+        let h_q = group.power(h, &cofactor);
+        // gamma generates the subgroup of order q.
+        let gamma = group.power(&g_q, &(qe.clone() / q.clone()));
+        let mut x = BigUint::zero();
+        let mut q_k = BigUint::one();
+        for _ in 0..*e {
+            // (g_q^-x h_q)^(q^(e-1-k)) = gamma^(digit k)
+            let shifted = group.operate(&inverse_power(group, &g_q, &x, &qe), &h_q);
+            let exponent = qe.clone() / q_k.clone() / q.clone();
+            let target = group.power(&shifted, &exponent);
+            let digit = log_prime_order(group, &gamma, &target, q)?;
+            x = x + digit * q_k.clone();
+            q_k = q_k * q.clone();
+        }
+        residues.push((x, qe));
+    }
+    Some(residues)
+}
+
+// x in 0..order with g^x = h, given the factorization of the order of g, combining the
+// prime-power residues by the Chinese remainder theorem.
+pub fn pohlig_hellman<G: Group>(
+    group: &G,
+    g: &G::Element,
+    h: &G::Element,
+    order_factors: &[(BigUint, u32)],
+) -> Option<BigUint> {
+    let residues = pohlig_hellman_residues(group, g, h, order_factors)?;
+    let (x, _) = crt(&residues)?;
+    (group.power(g, &x) == *h).then_some(x)
+}
+
+// The order of g as a unit mod n, with its factorization, or an error if g is not a
+// unit or one of n, lambda(n) and the order is too hard to factor.
+pub fn unit_order(g: &BigUint, n: &BigUint) -> Result<(BigUint, Vec<(BigUint, u32)>), String> {
+    if n.is_zero() || gcd(g.clone(), n.clone()) != BigUint::one() {
+        return Err(format!("{} is not a unit modulo {}", g, n));
+    }
+    let lambda = carmichael_lambda(&prime_factors(n)?);
+    let order = order_dividing(
+        &(g.clone() % n.clone()),
+        n,
+        &lambda,
+        &prime_factors(&lambda)?,
+    );
+    let factors = prime_factors(&order)?;
+    Ok((order, factors))
+}
+
+// The smallest x with g^x = h mod n, or None if h is not a power of g or the order of g
+// has a prime factor beyond reach.
+pub fn discrete_log(g: &BigUint, h: &BigUint, n: &BigUint) -> Option<BigUint> {
+    let (_, factors) = unit_order(g, n).ok()?;
+    let group = MultiplicativeGroup { modulus: n.clone() };
+    pohlig_hellman(
+        &group,
+        &(g.clone() % n.clone()),
+        &(h.clone() % n.clone()),
+        &factors,
+    )
+}
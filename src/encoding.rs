@@ -0,0 +1,499 @@
+// src/encoding.rs
+//
+// Reversible text encodings: letter numbers, code points, the usual binary-to-text
+// formats and the two MIME transfer encodings. Every encoding works on the UTF-8 bytes
+// of the text (or its letters or characters, where that is the unit). Strict decoders
+// accept only the canonical form that `encode` produces, give or take letter case;
+// lenient ones also take the separators, prefixes and missing padding found in the
+// wild.
+
+use crate::ceasar::{letter_to_number, number_to_letter};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+    A0Z25,
+    A1Z26,
+    CodePoints,
+    Binary,
+    Octal,
+    Hex,
+    Base32,
+    Base58,
+    Base64,
+    Base64Url,
+    Percent,
+    QuotedPrintable,
+}
+
+const NAMES: [(Encoding, &str); 12] = [
+    (Encoding::A0Z25, "a0z25"),
+    (Encoding::A1Z26, "a1z26"),
+    (Encoding::CodePoints, "decimal"),
+    (Encoding::Binary, "binary"),
+    (Encoding::Octal, "octal"),
+    (Encoding::Hex, "hex"),
+    (Encoding::Base32, "base32"),
+    (Encoding::Base58, "base58"),
+    (Encoding::Base64, "base64"),
+    (Encoding::Base64Url, "base64url"),
+    (Encoding::Percent, "url"),
+    (Encoding::QuotedPrintable, "qp"),
+];
+
+const BASE32: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const BASE58: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_URL: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+impl Encoding {
+    // Takes the names above, plus "ascii", "unicode", "percent" and "quoted-printable".
+    pub fn parse(name: &str) -> Result<Encoding, String> {
+        let name = name.to_ascii_lowercase();
+        let name = match name.as_str() {
+            "ascii" | "unicode" => "decimal",
+            "percent" => "url",
+            "quoted-printable" => "qp",
+            name => name,
+        };
+        NAMES
+            .iter()
+            .find(|(_, n)| *n == name)
+            .map(|(encoding, _)| *encoding)
+            .ok_or(format!("Unknown encoding '{}'", name))
+    }
+
+    pub fn name(self) -> &'static str {
+        NAMES.iter().find(|(e, _)| *e == self).unwrap().1
+    }
+
+    pub fn encode(self, text: &str) -> String {
+        let bytes = text.as_bytes();
+        match self {
+            Encoding::A0Z25 | Encoding::A1Z26 => {
+                let offset = (self == Encoding::A1Z26) as u8;
+                let numbers: Vec<String> = text
+                    .chars()
+                    .filter_map(letter_to_number)
+                    .map(|n| (n + offset).to_string())
+                    .collect();
+                numbers.join(" ")
+            }
+            Encoding::CodePoints => {
+                let numbers: Vec<String> = text.chars().map(|c| (c as u32).to_string()).collect();
+                numbers.join(" ")
+            }
+            Encoding::Binary => {
+                let groups: Vec<String> = bytes.iter().map(|b| format!("{:08b}", b)).collect();
+                groups.join(" ")
+            }
+            Encoding::Octal => {
+                let groups: Vec<String> = bytes.iter().map(|b| format!("{:03o}", b)).collect();
+                groups.join(" ")
+            }
+            Encoding::Hex => bytes.iter().map(|b| format!("{:02x}", b)).collect(),
+            Encoding::Base32 => encode_bits(bytes, 5, BASE32, 8),
+            Encoding::Base58 => encode_base58(bytes),
+            Encoding::Base64 => encode_bits(bytes, 6, BASE64, 4),
+            Encoding::Base64Url => encode_bits(bytes, 6, BASE64_URL, 1),
+            Encoding::Percent => bytes
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_alphanumeric() || b"-_.~".contains(&b) {
+                        (b as char).to_string()
+                    } else {
+                        format!("%{:02X}", b)
+                    }
+                })
+                .collect(),
+            Encoding::QuotedPrintable => encode_quoted_printable(text),
+        }
+    }
+
+    pub fn decode(self, text: &str, strict: bool) -> Result<String, String> {
+        match self {
+            Encoding::A0Z25 | Encoding::A1Z26 => {
+                let offset = (self == Encoding::A1Z26) as u32;
+                numbers(text, strict)?
+                    .iter()
+                    .map(|&n| {
+                        n.checked_sub(offset)
+                            .and_then(|n| u8::try_from(n).ok())
+                            .and_then(number_to_letter)
+                            .ok_or(format!("{} is out of range for {}", n, self.name()))
+                    })
+                    .collect()
+            }
+            Encoding::CodePoints => numbers(text, strict)?
+                .iter()
+                .map(|&n| char::from_u32(n).ok_or(format!("{} is not a Unicode code point", n)))
+                .collect(),
+            _ => to_text(self.decode_bytes(text, strict)?, strict),
+        }
+    }
+
+    // The raw bytes behind the text, for data that need not be UTF-8. The letter and
+    // code point encodings give the UTF-8 bytes of the text they decode to.
+    pub fn decode_bytes(self, text: &str, strict: bool) -> Result<Vec<u8>, String> {
+        match self {
+            Encoding::A0Z25 | Encoding::A1Z26 | Encoding::CodePoints => {
+                Ok(self.decode(text, strict)?.into_bytes())
+            }
+            Encoding::Binary => decode_radix(text, 2, 8, &["0b"], strict),
+            Encoding::Octal => decode_radix(text, 8, 3, &["0o", "\\"], strict),
+            Encoding::Hex => decode_hex(text, strict),
+            Encoding::Base32 => decode_bits(text, 5, BASE32, Some(8), strict),
+            Encoding::Base58 => decode_base58(text, strict),
+            Encoding::Base64 => decode_bits(text, 6, BASE64, Some(4), strict),
+            Encoding::Base64Url => decode_bits(text, 6, BASE64_URL, None, strict),
+            Encoding::Percent => decode_percent(text, strict),
+            Encoding::QuotedPrintable => decode_quoted_printable(text, strict),
+        }
+    }
+}
+
+fn to_text(bytes: Vec<u8>, strict: bool) -> Result<String, String> {
+    if strict {
+        String::from_utf8(bytes).map_err(|_| "The decoded bytes are not valid UTF-8".to_string())
+    } else {
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+// Decimal numbers separated by whitespace, or by anything but digits when lenient.
+fn numbers(text: &str, strict: bool) -> Result<Vec<u32>, String> {
+    let tokens: Vec<&str> = if strict {
+        text.split_whitespace().collect()
+    } else {
+        text.split(|c: char| !c.is_ascii_digit())
+            .filter(|t| !t.is_empty())
+            .collect()
+    };
+    tokens
+        .iter()
+        .map(|t| {
+            Some(t)
+                .filter(|t| t.chars().all(|c| c.is_ascii_digit()))
+                .and_then(|t| t.parse::<u32>().ok())
+                .ok_or(format!("'{}' is not a number", t))
+        })
+        .collect()
+}
+
+// Strips the given prefixes and common separators from lenient input.
+fn lenient_tokens(text: &str, prefixes: &[&str]) -> Vec<String> {
+    text.split(|c: char| c.is_whitespace() || ",;:-".contains(c))
+        .filter(|t| !t.is_empty())
+        .map(|t| {
+            let t = t.to_ascii_lowercase();
+            prefixes
+                .iter()
+                .find_map(|p| t.strip_prefix(p))
+                .unwrap_or(&t)
+                .to_string()
+        })
+        .collect()
+}
+
+// Whitespace-separated groups of exactly `width` digits when strict. Lenient input may
+// drop leading zeros, use prefixes and other separators, or run the groups together.
+fn decode_radix(
+    text: &str,
+    radix: u32,
+    width: usize,
+    prefixes: &[&str],
+    strict: bool,
+) -> Result<Vec<u8>, String> {
+    let mut tokens: Vec<String> = if strict {
+        text.split_whitespace().map(str::to_string).collect()
+    } else {
+        lenient_tokens(text, prefixes)
+    };
+    if !strict && tokens.len() == 1 && tokens[0].len() > width {
+        let run = tokens.remove(0);
+        if !run.len().is_multiple_of(width) {
+            return Err(format!("The digits do not divide into groups of {}", width));
+        }
+        tokens = run
+            .as_bytes()
+            .chunks(width)
+            .map(|c| String::from_utf8_lossy(c).into_owned())
+            .collect();
+    }
+    tokens
+        .iter()
+        .map(|t| {
+            if (strict && t.len() != width) || t.len() > width {
+                return Err(format!("'{}' is not a group of {} digits", t, width));
+            }
+            // from_str_radix would also take a leading '+'.
+            Some(t)
+                .filter(|t| t.chars().all(|c| c.is_digit(radix)))
+                .and_then(|t| u8::from_str_radix(t, radix).ok())
+                .ok_or(format!("'{}' is not a byte in base {}", t, radix))
+        })
+        .collect()
+}
+
+fn decode_hex(text: &str, strict: bool) -> Result<Vec<u8>, String> {
+    let digits: String = if strict {
+        text.to_string()
+    } else {
+        let text = text
+            .to_ascii_lowercase()
+            .replace("0x", " ")
+            .replace("\\x", " ");
+        lenient_tokens(&text, &[]).concat()
+    };
+    if let Some((i, c)) = digits.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid hex digit '{}' at position {}", c, i + 1));
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err("Hex needs an even number of digits".to_string());
+    }
+    Ok((0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).unwrap())
+        .collect())
+}
+
+// Base32 and base64: each character carries `bits` bits, and the output is padded with
+// '=' to a multiple of `block` characters.
+fn encode_bits(bytes: &[u8], bits: u32, alphabet: &[u8], block: usize) -> String {
+    let mut output = String::new();
+    let (mut buffer, mut held) = (0u32, 0u32);
+    for &b in bytes {
+        buffer = buffer << 8 | b as u32;
+        held += 8;
+        while held >= bits {
+            held -= bits;
+            output.push(alphabet[(buffer >> held) as usize & ((1 << bits) - 1)] as char);
+        }
+    }
+    if held > 0 {
+        output.push(alphabet[(buffer << (bits - held)) as usize & ((1 << bits) - 1)] as char);
+    }
+    while !output.len().is_multiple_of(block) {
+        output.push('=');
+    }
+    output
+}
+
+// Padding is optional when `block` is None, and otherwise required when strict. Where
+// present, strict decoding checks it is exactly what `encode_bits` would write.
+fn decode_bits(
+    text: &str,
+    bits: u32,
+    alphabet: &[u8],
+    block: Option<usize>,
+    strict: bool,
+) -> Result<Vec<u8>, String> {
+    let text: String = if strict {
+        text.to_string()
+    } else {
+        text.chars().filter(|c| !c.is_whitespace()).collect()
+    };
+    let data = text.trim_end_matches('=');
+    if strict {
+        // Characters per block: 8 for base32, 4 for base64.
+        let size = block.unwrap_or(if bits == 5 { 8 } else { 4 });
+        let needed = (size - data.len() % size) % size;
+        let padding = text.len() - data.len();
+        if (padding > 0 || block.is_some()) && padding != needed {
+            return Err(format!(
+                "Expected {} padding characters, found {}",
+                needed, padding
+            ));
+        }
+    }
+    let mut output = vec![];
+    let (mut buffer, mut held) = (0u32, 0u32);
+    for (i, c) in data.chars().enumerate() {
+        let value = alphabet.iter().position(|&a| a as char == c).or_else(|| {
+            // Lenient base64 takes either alphabet, and base32 takes lowercase.
+            match (strict, bits, c) {
+                (false, 6, '+' | '-') => Some(62),
+                (false, 6, '/' | '_') => Some(63),
+                (false, 5, c) => BASE32
+                    .iter()
+                    .position(|&a| a as char == c.to_ascii_uppercase()),
+                _ => None,
+            }
+        });
+        let value = value.ok_or(format!("Invalid character '{}' at position {}", c, i + 1))?;
+        buffer = (buffer << bits | value as u32) & 0xffff;
+        held += bits;
+        if held >= 8 {
+            held -= 8;
+            output.push((buffer >> held) as u8);
+        }
+    }
+    if held >= bits {
+        return Err("The input ends partway through a byte".to_string());
+    }
+    Ok(output)
+}
+
+fn encode_base58(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+    // Little-endian base-58 digits of the number the bytes spell.
+    let mut digits: Vec<u8> = vec![];
+    for &b in &bytes[zeros..] {
+        let mut carry = b as u32;
+        for d in digits.iter_mut() {
+            carry += (*d as u32) << 8;
+            *d = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    "1".repeat(zeros)
+        + &digits
+            .iter()
+            .rev()
+            .map(|&d| BASE58[d as usize] as char)
+            .collect::<String>()
+}
+
+fn decode_base58(text: &str, strict: bool) -> Result<Vec<u8>, String> {
+    let text: String = if strict {
+        text.to_string()
+    } else {
+        text.chars().filter(|c| !c.is_whitespace()).collect()
+    };
+    let zeros = text.chars().take_while(|&c| c == '1').count();
+    let mut bytes: Vec<u8> = vec![];
+    for (i, c) in text.chars().enumerate().skip(zeros) {
+        let value = BASE58.iter().position(|&a| a as char == c).ok_or(format!(
+            "Invalid base58 character '{}' at position {}",
+            c,
+            i + 1
+        ))?;
+        let mut carry = value as u32;
+        for b in bytes.iter_mut() {
+            carry += *b as u32 * 58;
+            *b = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    bytes.extend(vec![0; zeros]);
+    bytes.reverse();
+    Ok(bytes)
+}
+
+// Strict decoding rejects a '%' without two hex digits; lenient decoding keeps it as it
+// is and also reads '+' as a space, as in HTML forms.
+fn decode_percent(text: &str, strict: bool) -> Result<Vec<u8>, String> {
+    let bytes = text.as_bytes();
+    let mut output = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|hex| bytes[i] == b'%' && hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match (bytes[i], escaped) {
+            (b'%', Some(b)) => {
+                output.push(b);
+                i += 3;
+                continue;
+            }
+            (b'%', None) if strict => {
+                return Err(format!(
+                    "'%' at position {} is not followed by two hex digits",
+                    i + 1
+                ))
+            }
+            (b'+', _) if !strict => output.push(b' '),
+            (b, _) => output.push(b),
+        }
+        i += 1;
+    }
+    Ok(output)
+}
+
+// RFC 2045: printable ASCII other than '=' stands for itself, spaces and tabs do too
+// except at the end of a line, and every other byte is written =XX. Lines are kept to 76
+// characters with soft breaks, a '=' at the end of a line.
+fn encode_quoted_printable(text: &str) -> String {
+    let mut lines = vec![];
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line).as_bytes();
+        let mut encoded = String::new();
+        let mut length = 0;
+        for (i, &b) in line.iter().enumerate() {
+            let last = i + 1 == line.len();
+            let piece = match b {
+                b' ' | b'\t' if !last => (b as char).to_string(),
+                33..=60 | 62..=126 => (b as char).to_string(),
+                _ => format!("={:02X}", b),
+            };
+            // Leave room for the soft break unless this piece ends the line.
+            let limit = if last { 76 } else { 75 };
+            if length + piece.len() > limit {
+                encoded.push_str("=\r\n");
+                length = 0;
+            }
+            length += piece.len();
+            encoded.push_str(&piece);
+        }
+        lines.push(encoded);
+    }
+    lines.join("\r\n")
+}
+
+fn decode_quoted_printable(text: &str, strict: bool) -> Result<Vec<u8>, String> {
+    let mut output = vec![];
+    let lines: Vec<&str> = text.split('\n').collect();
+    for (number, line) in lines.iter().enumerate() {
+        // Trailing whitespace was added in transport and is not part of the data.
+        let line = line
+            .strip_suffix('\r')
+            .unwrap_or(line)
+            .trim_end_matches([' ', '\t'])
+            .as_bytes();
+        let mut soft_break = false;
+        let mut i = 0;
+        while i < line.len() {
+            if line[i] != b'=' {
+                output.push(line[i]);
+                i += 1;
+                continue;
+            }
+            if i + 1 == line.len() {
+                soft_break = true;
+                break;
+            }
+            let hex = line
+                .get(i + 1..i + 3)
+                .and_then(|h| std::str::from_utf8(h).ok());
+            match hex.filter(|h| !strict || !h.bytes().any(|b| b.is_ascii_lowercase())) {
+                Some(h) if h.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                    output.push(u8::from_str_radix(h, 16).unwrap());
+                    i += 3;
+                }
+                _ if strict => {
+                    return Err(format!(
+                        "Invalid escape on line {} at column {}",
+                        number + 1,
+                        i + 1
+                    ))
+                }
+                _ => {
+                    output.push(b'=');
+                    i += 1;
+                }
+            }
+        }
+        if !soft_break && number + 1 < lines.len() {
+            output.push(b'\n');
+        }
+    }
+    Ok(output)
+}
@@ -0,0 +1,436 @@
+// src/enigma.rs
+use std::fmt;
+
+// Historical rotor wirings and turnover notches.
+const ROTORS: [(&str, &str, &str); 10] = [
+    ("I", "EKMFLGDQVZNTOWYHXUSPAIBRCJ", "Q"),
+    ("II", "AJDKSIRUXBLHWTMCQGZNPYFVOE", "E"),
+    ("III", "BDFHJLCPRTXVZNYEIWGAKMUSQO", "V"),
+    ("IV", "ESOVPZJAYQUIRHXLNFTGKDCMWB", "J"),
+    ("V", "VZBRGITYUPSDNHLXAWMJQOFECK", "Z"),
+    ("VI", "JPGVOUMFYQBENHZRDKASXLICTW", "ZM"),
+    ("VII", "NZJHGRCXMYSWBOUFAIVLPEKQDT", "ZM"),
+    ("VIII", "FKQHTLXOCBJSPDZMUINRGVYEWA", "ZM"),
+    ("BETA", "LEYJVCNIXWPBQMDRTAKZGFUHOS", ""),
+    ("GAMMA", "FSOKANUERHMBTIQCWGXJDPVLZY", ""),
+];
+
+const REFLECTORS: [(&str, &str); 5] = [
+    ("A", "EJMZALYXVBWFCRQUONTSPIKHGD"),
+    ("B", "YRUHQSLDPXNGOKMIEBFZCWVJAT"),
+    ("C", "FVPJIAOYEDRZXWGCTKUQSBNMHL"),
+    ("B-THIN", "ENKQAUYWJICOPBLMDXZVFTHRGS"),
+    ("C-THIN", "RDOBJNTKVEHMLFCWZAXGYIPSUQ"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Model {
+    EnigmaI,
+    M3,
+    M4,
+}
+
+impl Model {
+    pub fn parse(s: &str) -> Result<Model, String> {
+        match s.to_ascii_uppercase().as_str() {
+            "I" | "ENIGMAI" | "ENIGMA_I" => Ok(Model::EnigmaI),
+            "M3" => Ok(Model::M3),
+            "M4" => Ok(Model::M4),
+            _ => Err(format!("Unknown Enigma model '{}'", s)),
+        }
+    }
+
+    fn rotor_count(self) -> usize {
+        match self {
+            Model::M4 => 4,
+            _ => 3,
+        }
+    }
+
+    fn allows_rotor(self, name: &str, slot: usize) -> bool {
+        let greek = name == "BETA" || name == "GAMMA";
+        match self {
+            Model::EnigmaI => matches!(name, "I" | "II" | "III" | "IV" | "V"),
+            Model::M3 => !greek,
+            Model::M4 => greek == (slot == 0),
+        }
+    }
+
+    fn allows_reflector(self, name: &str) -> bool {
+        match self {
+            Model::EnigmaI => matches!(name, "A" | "B" | "C"),
+            Model::M3 => matches!(name, "B" | "C"),
+            Model::M4 => matches!(name, "B-THIN" | "C-THIN"),
+        }
+    }
+}
+
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Model::EnigmaI => "I",
+            Model::M3 => "M3",
+            Model::M4 => "M4",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// The machine settings, with rotors, rings and positions listed left to right.
+// Rings and positions are 0-based (ring setting 01 / 'A' is 0).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnigmaSettings {
+    pub model: Model,
+    pub reflector: String,
+    pub rotors: Vec<String>,
+    pub rings: Vec<u8>,
+    pub positions: Vec<u8>,
+    pub plugboard: Vec<(u8, u8)>,
+}
+
+impl Default for EnigmaSettings {
+    fn default() -> Self {
+        EnigmaSettings {
+            model: Model::M3,
+            reflector: "B".to_string(),
+            rotors: vec!["I".to_string(), "II".to_string(), "III".to_string()],
+            rings: vec![0; 3],
+            positions: vec![0; 3],
+            plugboard: vec![],
+        }
+    }
+}
+
+fn parse_letters(s: &str) -> Result<Vec<u8>, String> {
+    s.chars()
+        .map(|c| {
+            crate::ceasar::letter_to_number(c).ok_or(format!("Invalid letter '{}' in '{}'", c, s))
+        })
+        .collect()
+}
+
+// Ring settings may be given as letters ("AAA") or as 1-based numbers ("1,1,1" or "01-01-01").
+fn parse_rings(s: &str) -> Result<Vec<u8>, String> {
+    if s.chars().all(|c| c.is_ascii_alphabetic()) {
+        return parse_letters(s);
+    }
+    s.split([',', '-'])
+        .map(|part| match part.parse::<u8>() {
+            Ok(n) if (1..=26).contains(&n) => Ok(n - 1),
+            _ => Err(format!("Invalid ring setting '{}'", part)),
+        })
+        .collect()
+}
+
+// Parses settings of the form
+// "model=M3 reflector=B rotors=I,II,III rings=1,1,1 positions=AAA plugboard=AB,CD".
+// Every field is optional and defaults to the M3 settings above.
+pub fn parse_settings(s: &str) -> Result<EnigmaSettings, String> {
+    let mut settings = EnigmaSettings::default();
+    let mut rings = None;
+    let mut positions = None;
+    let mut reflector = None;
+    let mut rotors = None;
+
+    for field in s.split_whitespace() {
+        let (name, value) = field
+            .split_once('=')
+            .ok_or(format!("Expected name=value, found '{}'", field))?;
+        match name.to_ascii_lowercase().as_str() {
+            "model" => settings.model = Model::parse(value)?,
+            "reflector" => reflector = Some(value.to_ascii_uppercase()),
+            "rotors" => {
+                rotors = Some(
+                    value
+                        .split([',', '-'])
+                        .map(|r| r.to_ascii_uppercase())
+                        .collect(),
+                )
+            }
+            "rings" => rings = Some(parse_rings(value)?),
+            "positions" => positions = Some(parse_letters(value)?),
+            "plugboard" => {
+                settings.plugboard = value
+                    .split(',')
+                    .filter(|pair| !pair.is_empty())
+                    .map(|pair| match parse_letters(pair)?.as_slice() {
+                        &[a, b] => Ok((a, b)),
+                        _ => Err(format!("Invalid plugboard pair '{}'", pair)),
+                    })
+                    .collect::<Result<_, String>>()?
+            }
+            _ => return Err(format!("Unknown setting '{}'", name)),
+        }
+    }
+
+    let count = settings.model.rotor_count();
+    if let Some(rotors) = rotors {
+        settings.rotors = rotors;
+    } else if settings.model == Model::M4 {
+        settings.rotors.insert(0, "BETA".to_string());
+    }
+    settings.reflector = reflector.unwrap_or(match settings.model {
+        Model::M4 => "B-THIN".to_string(),
+        _ => "B".to_string(),
+    });
+    settings.rings = rings.unwrap_or(vec![0; count]);
+    settings.positions = positions.unwrap_or(vec![0; count]);
+    Ok(settings)
+}
+
+impl fmt::Display for EnigmaSettings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let letters = |v: &[u8]| v.iter().map(|&n| (b'A' + n) as char).collect::<String>();
+        write!(
+            f,
+            "model={} reflector={} rotors={} rings={} positions={}",
+            self.model,
+            self.reflector,
+            self.rotors.join(","),
+            letters(&self.rings),
+            letters(&self.positions)
+        )?;
+        if !self.plugboard.is_empty() {
+            let pairs = self
+                .plugboard
+                .iter()
+                .map(|&(a, b)| letters(&[a, b]))
+                .collect::<Vec<_>>();
+            write!(f, " plugboard={}", pairs.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Rotor {
+    name: String,
+    forward: [u8; 26],
+    backward: [u8; 26],
+    notches: Vec<u8>,
+    ring: u8,
+    position: u8,
+}
+
+impl Rotor {
+    fn new(name: &str, ring: u8, position: u8) -> Result<Rotor, String> {
+        let &(_, wiring, notches) = ROTORS
+            .iter()
+            .find(|(n, _, _)| *n == name)
+            .ok_or(format!("Unknown rotor '{}'", name))?;
+        let mut forward = [0; 26];
+        let mut backward = [0; 26];
+        for (i, b) in wiring.bytes().enumerate() {
+            forward[i] = b - b'A';
+            backward[(b - b'A') as usize] = i as u8;
+        }
+        Ok(Rotor {
+            name: name.to_string(),
+            forward,
+            backward,
+            notches: notches.bytes().map(|b| b - b'A').collect(),
+            ring,
+            position,
+        })
+    }
+
+    fn at_notch(&self) -> bool {
+        self.notches.contains(&self.position)
+    }
+
+    fn step(&mut self) {
+        self.position = (self.position + 1) % 26;
+    }
+
+    fn pass(&self, c: u8, wiring: &[u8; 26]) -> u8 {
+        let shift = (26 + self.position - self.ring) % 26;
+        (wiring[((c + shift) % 26) as usize] + 26 - shift) % 26
+    }
+}
+
+// One key press: the rotor positions after stepping and the letter after each component.
+#[derive(Clone, Debug)]
+pub struct TraceStep {
+    pub input: char,
+    pub positions: String,
+    pub path: Vec<(String, char)>,
+    pub output: char,
+}
+
+impl fmt::Display for TraceStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} [{}]", self.input, self.positions)?;
+        for (component, c) in &self.path {
+            write!(f, " {}:{}", component, c)?;
+        }
+        write!(f, " -> {}", self.output)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Enigma {
+    reflector: (String, [u8; 26]),
+    // Left to right; for the M4 the first rotor is the non-stepping greek wheel.
+    rotors: Vec<Rotor>,
+    stepping: usize,
+    plugboard: [u8; 26],
+}
+
+impl Enigma {
+    pub fn new(settings: &EnigmaSettings) -> Result<Enigma, String> {
+        let model = settings.model;
+        let count = model.rotor_count();
+        if settings.rotors.len() != count
+            || settings.rings.len() != count
+            || settings.positions.len() != count
+        {
+            return Err(format!(
+                "Enigma {} needs {} rotors, rings and positions",
+                model, count
+            ));
+        }
+        for (slot, name) in settings.rotors.iter().enumerate() {
+            if !model.allows_rotor(name, slot) {
+                return Err(format!(
+                    "Rotor {} cannot be used in that slot of the {}",
+                    name, model
+                ));
+            }
+            if settings.rotors[..slot].contains(name) {
+                return Err(format!("Rotor {} is used twice", name));
+            }
+        }
+        if !model.allows_reflector(&settings.reflector) {
+            return Err(format!(
+                "Reflector {} is not available on the {}",
+                settings.reflector, model
+            ));
+        }
+        let &(_, wiring) = REFLECTORS
+            .iter()
+            .find(|(n, _)| *n == settings.reflector)
+            .ok_or(format!("Unknown reflector '{}'", settings.reflector))?;
+        let mut reflector = [0; 26];
+        for (i, b) in wiring.bytes().enumerate() {
+            reflector[i] = b - b'A';
+        }
+
+        let rotors = settings
+            .rotors
+            .iter()
+            .zip(&settings.rings)
+            .zip(&settings.positions)
+            .map(|((name, &ring), &position)| Rotor::new(name, ring % 26, position % 26))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut plugboard: [u8; 26] = std::array::from_fn(|i| i as u8);
+        for &(a, b) in &settings.plugboard {
+            if a == b || plugboard[a as usize] != a || plugboard[b as usize] != b {
+                return Err("Plugboard letters must be distinct and used only once".to_string());
+            }
+            plugboard[a as usize] = b;
+            plugboard[b as usize] = a;
+        }
+
+        Ok(Enigma {
+            reflector: (format!("UKW-{}", settings.reflector), reflector),
+            rotors,
+            stepping: count - 3,
+            plugboard,
+        })
+    }
+
+    pub fn positions(&self) -> String {
+        self.rotors
+            .iter()
+            .map(|r| (b'A' + r.position) as char)
+            .collect()
+    }
+
+    pub fn set_positions(&mut self, positions: &[u8]) {
+        for (rotor, &p) in self.rotors.iter_mut().zip(positions) {
+            rotor.position = p % 26;
+        }
+    }
+
+    pub fn set_rings(&mut self, rings: &[u8]) {
+        for (rotor, &r) in self.rotors.iter_mut().zip(rings) {
+            rotor.ring = r % 26;
+        }
+    }
+
+    // Replaces the plugboard with the given letter pairs, which must not overlap.
+    pub fn set_plugboard(&mut self, pairs: &[(u8, u8)]) {
+        self.plugboard = std::array::from_fn(|i| i as u8);
+        for &(a, b) in pairs {
+            self.plugboard[a as usize] = b;
+            self.plugboard[b as usize] = a;
+        }
+    }
+
+    // Steps the three rightmost rotors, including the middle rotor's double step.
+    fn step(&mut self) {
+        let (left, middle, right) = (self.stepping, self.stepping + 1, self.stepping + 2);
+        if self.rotors[middle].at_notch() {
+            self.rotors[middle].step();
+            self.rotors[left].step();
+        } else if self.rotors[right].at_notch() {
+            self.rotors[middle].step();
+        }
+        self.rotors[right].step();
+    }
+
+    // Presses one key, recording the letter after each component when a trace is requested.
+    fn press(&mut self, c: u8, mut path: Option<&mut Vec<(String, char)>>) -> u8 {
+        self.step();
+        let mut record = |component: &str, c: u8| {
+            if let Some(path) = path.as_mut() {
+                path.push((component.to_string(), (b'A' + c) as char));
+            }
+        };
+        let mut c = self.plugboard[c as usize];
+        record("P", c);
+        for rotor in self.rotors.iter().rev() {
+            c = rotor.pass(c, &rotor.forward);
+            record(&rotor.name, c);
+        }
+        c = self.reflector.1[c as usize];
+        record(&self.reflector.0, c);
+        for rotor in &self.rotors {
+            c = rotor.pass(c, &rotor.backward);
+            record(&rotor.name, c);
+        }
+        c = self.plugboard[c as usize];
+        record("P", c);
+        c
+    }
+
+    // Works on 0-25 letter numbers, avoiding string handling in the cryptanalysis code.
+    pub fn encrypt_numbers(&mut self, numbers: &[u8]) -> Vec<u8> {
+        numbers.iter().map(|&n| self.press(n, None)).collect()
+    }
+
+    // Encryption and decryption are the same operation. Non-letters are dropped.
+    pub fn encrypt(&mut self, text: &str) -> String {
+        crate::ceasar::string_to_numbers(text)
+            .into_iter()
+            .map(|n| (b'A' + self.press(n, None)) as char)
+            .collect()
+    }
+
+    pub fn encrypt_with_trace(&mut self, text: &str) -> (String, Vec<TraceStep>) {
+        let mut steps = vec![];
+        for n in crate::ceasar::string_to_numbers(text) {
+            let mut path = vec![];
+            let out = self.press(n, Some(&mut path));
+            steps.push(TraceStep {
+                input: (b'A' + n) as char,
+                positions: self.positions(),
+                path,
+                output: (b'A' + out) as char,
+            });
+        }
+        let output = steps.iter().map(|s| s.output).collect();
+        (output, steps)
+    }
+}
@@ -0,0 +1,326 @@
+// src/enigma_cracker.rs
+//
+// Ciphertext-only attack on three-rotor Enigma messages, after Gillogly: the rotor
+// order and start positions are found by index of coincidence with the plugboard
+// left empty, then the ring settings of the two fast rotors, and finally the
+// plugboard is hill-climbed with bigram and trigram fitness.
+
+use crate::cryptanalysis::index_of_coincidence_of_counts;
+use crate::enigma::{Enigma, EnigmaSettings, Model};
+use crate::ngram::NgramModel;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+pub struct AttackOptions {
+    pub model: Model,
+    pub reflector: String,
+    // The rotors the three slots are chosen from.
+    pub rotors: Vec<String>,
+    pub candidates: usize,
+    pub max_plugs: usize,
+    pub threads: usize,
+}
+
+impl Default for AttackOptions {
+    fn default() -> Self {
+        AttackOptions {
+            model: Model::EnigmaI,
+            reflector: "B".to_string(),
+            rotors: ["I", "II", "III", "IV", "V"]
+                .iter()
+                .map(|r| r.to_string())
+                .collect(),
+            candidates: 5,
+            max_plugs: 10,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+}
+
+// Parses options of the form "model=I reflector=B rotors=I,II,III,IV,V candidates=5 plugs=10 threads=4".
+pub fn parse_attack_options(s: &str) -> Result<AttackOptions, String> {
+    let mut options = AttackOptions::default();
+    for field in s.split_whitespace() {
+        let (name, value) = field
+            .split_once('=')
+            .ok_or(format!("Expected name=value, found '{}'", field))?;
+        let number = || {
+            value
+                .parse::<usize>()
+                .map_err(|e| format!("Invalid {} '{}': {}", name, value, e))
+        };
+        match name.to_ascii_lowercase().as_str() {
+            "model" => {
+                options.model = Model::parse(value)?;
+                if options.model == Model::M4 {
+                    return Err("The attack only supports three-rotor machines".to_string());
+                }
+            }
+            "reflector" => options.reflector = value.to_ascii_uppercase(),
+            "rotors" => {
+                options.rotors = value
+                    .split([',', '-'])
+                    .map(|r| r.to_ascii_uppercase())
+                    .collect()
+            }
+            "candidates" => options.candidates = number()?.max(1),
+            "plugs" => options.max_plugs = number()?.min(13),
+            "threads" => options.threads = number()?.max(1),
+            _ => return Err(format!("Unknown option '{}'", name)),
+        }
+    }
+    Ok(options)
+}
+
+// Phase one keeps this many start positions for the ring and plugboard searches.
+const PHASE_ONE_KEEP: usize = 100;
+
+pub struct Candidate {
+    pub settings: EnigmaSettings,
+    pub plaintext: String,
+    pub score: f64,
+}
+
+fn rotor_orders(pool: &[String]) -> Vec<Vec<String>> {
+    let mut orders = vec![];
+    for a in pool {
+        for b in pool.iter().filter(|&b| b != a) {
+            for c in pool.iter().filter(|&c| c != a && c != b) {
+                orders.push(vec![a.clone(), b.clone(), c.clone()]);
+            }
+        }
+    }
+    orders
+}
+
+fn ioc(numbers: &[u8]) -> f64 {
+    let mut counts = [0usize; 26];
+    for &n in numbers {
+        counts[n as usize] += 1;
+    }
+    index_of_coincidence_of_counts(&counts)
+}
+
+fn decrypt(machine: &Enigma, settings: &EnigmaSettings, ciphertext: &[u8]) -> Vec<u8> {
+    let mut machine = machine.clone();
+    machine.set_rings(&settings.rings);
+    machine.set_positions(&settings.positions);
+    machine.set_plugboard(&settings.plugboard);
+    machine.encrypt_numbers(ciphertext)
+}
+
+// Keeps the best `limit` entries, highest score first.
+fn keep_best<T>(best: &mut Vec<(f64, T)>, score: f64, item: T, limit: usize) {
+    if best.len() < limit || score > best[best.len() - 1].0 {
+        let at = best.partition_point(|(s, _)| *s >= score);
+        best.insert(at, (score, item));
+        best.truncate(limit);
+    }
+}
+
+// Phase one: every rotor order and start position, rings at AAA and no plugboard.
+fn search_positions(
+    ciphertext: &[u8],
+    options: &AttackOptions,
+    progress: &(dyn Fn(usize, usize) + Sync),
+) -> Result<Vec<(f64, EnigmaSettings)>, String> {
+    let orders = rotor_orders(&options.rotors);
+    if orders.is_empty() {
+        return Err("At least three rotors are needed".to_string());
+    }
+    let machines = orders
+        .iter()
+        .map(|order| {
+            let settings = EnigmaSettings {
+                model: options.model,
+                reflector: options.reflector.clone(),
+                rotors: order.clone(),
+                ..EnigmaSettings::default()
+            };
+            Enigma::new(&settings).map(|machine| (settings, machine))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let best = Mutex::new(vec![]);
+    thread::scope(|scope| {
+        for _ in 0..options.threads {
+            scope.spawn(|| {
+                let mut local: Vec<(f64, EnigmaSettings)> = vec![];
+                loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    let Some((settings, machine)) = machines.get(i) else {
+                        break;
+                    };
+                    let mut machine = machine.clone();
+                    for p in 0..26 * 26 * 26 {
+                        let positions = [(p / 676) as u8, (p / 26 % 26) as u8, (p % 26) as u8];
+                        machine.set_positions(&positions);
+                        let score = ioc(&machine.encrypt_numbers(ciphertext));
+                        let candidate = || EnigmaSettings {
+                            positions: positions.to_vec(),
+                            ..settings.clone()
+                        };
+                        if local.len() < PHASE_ONE_KEEP || score > local[local.len() - 1].0 {
+                            keep_best(&mut local, score, candidate(), PHASE_ONE_KEEP);
+                        }
+                    }
+                    progress(done.fetch_add(1, Ordering::SeqCst) + 1, machines.len());
+                }
+                let mut best = best.lock().unwrap();
+                for (score, settings) in local {
+                    keep_best(&mut best, score, settings, PHASE_ONE_KEEP);
+                }
+            });
+        }
+    });
+    Ok(best.into_inner().unwrap())
+}
+
+// Phase two: the right and then the middle ring, moving the start position with the
+// ring so that the wiring stays aligned and only the turnover point changes.
+fn search_rings(settings: &mut EnigmaSettings, score: impl Fn(&EnigmaSettings) -> f64) -> f64 {
+    let mut best = (score(settings), settings.clone());
+    for slot in [2, 1] {
+        let base = best.1.clone();
+        for delta in 1..26 {
+            let mut trial = base.clone();
+            trial.rings[slot] = (base.rings[slot] + delta) % 26;
+            trial.positions[slot] = (base.positions[slot] + delta) % 26;
+            let trial_score = score(&trial);
+            if trial_score > best.0 {
+                best = (trial_score, trial);
+            }
+        }
+    }
+    *settings = best.1;
+    best.0
+}
+
+// Greedily adds the plug that most improves the score until no plug helps.
+fn add_plugs(
+    settings: &mut EnigmaSettings,
+    max_plugs: usize,
+    score: impl Fn(&EnigmaSettings) -> f64,
+) {
+    let mut current = score(settings);
+    while settings.plugboard.len() < max_plugs {
+        let mut best: Option<(f64, (u8, u8))> = None;
+        let free: Vec<u8> = (0..26).filter(|&c| unplugged(settings, c)).collect();
+        for (i, &a) in free.iter().enumerate() {
+            for &b in &free[i + 1..] {
+                settings.plugboard.push((a, b));
+                let trial = score(settings);
+                settings.plugboard.pop();
+                if trial > best.map_or(current, |(s, _)| s) {
+                    best = Some((trial, (a, b)));
+                }
+            }
+        }
+        match best {
+            Some((trial, pair)) => {
+                settings.plugboard.push(pair);
+                current = trial;
+            }
+            None => break,
+        }
+    }
+}
+
+fn unplugged(settings: &EnigmaSettings, c: u8) -> bool {
+    !settings.plugboard.iter().any(|&(a, b)| a == c || b == c)
+}
+
+// Phase three: plugs are added by index of coincidence and then by bigram score, and
+// the result is refined with trigrams by trying every replacement of each plug letter.
+fn search_plugboard(
+    machine: &Enigma,
+    settings: &mut EnigmaSettings,
+    ciphertext: &[u8],
+    max_plugs: usize,
+) {
+    let fitness = |model: &NgramModel, settings: &EnigmaSettings| {
+        model.score_numbers(&decrypt(machine, settings, ciphertext))
+    };
+    add_plugs(settings, max_plugs, |s| {
+        ioc(&decrypt(machine, s, ciphertext))
+    });
+    add_plugs(settings, max_plugs, |s| fitness(NgramModel::english(2), s));
+
+    let trigrams = NgramModel::english(3);
+    let mut score = fitness(trigrams, settings);
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..settings.plugboard.len() {
+            let (a, b) = settings.plugboard[i];
+            let mut trials = vec![];
+            for c in 0..26 {
+                if unplugged(settings, c) {
+                    trials.push(Some((a, c)));
+                    trials.push(Some((c, b)));
+                }
+            }
+            trials.push(None);
+            for trial_pair in trials {
+                let mut trial = settings.clone();
+                match trial_pair {
+                    Some(pair) => trial.plugboard[i] = pair,
+                    None => {
+                        trial.plugboard.remove(i);
+                    }
+                }
+                let trial_score = fitness(trigrams, &trial);
+                if trial_score > score {
+                    *settings = trial;
+                    score = trial_score;
+                    improved = true;
+                    break;
+                }
+            }
+            if improved {
+                break;
+            }
+        }
+    }
+}
+
+// Returns the candidates ranked by trigram fitness per letter, best first. The progress
+// callback receives the number of rotor orders searched so far and the total.
+pub fn crack_enigma(
+    ciphertext: &str,
+    options: &AttackOptions,
+    progress: &(dyn Fn(usize, usize) + Sync),
+) -> Result<Vec<Candidate>, String> {
+    let numbers = crate::ceasar::string_to_numbers(ciphertext);
+    if numbers.len() < 2 {
+        return Err("The ciphertext is too short to attack".to_string());
+    }
+
+    let mut ringed = vec![];
+    for (_, mut settings) in search_positions(&numbers, options, progress)? {
+        let machine = Enigma::new(&settings)?;
+        let score = search_rings(&mut settings, |s| ioc(&decrypt(&machine, s, &numbers)));
+        keep_best(&mut ringed, score, (machine, settings), options.candidates);
+    }
+
+    let mut candidates = vec![];
+    for (_, (machine, mut settings)) in ringed {
+        search_plugboard(&machine, &mut settings, &numbers, options.max_plugs);
+        // With the plugboard in place the trigram score can settle the rings exactly.
+        let trigrams = NgramModel::english(3);
+        let score = search_rings(&mut settings, |s| {
+            trigrams.score_numbers(&decrypt(&machine, s, &numbers))
+        }) / numbers.len() as f64;
+        let plaintext = Enigma::new(&settings)?.encrypt(ciphertext);
+        candidates.push(Candidate {
+            settings,
+            plaintext,
+            score,
+        });
+    }
+    candidates.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+    Ok(candidates)
+}
@@ -0,0 +1,358 @@
+// src/factor.rs
+//
+// Integer factorization for attacking toy keys. Each method finds one factor quickly
+// when the number has a particular weakness: trial division when a factor is small,
+// Fermat's method when two factors are close together, Pollard's p-1 and Williams' p+1
+// when some factor p has p-1 or p+1 made of small primes, and Pollard's rho, slower but
+// with no such condition, when a factor is below about 2^40. The automatic strategy
+// tries them from cheapest to most general and splits the parts until all are prime,
+// or until a part has used up its budget of rho steps, as an RSA modulus will.
+
+use crate::bigint::BigUint;
+use crate::gcd;
+use crate::number_theory::{format_factorization, mod_pow};
+use crate::primes::{is_prime, primes_between};
+use crate::Unsigned;
+use std::collections::BTreeMap;
+use std::fmt;
+
+// |a - b|
+fn distance(a: &BigUint, b: &BigUint) -> BigUint {
+    if a >= b {
+        a.clone() - b.clone()
+    } else {
+        b.clone() - a.clone()
+    }
+}
+
+// A factor of n strictly between 1 and n, if g is one.
+fn proper(g: BigUint, n: &BigUint) -> Option<BigUint> {
+    (g > BigUint::one() && g < *n).then_some(g)
+}
+
+// Divides out every prime up to the bound, returning the prime factors found and the
+// part of n that is left.
+pub fn trial_division(n: &BigUint, bound: u64) -> (Vec<(BigUint, u32)>, BigUint) {
+    let mut factors = vec![];
+    let mut rest = n.clone();
+    for p in primes_between(2, bound) {
+        if rest < BigUint::from(p) * BigUint::from(p) {
+            break;
+        }
+        let mut k = 0;
+        while rest.rem_u64(p) == 0 {
+            rest = rest / BigUint::from(p);
+            k += 1;
+        }
+        if k > 0 {
+            factors.push((BigUint::from(p), k));
+        }
+    }
+    // What is left after the loop stops early is 1 or a prime.
+    if rest > BigUint::one()
+        && rest
+            .to_u64()
+            .is_some_and(|r| r <= bound.saturating_mul(bound))
+    {
+        factors.push((rest, 1));
+        rest = BigUint::one();
+    }
+    (factors, rest)
+}
+
+// Fermat's method for odd n: looks for n = a^2 - b^2 = (a - b)(a + b) with a counting up
+// from the square root of n, which takes few steps when two factors are close.
+pub fn fermat(n: &BigUint, max_steps: u64) -> Option<BigUint> {
+    if n.is_even() {
+        return proper(BigUint::from(2), n);
+    }
+    let mut a = n.sqrt();
+    if a.clone() * a.clone() < *n {
+        a = a + BigUint::one();
+    }
+    // b2 = a^2 - n, kept up to date as a grows: (a + 1)^2 - a^2 = 2a + 1.
+    let mut b2 = a.clone() * a.clone() - n.clone();
+    for _ in 0..max_steps {
+        let b = b2.sqrt();
+        if b.clone() * b.clone() == b2 {
+            return proper(a - b, n);
+        }
+        b2 = b2 + (a.clone() << 1) + BigUint::one();
+        a = a + BigUint::one();
+    }
+    None
+}
+
+// Pollard's p-1: if p - 1 divides M, the product of the prime powers up to the bound,
+// then 2^M = 1 mod p, and gcd(2^M - 1, n) picks out p. The gcd is taken every so many
+// primes, and a batch that finds all of n at once is repeated a prime at a time.
+pub fn pollard_p_minus_1(n: &BigUint, bound: u64) -> Option<BigUint> {
+    const BATCH: usize = 64;
+    let primes = primes_between(2, bound);
+    let power = |p: u64| {
+        let mut pk = p;
+        while pk <= bound / p {
+            pk *= p;
+        }
+        BigUint::from(pk)
+    };
+    let mut a = BigUint::from(2) % n.clone();
+    for batch in primes.chunks(BATCH) {
+        let saved = a.clone();
+        for &p in batch {
+            a = mod_pow(a, power(p), n.clone());
+        }
+        let g = gcd(distance(&a, &BigUint::one()), n.clone());
+        if g == BigUint::one() {
+            continue;
+        }
+        if g != *n {
+            return Some(g);
+        }
+        a = saved;
+        for &p in batch {
+            a = mod_pow(a, power(p), n.clone());
+            let g = gcd(distance(&a, &BigUint::one()), n.clone());
+            if g != BigUint::one() {
+                return proper(g, n);
+            }
+        }
+    }
+    None
+}
+
+// V_m of the Lucas sequence V_0 = 2, V_1 = v, V_(k+1) = v V_k - V_(k-1), mod n, by the
+// ladder that keeps the pair (V_k, V_(k+1)).
+fn lucas_v(v: &BigUint, m: u64, n: &BigUint) -> BigUint {
+    let two = BigUint::from(2);
+    let minus = |x: BigUint, y: &BigUint| (x + n.clone() - y.clone() % n.clone()) % n.clone();
+    let mut x = v.clone();
+    let mut y = minus(v.mul_mod(v, n), &two);
+    for i in (0..63 - m.leading_zeros()).rev() {
+        if m >> i & 1 == 1 {
+            x = minus(x.mul_mod(&y, n), v);
+            y = minus(y.mul_mod(&y, n), &two);
+        } else {
+            y = minus(x.mul_mod(&y, n), v);
+            x = minus(x.mul_mod(&x, n), &two);
+        }
+    }
+    x
+}
+
+// Williams' p+1, the counterpart of p-1 for a factor p with p + 1 smooth. It works for
+// a seed v when v^2 - 4 is not a square mod p, which is not known in advance, so each
+// seed in turn is tried.
+pub fn williams_p_plus_1(n: &BigUint, bound: u64, seeds: &[u64]) -> Option<BigUint> {
+    let primes = primes_between(2, bound);
+    let two = BigUint::from(2);
+    for &seed in seeds {
+        let mut v = BigUint::from(seed) % n.clone();
+        for &p in &primes {
+            let mut pk = p;
+            while pk <= bound / p {
+                pk *= p;
+            }
+            v = lucas_v(&v, pk, n);
+        }
+        if let Some(factor) = proper(gcd(distance(&v, &two), n.clone()), n) {
+            return Some(factor);
+        }
+    }
+    None
+}
+
+// Pollard's rho with Brent's cycle finding: x -> x^2 + c mod n falls into a cycle mod p
+// after about sqrt(p) steps, long before it does mod n. Brent's version compares against
+// a point that moves at powers of two, and multiplies many differences together before
+// each gcd. Gives up after `max_steps` steps of the sequence.
+pub fn pollard_rho_brent(n: &BigUint, c: u64, max_steps: u64) -> Option<BigUint> {
+    const BATCH: u64 = 128;
+    if n.is_even() {
+        return proper(BigUint::from(2), n);
+    }
+    let c = BigUint::from(c);
+    let f = |x: &BigUint| (x.mul_mod(x, n) + c.clone()) % n.clone();
+    let (mut y, mut ys, mut x) = (BigUint::from(2), BigUint::from(2), BigUint::from(2));
+    let mut q = BigUint::one();
+    let mut g = BigUint::one();
+    let mut r = 1;
+    let mut steps = 0;
+    while g == BigUint::one() {
+        x = y.clone();
+        for _ in 0..r {
+            y = f(&y);
+        }
+        let mut k = 0;
+        while k < r && g == BigUint::one() {
+            ys = y.clone();
+            for _ in 0..BATCH.min(r - k) {
+                y = f(&y);
+                q = q.mul_mod(&distance(&x, &y), n);
+            }
+            g = gcd(q.clone(), n.clone());
+            k += BATCH;
+        }
+        steps += 2 * r;
+        r *= 2;
+        if g == BigUint::one() && steps > max_steps {
+            return None;
+        }
+    }
+    // The batch overshot to a multiple of n: step through it again one gcd at a time.
+    if g == *n {
+        loop {
+            ys = f(&ys);
+            g = gcd(distance(&x, &ys), n.clone());
+            if g != BigUint::one() {
+                break;
+            }
+        }
+    }
+    proper(g, n)
+}
+
+// How one composite was split on the way to the full factorization.
+pub struct Split {
+    pub method: &'static str,
+    pub composite: BigUint,
+    pub factor: BigUint,
+}
+
+pub struct Factorization {
+    pub n: BigUint,
+    // The prime factors with their multiplicities, in increasing order.
+    pub factors: Vec<(BigUint, u32)>,
+    pub small: Vec<(BigUint, u32)>,
+    pub splits: Vec<Split>,
+    // Composite parts that no method split within its budget.
+    pub unsplit: Vec<BigUint>,
+}
+
+impl Factorization {
+    // The prime factors, or an error naming a part that was left composite.
+    pub fn complete(self) -> Result<Vec<(BigUint, u32)>, String> {
+        match self.unsplit.first() {
+            Some(part) => Err(format!(
+                "Could not factor {}: {} would not split",
+                self.n, part
+            )),
+            None => Ok(self.factors),
+        }
+    }
+}
+
+// Rho is run with up to this many constants c, for this many steps each on a number of
+// one or two 64-bit words: enough for any factor below 2^40, and so for any composite
+// of 64 bits. A step costs the square of the number of words, so longer numbers get
+// that many times fewer steps, and the split of a 2048-bit modulus gives up in about a
+// second.
+const RHO_ATTEMPTS: u64 = 2;
+const RHO_STEPS: u64 = 1 << 20;
+
+fn rho_steps(n: &BigUint) -> u64 {
+    let words = n.bits().div_ceil(64).max(2);
+    RHO_STEPS * 4 / (words * words)
+}
+
+// One nontrivial factor of a composite n with no small factors, with the method that
+// found it, or None once every method has used up its budget.
+fn split(n: &BigUint) -> Option<(&'static str, BigUint)> {
+    if let Some(factor) = fermat(n, 100) {
+        return Some(("Fermat", factor));
+    }
+    if let Some(factor) = pollard_p_minus_1(n, 10_000) {
+        return Some(("Pollard p-1", factor));
+    }
+    if let Some(factor) = williams_p_plus_1(n, 2_000, &[3, 5, 7]) {
+        return Some(("Williams p+1", factor));
+    }
+    (1..=RHO_ATTEMPTS)
+        .find_map(|c| pollard_rho_brent(n, c, rho_steps(n)))
+        .map(|factor| ("Pollard rho", factor))
+}
+
+// The full prime factorization: trial division up to 10000, then each composite part is
+// split by the first method that succeeds, until every part is prime or is given up on.
+pub fn factorize(n: &BigUint) -> Factorization {
+    let (small, rest) = trial_division(n, 10_000);
+    let mut primes: BTreeMap<BigUint, u32> = small.iter().cloned().collect();
+    let mut splits = vec![];
+    let mut unsplit = vec![];
+    let mut parts = vec![];
+    if rest > BigUint::one() {
+        parts.push(rest);
+    }
+    while let Some(part) = parts.pop() {
+        if is_prime(&part) {
+            *primes.entry(part).or_insert(0) += 1;
+            continue;
+        }
+        let Some((method, factor)) = split(&part) else {
+            unsplit.push(part);
+            continue;
+        };
+        parts.push(part.clone() / factor.clone());
+        parts.push(factor.clone());
+        splits.push(Split {
+            method,
+            composite: part,
+            factor,
+        });
+    }
+    Factorization {
+        n: n.clone(),
+        factors: primes.into_iter().collect(),
+        small,
+        splits,
+        unsplit,
+    }
+}
+
+// The prime factorization of n of any width, for the routines generic over Unsigned.
+pub fn prime_factors<T: Unsigned>(n: &T) -> Result<Vec<(T, u32)>, String> {
+    let factors = factorize(&n.to_biguint()).complete()?;
+    Ok(factors
+        .into_iter()
+        .map(|(p, k)| (T::from_biguint(&p).unwrap(), k))
+        .collect())
+}
+
+// The steps taken, then the factorization, as in
+//   Trial division: 2^2 * 5
+//   Fermat: 8051 = 83 * 97
+//   161020 = 2^2 * 5 * 83 * 97
+// with any part left composite marked as such.
+impl fmt::Display for Factorization {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.small.is_empty() {
+            writeln!(f, "Trial division: {}", format_factorization(&self.small))?;
+        }
+        for split in &self.splits {
+            writeln!(
+                f,
+                "{}: {} = {} * {}",
+                split.method,
+                split.composite,
+                split.factor,
+                split.composite.clone() / split.factor.clone()
+            )?;
+        }
+        for part in &self.unsplit {
+            writeln!(f, "Unsplit: {} is composite, but no method split it", part)?;
+        }
+        if self.factors.len() == 1 && self.factors[0].1 == 1 && self.unsplit.is_empty() {
+            return write!(f, "{} is prime", self.n);
+        }
+        let mut parts = vec![];
+        if !self.factors.is_empty() {
+            parts.push(format_factorization(&self.factors));
+        }
+        parts.extend(
+            self.unsplit
+                .iter()
+                .map(|part| format!("{} (composite)", part)),
+        );
+        write!(f, "{} = {}", self.n, parts.join(" * "))
+    }
+}
@@ -0,0 +1,195 @@
+// src/fractionated.rs
+//
+// Ciphers that fractionate Morse code. The message is written in Morse with an x after
+// each letter and a second x between words, and the resulting stream of dots, dashes
+// and xs is enciphered in pieces: three symbols to a letter of a keyed alphabet
+// (Fractionated Morse), two symbols to a digit (Morbit), or one symbol to one of
+// several digits (Pollux).
+
+use crate::morse::{code_letter, letter_code};
+
+// Morse with x between letters and xx between words, dropping anything without a code.
+fn to_symbols(plaintext: &str) -> String {
+    plaintext
+        .split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter_map(letter_code)
+                .collect::<Vec<_>>()
+                .join("x")
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("xx")
+}
+
+fn from_symbols(symbols: &str) -> Result<String, String> {
+    let mut words = vec![];
+    for word in symbols.split("xx").filter(|w| !w.is_empty()) {
+        let letters = word
+            .split('x')
+            .filter(|l| !l.is_empty())
+            .map(|code| code_letter(code).ok_or(format!("Unknown Morse code '{}'", code)))
+            .collect::<Result<String, _>>()?;
+        words.push(letters);
+    }
+    Ok(words.join(" "))
+}
+
+// Pads with xs so the symbols divide into pieces of `size`.
+fn padded(mut symbols: String, size: usize) -> String {
+    while !symbols.len().is_multiple_of(size) {
+        symbols.push('x');
+    }
+    symbols
+}
+
+// The pieces of `size` symbols in the order . - x, for example "..", ".-", ".x", "-.".
+fn pieces(size: u32) -> Vec<String> {
+    (0..3usize.pow(size))
+        .map(|mut i| {
+            let mut piece = vec![' '; size as usize];
+            for slot in piece.iter_mut().rev() {
+                *slot = ['.', '-', 'x'][i % 3];
+                i /= 3;
+            }
+            piece.into_iter().collect()
+        })
+        .collect()
+}
+
+// The keyword's distinct letters followed by the rest of the alphabet.
+fn keyed_alphabet(keyword: &str) -> Vec<char> {
+    let mut alphabet = vec![];
+    for c in keyword.chars().chain('A'..='Z') {
+        let c = c.to_ascii_uppercase();
+        if c.is_ascii_uppercase() && !alphabet.contains(&c) {
+            alphabet.push(c);
+        }
+    }
+    alphabet
+}
+
+pub fn fractionated_morse_encrypt(plaintext: &str, keyword: &str) -> String {
+    let alphabet = keyed_alphabet(keyword);
+    let triplets = pieces(3);
+    let symbols = padded(to_symbols(plaintext), 3);
+    symbols
+        .as_bytes()
+        .chunks(3)
+        .map(|chunk| {
+            let i = triplets.iter().position(|t| t.as_bytes() == chunk).unwrap();
+            alphabet[i]
+        })
+        .collect()
+}
+
+pub fn fractionated_morse_decrypt(ciphertext: &str, keyword: &str) -> Result<String, String> {
+    let alphabet = keyed_alphabet(keyword);
+    let triplets = pieces(3);
+    let symbols = ciphertext
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| {
+            let i = alphabet
+                .iter()
+                .position(|&a| a == c.to_ascii_uppercase())
+                .unwrap();
+            triplets[i].as_str()
+        })
+        .collect::<String>();
+    from_symbols(&symbols)
+}
+
+// Morbit numbers the pairs .. .- .x -. -- -x x. x- xx by the alphabetical order of the
+// letters of a nine-letter keyword.
+fn morbit_digits(keyword: &str) -> Result<Vec<u8>, String> {
+    let letters: Vec<char> = keyword
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    if letters.len() != 9 {
+        return Err("The Morbit keyword needs nine letters".to_string());
+    }
+    let mut order: Vec<usize> = (0..9).collect();
+    order.sort_by_key(|&i| letters[i]);
+    let mut digits = vec![0; 9];
+    for (rank, &i) in order.iter().enumerate() {
+        digits[i] = rank as u8 + 1;
+    }
+    Ok(digits)
+}
+
+pub fn morbit_encrypt(plaintext: &str, keyword: &str) -> Result<String, String> {
+    let digits = morbit_digits(keyword)?;
+    let pairs = pieces(2);
+    let symbols = padded(to_symbols(plaintext), 2);
+    Ok(symbols
+        .as_bytes()
+        .chunks(2)
+        .map(|chunk| {
+            let i = pairs.iter().position(|p| p.as_bytes() == chunk).unwrap();
+            (b'0' + digits[i]) as char
+        })
+        .collect())
+}
+
+pub fn morbit_decrypt(ciphertext: &str, keyword: &str) -> Result<String, String> {
+    let digits = morbit_digits(keyword)?;
+    let pairs = pieces(2);
+    let symbols = ciphertext
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| {
+            c.to_digit(10)
+                .and_then(|d| digits.iter().position(|&k| k as u32 == d))
+                .map(|i| pairs[i].as_str())
+                .ok_or(format!("'{}' is not a Morbit digit", c))
+        })
+        .collect::<Result<String, _>>()?;
+    from_symbols(&symbols)
+}
+
+// A Pollux key gives the symbol for each digit 0-9, such as "x.-x.-x.-x". Every
+// symbol needs at least one digit.
+fn parse_pollux_key(key: &str) -> Result<Vec<char>, String> {
+    let symbols: Vec<char> = key.chars().collect();
+    if symbols.len() != 10 || symbols.iter().any(|c| !".-x".contains(*c)) {
+        return Err("A Pollux key is ten of '.', '-' and 'x', one for each digit".to_string());
+    }
+    if ".-x".chars().any(|c| !symbols.contains(&c)) {
+        return Err("Each of '.', '-' and 'x' needs a digit".to_string());
+    }
+    Ok(symbols)
+}
+
+// Each symbol cycles through its digits in turn, so the output is reproducible.
+pub fn pollux_encrypt(plaintext: &str, key: &str) -> Result<String, String> {
+    let symbols = parse_pollux_key(key)?;
+    let mut next = [0usize; 3];
+    Ok(to_symbols(plaintext)
+        .chars()
+        .map(|c| {
+            let kind = ".-x".find(c).unwrap();
+            let digits: Vec<usize> = (0..10).filter(|&d| symbols[d] == c).collect();
+            let digit = digits[next[kind] % digits.len()];
+            next[kind] += 1;
+            (b'0' + digit as u8) as char
+        })
+        .collect())
+}
+
+pub fn pollux_decrypt(ciphertext: &str, key: &str) -> Result<String, String> {
+    let symbols = parse_pollux_key(key)?;
+    let morse = ciphertext
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| {
+            c.to_digit(10)
+                .map(|d| symbols[d as usize])
+                .ok_or(format!("'{}' is not a digit", c))
+        })
+        .collect::<Result<String, _>>()?;
+    from_symbols(&morse)
+}
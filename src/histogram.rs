@@ -0,0 +1,172 @@
+// src/histogram.rs
+//
+// Bar charts of n-gram frequencies for the terminal. The ciphertext's bars are drawn
+// beside the English reference profile and, given a guessed Caesar shift, beside the
+// profile shifted by that key, so that the peaks of a shifted alphabet line up by eye.
+
+use crate::ngram::{english_distribution, letters};
+use crate::vigenere_cracker::ngram_frequency_distribution;
+use std::collections::HashMap;
+
+// The partial blocks of a Unicode bar, one to seven eighths of a character.
+const EIGHTHS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+#[derive(Clone, Debug)]
+pub struct HistogramOptions {
+    pub n: usize,
+    // The length of the longest bar in characters.
+    pub width: usize,
+    // '#' bars instead of Unicode blocks, for terminals without them.
+    pub ascii: bool,
+    pub shift: Option<u8>,
+    // How many of the ciphertext's most common n-grams to chart when n > 1.
+    pub top: usize,
+}
+
+impl Default for HistogramOptions {
+    fn default() -> Self {
+        HistogramOptions {
+            n: 1,
+            width: 30,
+            ascii: false,
+            shift: None,
+            top: 20,
+        }
+    }
+}
+
+// Parses "n=2 width=40 style=ascii shift=3 top=10".
+pub fn parse_histogram_options(s: &str) -> Result<HistogramOptions, String> {
+    let mut options = HistogramOptions::default();
+    let number = |name: &str, value: &str| {
+        value
+            .parse::<usize>()
+            .ok()
+            .filter(|&v| v > 0)
+            .ok_or(format!("The {} must be a positive number", name))
+    };
+    for field in s.split_whitespace() {
+        let (name, value) = field
+            .split_once('=')
+            .ok_or(format!("Expected name=value, found '{}'", field))?;
+        match name.to_ascii_lowercase().as_str() {
+            "n" => options.n = number(name, value)?,
+            "width" => options.width = number(name, value)?,
+            "top" => options.top = number(name, value)?,
+            "shift" => {
+                let shift = value
+                    .parse::<u8>()
+                    .map_err(|e| format!("Invalid shift '{}': {}", value, e))?;
+                options.shift = Some(shift % 26);
+            }
+            "style" => match value {
+                "ascii" => options.ascii = true,
+                "unicode" => options.ascii = false,
+                _ => return Err(format!("Unknown style '{}', use ascii or unicode", value)),
+            },
+            _ => return Err(format!("Unknown option '{}'", name)),
+        }
+    }
+    Ok(options)
+}
+
+// A bar for `fraction` of the full width, padded with spaces to the full width.
+fn bar(fraction: f64, width: usize, ascii: bool) -> String {
+    let mut bar = if ascii {
+        "#".repeat((fraction * width as f64).round() as usize)
+    } else {
+        let eighths = (fraction * width as f64 * 8.0).round() as usize;
+        let mut bar = "█".repeat(eighths / 8);
+        if !eighths.is_multiple_of(8) {
+            bar.push(EIGHTHS[eighths % 8 - 1]);
+        }
+        bar
+    };
+    let len = bar.chars().count();
+    bar.extend(std::iter::repeat_n(' ', width.saturating_sub(len)));
+    bar
+}
+
+// Moves every letter of an uppercase n-gram back by `shift`.
+fn unshift(ngram: &str, shift: u8) -> String {
+    ngram
+        .bytes()
+        .map(|b| (b'A' + (b - b'A' + 26 - shift) % 26) as char)
+        .collect()
+}
+
+pub fn render_histogram(ciphertext: &str, options: &HistogramOptions) -> Result<String, String> {
+    let n = options.n;
+    let cipher = ngram_frequency_distribution(&letters(ciphertext), n);
+    if cipher.is_empty() {
+        return Err(format!("The ciphertext has no {}-letter n-grams", n));
+    }
+    let english = english_distribution(n);
+
+    let rows: Vec<String> = if n == 1 {
+        ('A'..='Z').map(String::from).collect()
+    } else {
+        // Ties in alphabetical order, as the map's own order varies from run to run.
+        let mut common: Vec<(&String, &f32)> = cipher.iter().collect();
+        common.sort_by(|a, b| b.1.total_cmp(a.1).then(a.0.cmp(b.0)));
+        common
+            .into_iter()
+            .take(options.top)
+            .map(|(ngram, _)| ngram.clone())
+            .collect()
+    };
+
+    let lookup = |distribution: &HashMap<String, f32>, ngram: &str| {
+        distribution.get(ngram).map_or(0.0, |&f| f as f64)
+    };
+    let mut columns: Vec<(String, Vec<f64>)> = vec![
+        (
+            "ciphertext".to_string(),
+            rows.iter().map(|g| lookup(&cipher, g)).collect(),
+        ),
+        (
+            "english".to_string(),
+            rows.iter().map(|g| lookup(&english, g)).collect(),
+        ),
+    ];
+    if let Some(shift) = options.shift {
+        columns.push((
+            format!("english+{}", shift),
+            rows.iter()
+                .map(|g| lookup(&english, &unshift(g, shift)))
+                .collect(),
+        ));
+    }
+
+    // One scale for every column, so that bars of the same length mean the same thing.
+    let max = columns
+        .iter()
+        .flat_map(|(_, values)| values.iter().copied())
+        .fold(0.0, f64::max);
+    let column_width = options.width + 8;
+    let mut lines = vec![];
+    let header: String = columns
+        .iter()
+        .map(|(title, _)| format!("{:<w$}", title, w = column_width))
+        .collect::<Vec<_>>()
+        .join("  ");
+    lines.push(format!("{:<n$}  {}", "", header, n = n));
+    for (row, ngram) in rows.iter().enumerate() {
+        let cells: Vec<String> = columns
+            .iter()
+            .map(|(_, values)| {
+                let value = values[row];
+                format!(
+                    "{:>6.2}% {}",
+                    value * 100.0,
+                    bar(value / max, options.width, options.ascii)
+                )
+            })
+            .collect();
+        lines.push(format!("{}  {}", ngram, cells.join("  ")));
+    }
+    Ok(lines
+        .iter()
+        .map(|line| line.trim_end().to_string() + "\n")
+        .collect())
+}
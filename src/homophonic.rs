@@ -0,0 +1,355 @@
+// src/homophonic.rs
+//
+// Homophonic substitution: each plaintext letter is replaced by one of several
+// two-digit symbols, with common letters getting more symbols so that the symbol
+// frequencies come out roughly flat. The solver hill-climbs a symbol-to-letter
+// assignment on n-gram fitness, restarting from random perturbations.
+
+use crate::ceasar::{numbers_to_string, string_to_numbers};
+use crate::ngram::NgramModel;
+use crate::vigenere_cracker::english_letter_frequencies;
+use crypto_converter::rng::Rng;
+use std::fmt;
+
+#[derive(Clone, Debug)]
+pub struct HomophonicKey {
+    // The symbols (0-99) for each letter A to Z.
+    symbols: Vec<Vec<u8>>,
+}
+
+impl HomophonicKey {
+    // Shares `symbol_count` symbols (26-100) among the letters in proportion to English
+    // letter frequencies, every letter getting at least one, and deals them out at random.
+    pub fn generate(symbol_count: usize, rng: &mut Rng) -> Result<HomophonicKey, String> {
+        if !(26..=100).contains(&symbol_count) {
+            return Err("A key needs between 26 and 100 symbols".to_string());
+        }
+        let spare = (symbol_count - 26) as f64;
+        let quotas: Vec<f64> = english_letter_frequencies()
+            .iter()
+            .map(|f| f * spare)
+            .collect();
+        let mut counts: Vec<usize> = quotas.iter().map(|q| 1 + *q as usize).collect();
+        let mut by_remainder: Vec<usize> = (0..26).collect();
+        by_remainder.sort_by(|&a, &b| (quotas[b].fract()).partial_cmp(&quotas[a].fract()).unwrap());
+        let allocated: usize = counts.iter().sum();
+        for &letter in by_remainder.iter().take(symbol_count - allocated) {
+            counts[letter] += 1;
+        }
+
+        let mut pool: Vec<u8> = (0..100).collect();
+        rng.shuffle(&mut pool);
+        let mut pool = pool.into_iter();
+        let symbols = counts
+            .iter()
+            .map(|&count| {
+                let mut symbols: Vec<u8> = pool.by_ref().take(count).collect();
+                symbols.sort();
+                symbols
+            })
+            .collect();
+        Ok(HomophonicKey { symbols })
+    }
+
+    // Parses the Display format, "A=12,45,67 B=03 ...", with every letter present.
+    pub fn parse(key: &str) -> Result<HomophonicKey, String> {
+        let mut symbols = vec![vec![]; 26];
+        let mut used = [false; 100];
+        for field in key.split_whitespace() {
+            let (letter, list) = field
+                .split_once('=')
+                .ok_or(format!("Expected letter=symbols, found '{}'", field))?;
+            let letter = match letter.chars().collect::<Vec<_>>().as_slice() {
+                &[c] => crate::ceasar::letter_to_number(c),
+                _ => None,
+            }
+            .ok_or(format!("Invalid letter '{}'", letter))?;
+            for symbol in parse_symbols(&list.replace(',', " "))? {
+                if used[symbol as usize] {
+                    return Err(format!("Symbol {:02} is used twice", symbol));
+                }
+                used[symbol as usize] = true;
+                symbols[letter as usize].push(symbol);
+            }
+        }
+        if let Some(letter) = symbols.iter().position(|s| s.is_empty()) {
+            return Err(format!("No symbols for {}", (b'A' + letter as u8) as char));
+        }
+        Ok(HomophonicKey { symbols })
+    }
+
+    fn letter_of(&self, symbol: u8) -> Option<u8> {
+        self.symbols
+            .iter()
+            .position(|s| s.contains(&symbol))
+            .map(|letter| letter as u8)
+    }
+}
+
+impl fmt::Display for HomophonicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fields: Vec<String> = self
+            .symbols
+            .iter()
+            .enumerate()
+            .map(|(letter, symbols)| {
+                let list: Vec<String> = symbols.iter().map(|s| format!("{:02}", s)).collect();
+                format!("{}={}", (b'A' + letter as u8) as char, list.join(","))
+            })
+            .collect();
+        write!(f, "{}", fields.join(" "))
+    }
+}
+
+// Reads two-digit symbols, either separated by whitespace or run together.
+pub fn parse_symbols(text: &str) -> Result<Vec<u8>, String> {
+    let digits: String = text.split_whitespace().collect();
+    if !digits.len().is_multiple_of(2) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err("Symbols must be two-digit numbers".to_string());
+    }
+    Ok(digits
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| (pair[0] - b'0') * 10 + pair[1] - b'0')
+        .collect())
+}
+
+// Each letter cycles through its homophones in turn, which keeps the output
+// reproducible while still spreading the letter over all of its symbols.
+pub fn homophonic_encrypt(plaintext: &str, key: &HomophonicKey) -> String {
+    let mut next = [0usize; 26];
+    string_to_numbers(plaintext)
+        .iter()
+        .map(|&n| {
+            let symbols = &key.symbols[n as usize];
+            let symbol = symbols[next[n as usize] % symbols.len()];
+            next[n as usize] += 1;
+            format!("{:02}", symbol)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn homophonic_decrypt(ciphertext: &str, key: &HomophonicKey) -> Result<String, String> {
+    let numbers = parse_symbols(ciphertext)?
+        .iter()
+        .map(|&symbol| {
+            key.letter_of(symbol)
+                .ok_or(format!("Symbol {:02} is not in the key", symbol))
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+    Ok(numbers_to_string(&numbers))
+}
+
+pub struct SolverOptions {
+    pub restarts: usize,
+    pub iterations: usize,
+    pub seed: u64,
+}
+
+impl Default for SolverOptions {
+    fn default() -> Self {
+        SolverOptions {
+            restarts: 5,
+            iterations: 500,
+            seed: 1,
+        }
+    }
+}
+
+// Parses "restarts=5 iterations=500 seed=1". Each iteration is one kick and climb.
+pub fn parse_solver_options(s: &str) -> Result<SolverOptions, String> {
+    let mut options = SolverOptions::default();
+    for field in s.split_whitespace() {
+        let (name, value) = field
+            .split_once('=')
+            .ok_or(format!("Expected name=value, found '{}'", field))?;
+        let number = value
+            .parse::<u64>()
+            .map_err(|e| format!("Invalid {} '{}': {}", name, value, e))?;
+        match name.to_ascii_lowercase().as_str() {
+            "restarts" => options.restarts = number.max(1) as usize,
+            "iterations" => options.iterations = number as usize,
+            "seed" => options.seed = number,
+            _ => return Err(format!("Unknown option '{}'", name)),
+        }
+    }
+    Ok(options)
+}
+
+// How strongly the letter frequencies are held to English, and how many symbols are
+// reassigned at random to kick the climber out of a local optimum.
+const CHI_WEIGHT: f64 = 1.0;
+const KICK: usize = 4;
+
+pub struct Solution {
+    // The letter (0-25) assigned to each symbol 0-99 that occurs in the ciphertext.
+    pub assignment: Vec<Option<u8>>,
+    pub plaintext: String,
+    pub score: f64,
+}
+
+// A trial decryption with its trigram score and letter counts, so that reassigning one
+// symbol only rescores the trigrams around the places it occurs.
+#[derive(Clone)]
+struct Decryption<'a> {
+    // Where each distinct symbol occurs in the ciphertext, and the starts of the
+    // trigrams that overlap those places.
+    positions: &'a [Vec<usize>],
+    windows: &'a [Vec<usize>],
+    letters: Vec<u8>,
+    text: Vec<u8>,
+    counts: [usize; 26],
+    trigrams: f64,
+}
+
+impl<'a> Decryption<'a> {
+    fn new(
+        positions: &'a [Vec<usize>],
+        windows: &'a [Vec<usize>],
+        length: usize,
+        letters: Vec<u8>,
+    ) -> Decryption<'a> {
+        let mut text = vec![0; length];
+        let mut counts = [0; 26];
+        for (symbol, places) in positions.iter().enumerate() {
+            for &p in places {
+                text[p] = letters[symbol];
+            }
+            counts[letters[symbol] as usize] += places.len();
+        }
+        let trigrams = NgramModel::english(3).score_numbers(&text);
+        Decryption {
+            positions,
+            windows,
+            letters,
+            text,
+            counts,
+            trigrams,
+        }
+    }
+
+    // Trigram score less a chi-squared penalty on the letter frequencies. Without the
+    // penalty the search drifts toward texts made only of E, T and H.
+    fn fitness(&self) -> f64 {
+        let length = self.text.len() as f64;
+        let chi_squared: f64 = self
+            .counts
+            .iter()
+            .zip(english_letter_frequencies())
+            .map(|(&count, &f)| (count as f64 - f * length).powi(2) / (f * length))
+            .sum();
+        self.trigrams - CHI_WEIGHT * chi_squared
+    }
+
+    fn assign(&mut self, symbol: usize, letter: u8) {
+        let model = NgramModel::english(3);
+        let starts = &self.windows[symbol];
+        let score = |text: &[u8]| -> f64 {
+            starts
+                .iter()
+                .map(|&s| model.score_numbers(&text[s..s + 3]))
+                .sum()
+        };
+        let before = score(&self.text);
+        let places = &self.positions[symbol];
+        for &p in places {
+            self.text[p] = letter;
+        }
+        self.counts[self.letters[symbol] as usize] -= places.len();
+        self.counts[letter as usize] += places.len();
+        self.letters[symbol] = letter;
+        self.trigrams += score(&self.text) - before;
+    }
+
+    // Tries every letter for every symbol, keeping any improvement, until none helps.
+    fn climb(&mut self) -> f64 {
+        let mut best = self.fitness();
+        loop {
+            let mut improved = false;
+            for symbol in 0..self.letters.len() {
+                let old = self.letters[symbol];
+                let mut keep = old;
+                for letter in 0..26 {
+                    if letter == old {
+                        continue;
+                    }
+                    self.assign(symbol, letter);
+                    let score = self.fitness();
+                    if score > best {
+                        best = score;
+                        keep = letter;
+                        improved = true;
+                    }
+                }
+                self.assign(symbol, keep);
+            }
+            if !improved {
+                return best;
+            }
+        }
+    }
+}
+
+// Iterated local search: climb from a random assignment, then repeatedly reassign a few
+// symbols at random and climb again, keeping the result whenever it is no worse.
+pub fn solve_homophonic(ciphertext: &str, options: &SolverOptions) -> Result<Solution, String> {
+    let symbols = parse_symbols(ciphertext)?;
+    if symbols.is_empty() {
+        return Err("Nothing to solve".to_string());
+    }
+    let mut distinct: Vec<u8> = symbols.clone();
+    distinct.sort();
+    distinct.dedup();
+    let mut positions = vec![vec![]; distinct.len()];
+    for (p, symbol) in symbols.iter().enumerate() {
+        positions[distinct.binary_search(symbol).unwrap()].push(p);
+    }
+    let windows: Vec<Vec<usize>> = positions
+        .iter()
+        .map(|places| {
+            let mut starts: Vec<usize> = places
+                .iter()
+                .flat_map(|&p| p.saturating_sub(2)..=p)
+                .filter(|&s| s + 3 <= symbols.len())
+                .collect();
+            starts.sort();
+            starts.dedup();
+            starts
+        })
+        .collect();
+
+    let mut rng = Rng::new(options.seed);
+    let mut best: Option<(f64, Decryption)> = None;
+    for _ in 0..options.restarts {
+        let letters = (0..distinct.len()).map(|_| rng.below(26) as u8).collect();
+        let mut current = Decryption::new(&positions, &windows, symbols.len(), letters);
+        let mut score = current.climb();
+        for _ in 0..options.iterations {
+            let mut trial = current.clone();
+            for _ in 0..KICK {
+                let symbol = rng.below(distinct.len() as u64) as usize;
+                trial.assign(symbol, rng.below(26) as u8);
+            }
+            let trial_score = trial.climb();
+            if trial_score >= score {
+                score = trial_score;
+                current = trial;
+            }
+        }
+        if best.as_ref().is_none_or(|(s, _)| score > *s) {
+            best = Some((score, current));
+        }
+    }
+
+    let (score, decryption) = best.unwrap();
+    let mut assignment = vec![None; 100];
+    for (&symbol, &letter) in distinct.iter().zip(&decryption.letters) {
+        assignment[symbol as usize] = Some(letter);
+    }
+    Ok(Solution {
+        assignment,
+        plaintext: numbers_to_string(&decryption.text),
+        score: score / symbols.len() as f64,
+    })
+}
@@ -0,0 +1,602 @@
+// src/identify.rs
+//
+// Guesses which kind of cipher produced an unknown ciphertext. A handful of statistics
+// that separate the classical families are measured first: the index of coincidence
+// overall and at each period, the character set, the length, doubled letters, repeats
+// and entropy. Each family is then scored from the statistics, with the reasons for and
+// against it, and the families are ranked.
+
+use crate::cryptanalysis::{
+    chi_squared, index_of_coincidence, index_of_coincidence_of_counts, letter_counts,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+const ENGLISH_IOC: f64 = 0.0667;
+const RANDOM_IOC: f64 = 1.0 / 26.0;
+// Columns shorter than this give too noisy an index of coincidence to be worth reading.
+const MIN_COLUMN: usize = 5;
+const MAX_PERIOD: usize = 20;
+
+pub struct Statistics {
+    // Characters other than whitespace, or letters in alphabetic text.
+    pub length: usize,
+    // Distinct symbols among them, ignoring case in alphabetic text.
+    pub symbols: usize,
+    // Letters with nothing but punctuation besides, in which case the statistics below
+    // are taken over the letters alone.
+    pub alphabetic: bool,
+    pub digits_only: bool,
+    pub missing_letters: Vec<char>,
+    // Adjacent equal symbols anywhere, and within the pairs 1-2, 3-4, ...
+    pub doubled: usize,
+    pub doubled_pairs: usize,
+    pub ioc: f64,
+    // The average index of coincidence of the columns at periods 1, 2, ...
+    pub periodic_ioc: Vec<f64>,
+    pub period: Option<usize>,
+    pub repeated_trigrams: usize,
+    pub longest_repeat: usize,
+    // Shannon entropy over the size of the alphabet, so 1.0 is perfectly flat.
+    pub entropy: f64,
+    // Letter counts against English; only meaningful for alphabetic text.
+    pub chi_squared: f64,
+    // The Caesar shift that best maps the letter counts onto English, with its statistic.
+    pub best_shift: (u8, f64),
+    tokens: Vec<String>,
+    chars: Vec<char>,
+}
+
+fn ioc_of<T: Copy + Eq + Hash>(stream: &[T]) -> f64 {
+    let mut counts: HashMap<T, usize> = HashMap::new();
+    for &c in stream {
+        *counts.entry(c).or_insert(0) += 1;
+    }
+    index_of_coincidence_of_counts(&counts.into_values().collect::<Vec<_>>())
+}
+
+// The length of the longest run of symbols that appears twice.
+fn longest_repeat(stream: &[char]) -> usize {
+    let mut longest = 0;
+    for distance in 1..stream.len() {
+        let mut run = 0;
+        for i in distance..stream.len() {
+            if stream[i] == stream[i - distance] {
+                run += 1;
+                longest = longest.max(run);
+            } else {
+                run = 0;
+            }
+        }
+    }
+    longest
+}
+
+impl Statistics {
+    pub fn new(text: &str) -> Statistics {
+        let chars: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+        let alphabetic = chars.iter().any(|c| c.is_ascii_alphabetic())
+            && chars
+                .iter()
+                .all(|c| c.is_ascii_alphabetic() || ".,;:!?'\"()".contains(*c));
+        let stream: Vec<char> = if alphabetic {
+            chars
+                .iter()
+                .filter(|c| c.is_ascii_alphabetic())
+                .map(|c| c.to_ascii_uppercase())
+                .collect()
+        } else {
+            chars.clone()
+        };
+        let digits_only = !chars.is_empty() && chars.iter().all(|c| c.is_ascii_digit());
+
+        let mut counts: HashMap<char, usize> = HashMap::new();
+        for &c in &stream {
+            *counts.entry(c).or_insert(0) += 1;
+        }
+        let letters = letter_counts(text);
+        let missing_letters = if alphabetic {
+            ('A'..='Z')
+                .filter(|&c| letters[(c as u8 - b'A') as usize] == 0)
+                .collect()
+        } else {
+            vec![]
+        };
+        let ioc = if alphabetic {
+            index_of_coincidence(text)
+        } else {
+            ioc_of(&stream)
+        };
+
+        let periodic_ioc: Vec<f64> = (1..=MAX_PERIOD.min(stream.len() / MIN_COLUMN))
+            .map(|period| {
+                let columns: Vec<f64> = (0..period)
+                    .map(|column| {
+                        let column: Vec<char> = stream
+                            .iter()
+                            .skip(column)
+                            .step_by(period)
+                            .copied()
+                            .collect();
+                        ioc_of(&column)
+                    })
+                    .collect();
+                columns.iter().sum::<f64>() / period as f64
+            })
+            .collect();
+        // Multiples of the true period rise as well as the period itself, so the
+        // shortest period that lifts the columns well above the whole text is taken.
+        let period = (2..=periodic_ioc.len())
+            .find(|&p| periodic_ioc[p - 1] >= 0.055 && periodic_ioc[p - 1] > 1.25 * ioc);
+
+        let mut trigrams: HashMap<&[char], usize> = HashMap::new();
+        for window in stream.windows(3) {
+            *trigrams.entry(window).or_insert(0) += 1;
+        }
+        let repeated_trigrams = trigrams.values().filter(|&&n| n > 1).count();
+
+        let alphabet_size = if alphabetic {
+            26
+        } else if digits_only {
+            10
+        } else {
+            counts.len().max(2)
+        };
+        let total = stream.len() as f64;
+        let entropy = counts
+            .values()
+            .map(|&n| {
+                // p log(1/p) rather than -p log p, which is -0 for a single symbol.
+                let p = n as f64 / total;
+                p * (1.0 / p).log2()
+            })
+            .sum::<f64>()
+            / (alphabet_size as f64).log2();
+
+        let best_shift = (0..26u8)
+            .map(|shift| {
+                let mut shifted = [0usize; 26];
+                for (i, &n) in letters.iter().enumerate() {
+                    shifted[(i + 26 - shift as usize) % 26] = n;
+                }
+                (shift, chi_squared(&shifted))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+
+        Statistics {
+            length: stream.len(),
+            symbols: counts.len(),
+            alphabetic,
+            digits_only,
+            missing_letters,
+            doubled: stream.windows(2).filter(|w| w[0] == w[1]).count(),
+            doubled_pairs: stream.chunks_exact(2).filter(|p| p[0] == p[1]).count(),
+            ioc,
+            periodic_ioc,
+            period,
+            repeated_trigrams,
+            longest_repeat: longest_repeat(&stream),
+            entropy,
+            chi_squared: chi_squared(&letters),
+            best_shift,
+            tokens: text.split_whitespace().map(String::from).collect(),
+            chars,
+        }
+    }
+
+    // The index of coincidence, or "n/a" with fewer than two symbols to pair up.
+    fn ioc_text(&self) -> String {
+        if self.length < 2 {
+            "n/a".to_string()
+        } else {
+            format!("{:.4}", self.ioc)
+        }
+    }
+
+    // Letter counts that look like English, allowing for the noise of a short text.
+    fn english_profile(&self) -> bool {
+        self.chi_squared < 40.0 + 0.15 * self.length as f64
+    }
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = if self.alphabetic {
+            "letters"
+        } else if self.digits_only {
+            "digits"
+        } else {
+            "mixed symbols"
+        };
+        writeln!(
+            f,
+            "Length: {} ({})",
+            self.length,
+            if self.length.is_multiple_of(2) {
+                "even"
+            } else {
+                "odd"
+            }
+        )?;
+        writeln!(f, "Symbols: {} distinct, {}", self.symbols, kind)?;
+        if self.alphabetic {
+            let missing: String = self.missing_letters.iter().collect();
+            writeln!(
+                f,
+                "Missing letters: {}",
+                if missing.is_empty() { "none" } else { &missing }
+            )?;
+        }
+        writeln!(f, "Index of coincidence: {}", self.ioc_text())?;
+        let peak = match self.period {
+            Some(p) => format!(", peak at period {} ({:.4})", p, self.periodic_ioc[p - 1]),
+            None => String::new(),
+        };
+        let periods: Vec<String> = self
+            .periodic_ioc
+            .iter()
+            .enumerate()
+            .map(|(i, ioc)| format!("{}:{:.3}", i + 1, ioc))
+            .collect();
+        if periods.is_empty() {
+            writeln!(f, "Periodic IoC: n/a")?;
+        } else {
+            writeln!(f, "Periodic IoC: {}{}", periods.join(" "), peak)?;
+        }
+        writeln!(
+            f,
+            "Doubled symbols: {} ({} within pairs)",
+            self.doubled, self.doubled_pairs
+        )?;
+        writeln!(
+            f,
+            "Repeats: {} repeated trigrams, longest repeat {}",
+            self.repeated_trigrams, self.longest_repeat
+        )?;
+        writeln!(f, "Normalized entropy: {:.3}", self.entropy)?;
+        if self.alphabetic {
+            writeln!(
+                f,
+                "Chi-squared against English: {:.1} (best Caesar shift {}: {:.1})",
+                self.chi_squared, self.best_shift.0, self.best_shift.1
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Candidate {
+    pub family: &'static str,
+    pub score: f64,
+    pub reasons: Vec<String>,
+}
+
+// 1.0 at the target, falling linearly to 0.0 at `tolerance` away from it.
+fn closeness(value: f64, target: f64, tolerance: f64) -> f64 {
+    (1.0 - (value - target).abs() / tolerance).max(0.0)
+}
+
+fn alphabetic_candidates(stats: &Statistics) -> Vec<Candidate> {
+    let mut candidates = vec![];
+    let english_ioc = closeness(stats.ioc, ENGLISH_IOC, 0.02);
+    let relation = if (stats.ioc - ENGLISH_IOC).abs() < 0.01 {
+        "close to"
+    } else if stats.ioc < ENGLISH_IOC {
+        "below"
+    } else {
+        "above"
+    };
+    let ioc_reason = if stats.length < 2 {
+        "IoC n/a, as fewer than two letters cannot be compared with English".to_string()
+    } else {
+        format!(
+            "IoC {} is {} English ({:.4})",
+            stats.ioc_text(),
+            relation,
+            ENGLISH_IOC
+        )
+    };
+    let english = stats.english_profile();
+
+    let mut reasons = vec![ioc_reason.clone()];
+    reasons.push(if english {
+        format!(
+            "the letter counts match English (chi-squared {:.1}), so the letters are only rearranged",
+            stats.chi_squared
+        )
+    } else {
+        format!("the letter counts differ from English (chi-squared {:.1})", stats.chi_squared)
+    });
+    candidates.push(Candidate {
+        family: "Transposition",
+        score: english_ioc * if english { 1.0 } else { 0.15 },
+        reasons,
+    });
+
+    let mut reasons = vec![ioc_reason.clone()];
+    if english {
+        reasons.push("the letter counts already match English".to_string());
+    } else {
+        reasons.push("the letter counts have the shape of English under other letters".to_string());
+    }
+    let (shift, shifted) = stats.best_shift;
+    if shift != 0 && shifted < 40.0 + 0.15 * stats.length as f64 {
+        reasons.push(format!(
+            "shifting by {} matches English (chi-squared {:.1}), a Caesar cipher",
+            shift, shifted
+        ));
+    }
+    candidates.push(Candidate {
+        family: "Monoalphabetic substitution",
+        score: english_ioc * if english { 0.25 } else { 1.0 },
+        reasons,
+    });
+
+    let flat = 1.0 - english_ioc;
+    let mut reasons = vec![ioc_reason.clone()];
+    let score = match stats.period {
+        Some(period) => {
+            let column_ioc = stats.periodic_ioc[period - 1];
+            reasons.push(format!(
+                "the IoC rises to {:.4} when the text is split into {} columns",
+                column_ioc, period
+            ));
+            if stats.repeated_trigrams > 0 {
+                reasons.push(format!(
+                    "{} repeated trigrams for a Kasiski examination",
+                    stats.repeated_trigrams
+                ));
+            }
+            flat * closeness(column_ioc, ENGLISH_IOC, 0.015).max(0.2)
+        }
+        None => {
+            reasons.push("no period raises the IoC of the columns".to_string());
+            0.1 * flat
+        }
+    };
+    candidates.push(Candidate {
+        family: "Periodic polyalphabetic (Vigenère, Beaufort, Porta)",
+        score,
+        reasons,
+    });
+
+    let mut reasons = vec![ioc_reason.clone()];
+    let mut score = closeness(stats.ioc, 0.05, 0.015);
+    if stats.length.is_multiple_of(2) {
+        reasons.push("the length is even".to_string());
+    } else {
+        reasons.push("the length is odd, but Playfair writes letters in pairs".to_string());
+        score = 0.0;
+    }
+    if stats.doubled_pairs == 0 {
+        reasons.push("no pair holds a doubled letter".to_string());
+    } else {
+        reasons.push(format!(
+            "{} pairs hold a doubled letter, which Playfair never produces",
+            stats.doubled_pairs
+        ));
+        score = 0.0;
+    }
+    if stats.missing_letters.is_empty() {
+        reasons.push("all 26 letters appear, but the square has 25".to_string());
+        score = 0.0;
+    } else {
+        reasons.push("at most 25 letters appear, as from a 5x5 square".to_string());
+    }
+    candidates.push(Candidate {
+        family: "Playfair",
+        score,
+        reasons,
+    });
+
+    let mut reasons = vec![ioc_reason.clone()];
+    let mut score = closeness(stats.ioc, 0.047, 0.012);
+    if stats.missing_letters.is_empty() {
+        reasons.push("all 26 letters appear, but the square has 25".to_string());
+        score *= 0.3;
+    } else {
+        reasons.push("at most 25 letters appear, as from a 5x5 square".to_string());
+    }
+    if stats.period.is_some() {
+        reasons.push("a periodic IoC peak points to a periodic cipher instead".to_string());
+        score *= 0.5;
+    }
+    if stats.doubled_pairs > 0 {
+        reasons.push("doubled letters within pairs rule out Playfair but not Bifid".to_string());
+    }
+    candidates.push(Candidate {
+        family: "Bifid or another fractionating square",
+        score,
+        reasons,
+    });
+
+    let mut reasons = vec![ioc_reason.clone()];
+    let mut score = closeness(stats.ioc, 0.042, 0.008);
+    if stats.period.is_none() {
+        reasons.push("no period raises the IoC of the columns".to_string());
+    } else {
+        score *= 0.3;
+    }
+    candidates.push(Candidate {
+        family: "Aperiodic polyalphabetic (autokey, running key, rotor machine)",
+        score,
+        reasons,
+    });
+
+    let reasons = vec![
+        ioc_reason,
+        format!("normalized entropy {:.3}", stats.entropy),
+    ];
+    candidates.push(Candidate {
+        family: "One-time pad or random letters",
+        score: closeness(stats.ioc, RANDOM_IOC, 0.004) * stats.entropy * 0.8,
+        reasons,
+    });
+
+    if stats.symbols <= 2 {
+        candidates.push(Candidate {
+            family: "Baconian",
+            score: 0.95,
+            reasons: vec![format!("only {} distinct letters appear", stats.symbols)],
+        });
+    }
+    candidates
+}
+
+fn numeric_candidates(stats: &Statistics) -> Vec<Candidate> {
+    let mut candidates = vec![];
+    let digits = &stats.chars;
+    let numbers: Option<Vec<u32>> = stats.tokens.iter().map(|t| t.parse().ok()).collect();
+
+    if digits.iter().all(|c| ('1'..='5').contains(c)) && stats.length.is_multiple_of(2) {
+        candidates.push(Candidate {
+            family: "Polybius square",
+            score: 0.9,
+            reasons: vec!["only the digits 1-5 appear, in pairs".to_string()],
+        });
+    }
+
+    // Two-digit symbols repeat more often when the digits are read in the pairs they
+    // were written in than when the pairs straddle two symbols.
+    let pairs = |start: usize| -> Vec<(char, char)> {
+        digits[start..]
+            .chunks_exact(2)
+            .map(|p| (p[0], p[1]))
+            .collect()
+    };
+    let aligned = ioc_of(&pairs(0));
+    let straddled = ioc_of(&pairs(1));
+    let paired = aligned > 1.4 * straddled;
+
+    if stats.length.is_multiple_of(2) {
+        let mut distinct = pairs(0);
+        distinct.sort_unstable();
+        distinct.dedup();
+        let mut reasons = vec![format!(
+            "{} distinct two-digit symbols, against 26 letters",
+            distinct.len()
+        )];
+        let mut score = 0.2;
+        if distinct.len() > 26 {
+            score += 0.35;
+        }
+        if paired {
+            reasons.push(format!(
+                "pairs read from the first digit repeat more than those from the second \
+                 (IoC {:.4} against {:.4})",
+                aligned, straddled
+            ));
+            score += 0.35;
+        }
+        if stats.tokens.iter().all(|t| t.len() == 2) {
+            reasons.push("the digits are written in groups of two".to_string());
+            score += 0.1;
+        }
+        candidates.push(Candidate {
+            family: "Homophonic substitution",
+            score,
+            reasons,
+        });
+    }
+
+    if let Some(numbers) = &numbers {
+        // Sums of two Polybius coordinates, 11 to 55 each, never end in a 1.
+        if numbers.len() > 1
+            && numbers
+                .iter()
+                .all(|&n| (22..=110).contains(&n) && n % 10 != 1)
+        {
+            candidates.push(Candidate {
+                family: "Nihilist substitution",
+                score: 0.9,
+                reasons: vec![
+                    "every number is a possible sum of two Polybius coordinates".to_string()
+                ],
+            });
+        }
+        if numbers.iter().all(|&n| (1..=26).contains(&n)) && numbers.len() > 1 {
+            candidates.push(Candidate {
+                family: "Letter numbers (A1Z26)",
+                score: 0.8,
+                reasons: vec!["every number is between 1 and 26".to_string()],
+            });
+        }
+    }
+
+    let mut reasons = vec![format!("{} of the 10 digits appear", stats.symbols)];
+    let mut score = 0.2;
+    if stats.symbols >= 9 && stats.ioc > 0.105 {
+        reasons.push(format!(
+            "the digit IoC {:.4} is uneven, as frequent letters take one digit",
+            stats.ioc
+        ));
+        score += 0.4;
+    }
+    if !paired {
+        reasons.push("the digits do not fall into fixed pairs".to_string());
+        score += 0.2;
+    }
+    candidates.push(Candidate {
+        family: "Straddling checkerboard (VIC, Nihilist substitution)",
+        score,
+        reasons,
+    });
+    candidates
+}
+
+fn symbol_candidates(stats: &Statistics) -> Vec<Candidate> {
+    let mut candidates = vec![];
+    let chars = &stats.chars;
+    if chars.iter().all(|c| ".-/|".contains(*c)) {
+        candidates.push(Candidate {
+            family: "Morse code",
+            score: 0.95,
+            reasons: vec!["only dots, dashes and separators appear".to_string()],
+        });
+    }
+    if chars.iter().all(|c| c.is_ascii_hexdigit()) && stats.length.is_multiple_of(2) {
+        candidates.push(Candidate {
+            family: "Hexadecimal encoding",
+            score: 0.8,
+            reasons: vec!["only hexadecimal digits appear, in pairs".to_string()],
+        });
+    }
+    let base64 = |c: &char| c.is_ascii_alphanumeric() || "+/=-_".contains(*c);
+    if chars.iter().all(base64) && stats.tokens.len() == 1 {
+        let mut reasons = vec!["only base64 characters appear, with no spaces".to_string()];
+        let mut score = 0.5;
+        if stats.length.is_multiple_of(4) {
+            reasons.push("the length is a multiple of 4".to_string());
+            score = 0.85;
+        }
+        candidates.push(Candidate {
+            family: "Base64 encoding",
+            score,
+            reasons,
+        });
+    }
+    candidates.push(Candidate {
+        family: "Modern cipher or binary data",
+        score: 0.3 * stats.entropy.min(1.0),
+        reasons: vec![format!(
+            "{} distinct symbols with normalized entropy {:.3}",
+            stats.symbols, stats.entropy
+        )],
+    });
+    candidates
+}
+
+// The candidate families, best first.
+pub fn identify(stats: &Statistics) -> Vec<Candidate> {
+    let mut candidates = if stats.alphabetic {
+        alphabetic_candidates(stats)
+    } else if stats.digits_only {
+        numeric_candidates(stats)
+    } else {
+        symbol_candidates(stats)
+    };
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates
+}
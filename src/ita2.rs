@@ -0,0 +1,95 @@
+// src/ita2.rs
+//
+// The ITA2 (Baudot-Murray) five-bit teleprinter alphabet. Codes are written with
+// bit 1 as the most significant bit, so 'A' (bits 1 and 2 marked) is 0b11000.
+
+pub const FIGS: u8 = 0b11011;
+pub const LTRS: u8 = 0b11111;
+
+// Letters and figures by code; '\0' marks codes with no printable meaning in that shift.
+const LETTERS: [char; 32] = [
+    '\0', 'T', '\n', 'O', ' ', 'H', 'N', 'M', '\r', 'L', 'R', 'G', 'I', 'P', 'C', 'V', 'E', 'Z',
+    'D', 'B', 'S', 'Y', 'F', 'X', 'A', 'W', 'J', '\0', 'U', 'Q', 'K', '\0',
+];
+const FIGURES: [char; 32] = [
+    '\0', '5', '\n', '9', ' ', '\0', ',', '.', '\r', ')', '4', '\0', '8', '0', ':', '=', '3', '+',
+    '\0', '?', '\'', '6', '\0', '/', '-', '2', '\0', '\0', '7', '1', '(', '\0',
+];
+
+// Bletchley Park notation: letters stand for themselves and the six non-printing codes
+// are written as digits and '/'.
+const BLETCHLEY: [char; 32] = [
+    '/', 'T', '3', 'O', '9', 'H', 'N', 'M', '4', 'L', 'R', 'G', 'I', 'P', 'C', 'V', 'E', 'Z', 'D',
+    'B', 'S', 'Y', 'F', 'X', 'A', 'W', 'J', '5', 'U', 'Q', 'K', '8',
+];
+
+// Encodes text, inserting FIGS and LTRS shifts as needed. Characters outside ITA2 are dropped.
+pub fn text_to_ita2(text: &str) -> Vec<u8> {
+    let mut codes = vec![];
+    let mut figures = false;
+    for c in text.chars().map(|c| c.to_ascii_uppercase()) {
+        if c == '\0' {
+            continue;
+        }
+        let letter = LETTERS.iter().position(|&l| l == c);
+        let figure = FIGURES.iter().position(|&f| f == c);
+        match (letter, figure) {
+            (Some(code), Some(_)) => codes.push(code as u8),
+            (Some(code), None) => {
+                if figures {
+                    codes.push(LTRS);
+                    figures = false;
+                }
+                codes.push(code as u8);
+            }
+            (None, Some(code)) => {
+                if !figures {
+                    codes.push(FIGS);
+                    figures = true;
+                }
+                codes.push(code as u8);
+            }
+            (None, None) => {}
+        }
+    }
+    codes
+}
+
+// Decodes five-bit codes, starting in letter shift.
+pub fn ita2_to_text(codes: &[u8]) -> String {
+    let mut text = String::new();
+    let mut figures = false;
+    for &code in codes {
+        match code & 0x1f {
+            FIGS => figures = true,
+            LTRS => figures = false,
+            code => {
+                let c = if figures { FIGURES } else { LETTERS }[code as usize];
+                if c != '\0' {
+                    text.push(c);
+                }
+            }
+        }
+    }
+    text
+}
+
+pub fn to_bletchley(codes: &[u8]) -> String {
+    codes
+        .iter()
+        .map(|&code| BLETCHLEY[(code & 0x1f) as usize])
+        .collect()
+}
+
+pub fn from_bletchley(s: &str) -> Result<Vec<u8>, String> {
+    s.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| {
+            BLETCHLEY
+                .iter()
+                .position(|&b| b == c.to_ascii_uppercase())
+                .map(|code| code as u8)
+                .ok_or(format!("'{}' is not an ITA2 character", c))
+        })
+        .collect()
+}
@@ -1,7 +1,293 @@
-pub fn gcd(n: u32, m: u32) -> u32 {
-    if m == 0 {
+pub mod bigint;
+pub mod contfrac;
+pub mod dlog;
+pub mod factor;
+pub mod number_theory;
+pub mod primes;
+pub mod residues;
+pub mod rng;
+
+use bigint::BigUint;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Rem, Sub};
+
+// What the number-theory routines need of an unsigned integer. It is implemented for
+// every primitive width, and the routines are generic over it.
+pub trait Unsigned:
+    Clone
+    + Ord
+    + fmt::Debug
+    + fmt::Display
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+{
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn from_u64(n: u64) -> Option<Self>;
+    fn to_u64(&self) -> Option<u64>;
+    // Through BigUint every width shares the factorization toolkit.
+    fn to_biguint(&self) -> BigUint;
+    fn from_biguint(n: &BigUint) -> Option<Self>;
+    // None when the result does not fit in the type.
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+    // (self * other) % n for self and other below n, without overflowing.
+    fn mul_mod(&self, other: &Self, n: &Self) -> Self;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+}
+
+macro_rules! impl_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl Unsigned for $t {
+                fn zero() -> Self {
+                    0
+                }
+
+                fn one() -> Self {
+                    1
+                }
+
+                fn from_u64(n: u64) -> Option<Self> {
+                    Self::try_from(n).ok()
+                }
+
+                fn to_u64(&self) -> Option<u64> {
+                    u64::try_from(*self).ok()
+                }
+
+                fn to_biguint(&self) -> BigUint {
+                    BigUint::from_u128(*self as u128)
+                }
+
+                fn from_biguint(n: &BigUint) -> Option<Self> {
+                    n.to_u128().and_then(|n| Self::try_from(n).ok())
+                }
+
+                fn checked_add(&self, other: &Self) -> Option<Self> {
+                    <$t>::checked_add(*self, *other)
+                }
+
+                fn checked_mul(&self, other: &Self) -> Option<Self> {
+                    <$t>::checked_mul(*self, *other)
+                }
+
+                fn mul_mod(&self, other: &Self, n: &Self) -> Self {
+                    ((*self as u128 * *other as u128) % *n as u128) as Self
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned!(u8, u16, u32, u64, usize);
+
+impl Unsigned for u128 {
+    fn zero() -> Self {
+        0
+    }
+
+    fn one() -> Self {
+        1
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(n as u128)
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        u64::try_from(*self).ok()
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        BigUint::from_u128(*self)
+    }
+
+    fn from_biguint(n: &BigUint) -> Option<Self> {
+        n.to_u128()
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        u128::checked_add(*self, *other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        u128::checked_mul(*self, *other)
+    }
+
+    // There is no wider type to multiply in, so a product that would overflow is built
+    // by doubling and adding, reducing at every step.
+    fn mul_mod(&self, other: &Self, n: &Self) -> Self {
+        if let Some(product) = u128::checked_mul(*self, *other) {
+            return product % n;
+        }
+        let add_mod = |x: u128, y: u128| if x >= n - y { x - (n - y) } else { x + y };
+        let (mut a, mut b, mut result) = (self % n, other % n, 0);
+        while b > 0 {
+            if b & 1 == 1 {
+                result = add_mod(result, a);
+            }
+            a = add_mod(a, a);
+            b >>= 1;
+        }
+        result
+    }
+}
+
+pub fn gcd<T: Unsigned>(n: T, m: T) -> T {
+    if m.is_zero() {
         n
     } else {
-        gcd(m, n % m)
+        let r = n % m.clone();
+        gcd(m, r)
+    }
+}
+
+// A signed number as a sign and an unsigned magnitude, for the Bézout coefficients of
+// extended_gcd. Zero is never negative.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Signed<T> {
+    pub negative: bool,
+    pub magnitude: T,
+}
+
+impl<T: Unsigned> Signed<T> {
+    pub fn new(negative: bool, magnitude: T) -> Signed<T> {
+        Signed {
+            negative: negative && !magnitude.is_zero(),
+            magnitude,
+        }
+    }
+
+    fn add(&self, other: &Signed<T>) -> Signed<T> {
+        if self.negative == other.negative {
+            Signed::new(
+                self.negative,
+                self.magnitude.clone() + other.magnitude.clone(),
+            )
+        } else if self.magnitude >= other.magnitude {
+            Signed::new(
+                self.negative,
+                self.magnitude.clone() - other.magnitude.clone(),
+            )
+        } else {
+            Signed::new(
+                other.negative,
+                other.magnitude.clone() - self.magnitude.clone(),
+            )
+        }
+    }
+
+    // self - q * other
+    fn sub_mul(&self, q: &T, other: &Signed<T>) -> Signed<T> {
+        self.add(&Signed::new(
+            !other.negative,
+            q.clone() * other.magnitude.clone(),
+        ))
+    }
+
+    // The number reduced into 0..n.
+    pub fn rem_euclid(&self, n: &T) -> T {
+        let r = self.magnitude.clone() % n.clone();
+        if self.negative && !r.is_zero() {
+            n.clone() - r
+        } else {
+            r
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Signed<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", self.magnitude)
+    }
+}
+
+// Returns (g, x, y) with g = gcd(a, b) and a*x + b*y = g. The coefficients are the small
+// ones the Euclidean algorithm finds, |x| <= b/g and |y| <= a/g, so they fit in T.
+pub fn extended_gcd<T: Unsigned>(a: T, b: T) -> (T, Signed<T>, Signed<T>) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (Signed::new(false, T::one()), Signed::new(false, T::zero()));
+    let (mut old_t, mut t) = (Signed::new(false, T::zero()), Signed::new(false, T::one()));
+    while !r.is_zero() {
+        let q = old_r.clone() / r.clone();
+        let next_r = old_r - q.clone() * r.clone();
+        old_r = std::mem::replace(&mut r, next_r);
+        let next_s = old_s.sub_mul(&q, &s);
+        old_s = std::mem::replace(&mut s, next_s);
+        let next_t = old_t.sub_mul(&q, &t);
+        old_t = std::mem::replace(&mut t, next_t);
+    }
+    (old_r, old_s, old_t)
+}
+
+// The inverse of a modulo n in 0..n, if a and n are coprime.
+pub fn mod_inverse<T: Unsigned>(a: T, n: T) -> Option<T> {
+    if n.is_zero() {
+        return None;
+    }
+    let (g, x, _) = extended_gcd(a % n.clone(), n.clone());
+    (g == T::one()).then(|| x.rem_euclid(&n))
+}
+
+// Every unit of Z/nZ paired with its inverse, in increasing order. The units are found
+// by striking out the multiples of n's prime factors, and all of them are inverted with
+// a single mod_inverse: the product of the units is inverted once, and each inverse is
+// peeled off it with the prefix products. None if n does not fit in a u64.
+pub fn inverse_table<T: Unsigned>(n: T) -> Option<Vec<(T, T)>> {
+    let size = usize::try_from(n.to_u64()?).ok()?;
+    if size < 2 {
+        return Some(vec![]);
+    }
+    let mut is_unit = vec![true; size];
+    is_unit[0] = false;
+    let mut rest = size;
+    let mut p = 2;
+    while p * p <= rest {
+        if rest.is_multiple_of(p) {
+            while rest.is_multiple_of(p) {
+                rest /= p;
+            }
+            for k in (p..size).step_by(p) {
+                is_unit[k] = false;
+            }
+        }
+        p += 1;
+    }
+    if rest > 1 {
+        for k in (rest..size).step_by(rest) {
+            is_unit[k] = false;
+        }
+    }
+    let units: Vec<T> = (1..size)
+        .filter(|&k| is_unit[k])
+        .map(|k| T::from_u64(k as u64).unwrap())
+        .collect();
+
+    let mut prefix = Vec::with_capacity(units.len());
+    let mut product = T::one();
+    for unit in &units {
+        product = product.mul_mod(unit, &n);
+        prefix.push(product.clone());
+    }
+    let mut inverse = mod_inverse(product, n.clone()).unwrap();
+    let mut inverses = vec![T::zero(); units.len()];
+    for i in (0..units.len()).rev() {
+        inverses[i] = if i == 0 {
+            inverse.clone()
+        } else {
+            inverse.mul_mod(&prefix[i - 1], &n)
+        };
+        inverse = inverse.mul_mod(&units[i], &n);
     }
+    Some(units.into_iter().zip(inverses).collect())
 }
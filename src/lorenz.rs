@@ -0,0 +1,179 @@
+// src/lorenz.rs
+//
+// The Lorenz SZ40/42 teleprinter cipher attachment ("Tunny"). Each ITA2 character is
+// added (XORed) to the characters from the five chi wheels and the five psi wheels.
+// The chi wheels step every character; the psi wheels step together when the motor
+// allows it: mu61 steps every character, mu37 steps when mu61 shows an active pin, and
+// the psi wheels step when mu37 shows an active pin. On the SZ42A the limitation (the
+// chi2 pin one character back) additionally holds the psi wheels when it is active.
+
+const CHI_SIZES: [usize; 5] = [41, 31, 29, 26, 23];
+const PSI_SIZES: [usize; 5] = [43, 47, 51, 53, 59];
+const MU_SIZES: [usize; 2] = [61, 37];
+
+#[derive(Clone, Debug)]
+struct Wheel {
+    pins: Vec<bool>,
+    position: usize,
+}
+
+impl Wheel {
+    fn active(&self) -> bool {
+        self.pins[self.position]
+    }
+
+    fn step(&mut self) {
+        self.position = (self.position + 1) % self.pins.len();
+    }
+}
+
+// Five wheels read as one ITA2 character, wheel 1 giving the most significant bit.
+fn character(wheels: &[Wheel]) -> u8 {
+    wheels
+        .iter()
+        .fold(0, |acc, wheel| acc << 1 | wheel.active() as u8)
+}
+
+#[derive(Clone, Debug)]
+pub struct Lorenz {
+    chi: Vec<Wheel>,
+    psi: Vec<Wheel>,
+    mu: Vec<Wheel>,
+    limitation: bool,
+}
+
+// Pins are written in Bletchley notation, 'x' (cross) for active and '.' (dot) for inactive.
+fn parse_pins(name: &str, value: &str, size: usize) -> Result<Vec<bool>, String> {
+    let pins = value
+        .chars()
+        .map(|c| match c {
+            'x' | 'X' | '1' => Ok(true),
+            '.' | '0' => Ok(false),
+            _ => Err(format!("Invalid pin '{}' on {}", c, name)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if pins.len() != size {
+        return Err(format!(
+            "{} needs {} pins, found {}",
+            name,
+            size,
+            pins.len()
+        ));
+    }
+    Ok(pins)
+}
+
+impl Lorenz {
+    // Parses "chi1=x.x.. ... chi5=... psi1=... psi5=... mu61=... mu37=... start=1,1,... limitation=chi2".
+    // Start positions are 1-based and listed chi1-5, psi1-5, mu61, mu37; they default to 1.
+    pub fn parse(key: &str) -> Result<Lorenz, String> {
+        let mut pins: Vec<Option<Vec<bool>>> = vec![None; 12];
+        let mut start = vec![0; 12];
+        let mut limitation = false;
+
+        for field in key.split_whitespace() {
+            let (name, value) = field
+                .split_once('=')
+                .ok_or(format!("Expected name=value, found '{}'", field))?;
+            let name = name.to_ascii_lowercase();
+            let slot = match name.as_str() {
+                "start" => {
+                    start = value
+                        .split(',')
+                        .map(|p| match p.parse::<usize>() {
+                            Ok(n) if n >= 1 => Ok(n - 1),
+                            _ => Err(format!("Invalid start position '{}'", p)),
+                        })
+                        .collect::<Result<_, _>>()?;
+                    continue;
+                }
+                "limitation" => {
+                    limitation = match value.to_ascii_lowercase().as_str() {
+                        "chi2" => true,
+                        "none" => false,
+                        _ => return Err(format!("Unknown limitation '{}'", value)),
+                    };
+                    continue;
+                }
+                "mu61" => 10,
+                "mu37" => 11,
+                _ => {
+                    let (kind, index) = name.split_at(3.min(name.len()));
+                    match (kind, index.parse::<usize>()) {
+                        ("chi", Ok(i @ 1..=5)) => i - 1,
+                        ("psi", Ok(i @ 1..=5)) => i + 4,
+                        _ => return Err(format!("Unknown setting '{}'", name)),
+                    }
+                }
+            };
+            let size = match slot {
+                0..=4 => CHI_SIZES[slot],
+                5..=9 => PSI_SIZES[slot - 5],
+                _ => MU_SIZES[slot - 10],
+            };
+            pins[slot] = Some(parse_pins(&name, value, size)?);
+        }
+
+        if start.len() != 12 {
+            return Err("Twelve start positions are needed".to_string());
+        }
+        let mut wheels = pins
+            .into_iter()
+            .zip(start)
+            .enumerate()
+            .map(|(i, (pins, position))| {
+                let pins = pins.ok_or(format!("Missing pins for wheel {}", i + 1))?;
+                if position >= pins.len() {
+                    return Err(format!(
+                        "Start position {} is off wheel {}",
+                        position + 1,
+                        i + 1
+                    ));
+                }
+                Ok(Wheel { pins, position })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let mu = wheels.split_off(10);
+        let psi = wheels.split_off(5);
+        Ok(Lorenz {
+            chi: wheels,
+            psi,
+            mu,
+            limitation,
+        })
+    }
+
+    // The chi wheels step every character, so chi2 one back is simply the pin behind the
+    // one being read, even for the first character of a message.
+    fn chi2_one_back(&self) -> bool {
+        let chi2 = &self.chi[1];
+        chi2.pins[(chi2.position + chi2.pins.len() - 1) % chi2.pins.len()]
+    }
+
+    fn step(&mut self) {
+        let basic_motor = self.mu[1].active();
+        let psi_moves = basic_motor && !(self.limitation && self.chi2_one_back());
+
+        self.chi.iter_mut().for_each(Wheel::step);
+        if psi_moves {
+            self.psi.iter_mut().for_each(Wheel::step);
+        }
+        if self.mu[0].active() {
+            self.mu[1].step();
+        }
+        self.mu[0].step();
+    }
+
+    // Adds the key to each five-bit code; the same operation decrypts.
+    pub fn encrypt(&mut self, codes: &[u8]) -> Vec<u8> {
+        codes
+            .iter()
+            .map(|&code| {
+                let key = character(&self.chi) ^ character(&self.psi);
+                self.step();
+                (code & 0x1f) ^ key
+            })
+            .collect()
+    }
+}
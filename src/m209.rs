@@ -0,0 +1,159 @@
+// src/m209.rs
+//
+// The Hagelin M-209 (C-38) pin-and-lug cipher machine. Six pin wheels of 26, 25, 23,
+// 21, 19 and 17 letters step once per character. The drum has 27 bars, each with two
+// lugs that can face any of the six wheels; every bar with a lug against an active
+// pin shifts the letter by one. The cipher is a reversed Beaufort, so the same key
+// both encrypts and decrypts.
+
+const WHEELS: [&str; 6] = [
+    "ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+    "ABCDEFGHIJKLMNOPQRSTUVXYZ",
+    "ABCDEFGHIJKLMNOPQRSTUVX",
+    "ABCDEFGHIJKLMNOPQRSTU",
+    "ABCDEFGHIJKLMNOPQRS",
+    "ABCDEFGHIJKLMNOPQ",
+];
+
+// The pin that acts on the lugs sits this many places after the letter in the window.
+const PIN_OFFSETS: [usize; 6] = [15, 14, 13, 12, 11, 10];
+
+#[derive(Clone, Debug)]
+pub struct M209 {
+    pins: Vec<Vec<bool>>,
+    // For each bar, the wheels (0-5) its lugs face.
+    bars: Vec<Vec<usize>>,
+    positions: Vec<usize>,
+}
+
+impl M209 {
+    // Parses "pins=ABDH.../ADEG.../... lugs=3-6,0-6,... start=AAAAAA". The pins list the
+    // active letters of each wheel, and each lug pair names the wheels (1-6, 0 for
+    // neither) a bar's two lugs face. Up to 27 bars may be given.
+    pub fn parse(key: &str) -> Result<M209, String> {
+        let mut pins = vec![vec![false; 0]; 6];
+        let mut bars = vec![];
+        let mut positions = vec![0; 6];
+        let index = |wheel: usize, c: char| {
+            WHEELS[wheel].find(c.to_ascii_uppercase()).ok_or(format!(
+                "'{}' is not on wheel {}",
+                c,
+                wheel + 1
+            ))
+        };
+
+        for field in key.split_whitespace() {
+            let (name, value) = field
+                .split_once('=')
+                .ok_or(format!("Expected name=value, found '{}'", field))?;
+            match name.to_ascii_lowercase().as_str() {
+                "pins" => {
+                    let groups: Vec<&str> = value.split('/').collect();
+                    if groups.len() != 6 {
+                        return Err("Pins are needed for all six wheels".to_string());
+                    }
+                    for (wheel, group) in groups.iter().enumerate() {
+                        pins[wheel] = vec![false; WHEELS[wheel].len()];
+                        for c in group.chars() {
+                            pins[wheel][index(wheel, c)?] = true;
+                        }
+                    }
+                }
+                "lugs" => {
+                    bars = value
+                        .split(',')
+                        .map(|bar| {
+                            let lugs = bar
+                                .split('-')
+                                .map(|lug| match lug.parse::<usize>() {
+                                    Ok(n) if n <= 6 => Ok(n),
+                                    _ => Err(format!("Invalid lug '{}'", lug)),
+                                })
+                                .collect::<Result<Vec<_>, _>>()?;
+                            Ok(lugs.into_iter().filter(|&l| l > 0).map(|l| l - 1).collect())
+                        })
+                        .collect::<Result<Vec<_>, String>>()?;
+                    if bars.len() > 27 {
+                        return Err("The drum has only 27 bars".to_string());
+                    }
+                }
+                "start" => {
+                    let letters: Vec<char> = value.chars().collect();
+                    if letters.len() != 6 {
+                        return Err("Six start letters are needed".to_string());
+                    }
+                    for (wheel, &c) in letters.iter().enumerate() {
+                        positions[wheel] = index(wheel, c)?;
+                    }
+                }
+                _ => return Err(format!("Unknown setting '{}'", name)),
+            }
+        }
+
+        if pins.iter().any(|p| p.is_empty()) {
+            return Err("Missing pin settings".to_string());
+        }
+        Ok(M209 {
+            pins,
+            bars,
+            positions,
+        })
+    }
+
+    // The number of bars kicked by the active pins at the current positions.
+    fn shift(&self) -> usize {
+        let active: Vec<bool> = (0..6)
+            .map(|w| {
+                let len = self.pins[w].len();
+                self.pins[w][(self.positions[w] + PIN_OFFSETS[w]) % len]
+            })
+            .collect();
+        self.bars
+            .iter()
+            .filter(|bar| bar.iter().any(|&w| active[w]))
+            .count()
+    }
+
+    fn step(&mut self) {
+        for (wheel, position) in self.positions.iter_mut().enumerate() {
+            *position = (*position + 1) % WHEELS[wheel].len();
+        }
+    }
+
+    fn transform(&mut self, n: u8) -> u8 {
+        let c = ((25 - n as usize) + self.shift()) % 26;
+        self.step();
+        c as u8
+    }
+
+    // Spaces become Z, as on the machine, and the ciphertext is printed in groups of five.
+    pub fn encrypt(&mut self, plaintext: &str) -> String {
+        let letters: Vec<u8> = plaintext
+            .chars()
+            .filter_map(|c| match c {
+                ' ' => Some(25),
+                c => crate::ceasar::letter_to_number(c),
+            })
+            .collect();
+        let cipher: Vec<char> = letters
+            .iter()
+            .map(|&n| (b'A' + self.transform(n)) as char)
+            .collect();
+        cipher
+            .chunks(5)
+            .map(|group| group.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    // Decrypted Zs are printed as spaces.
+    pub fn decrypt(&mut self, ciphertext: &str) -> String {
+        crate::ceasar::string_to_numbers(ciphertext)
+            .into_iter()
+            .map(|n| match self.transform(n) {
+                25 => ' ',
+                p => (b'A' + p) as char,
+            })
+            .collect()
+    }
+}
@@ -1,50 +1,190 @@
 // src/main.rs
 use std::env;
+use std::fs;
+use std::io::{self, Write};
 use std::process;
 
 mod ceasar;
-use ceasar::{string_to_numbers, numbers_to_string};
+mod mult_table;
+use mult_table::{
+    classify_modulus, find_multiplicative_inverses, multiplication_table_mod_n,
+    quadratic_residue_table_mod_n,
+};
+mod cayley;
+use cayley::{parse_cayley_options, CayleyTable, MAX_MODULUS};
+mod math;
+use math::run_math;
 mod vigenere;
 use vigenere::{vigenere_encrypt, vigenere_decrypt};
+// Only the English tables are used from the cracker, which is not wired to a mode.
+#[allow(dead_code, unused_variables)]
+mod vigenere_cracker;
+mod enigma;
+use enigma::{parse_settings, Enigma};
+mod cryptanalysis;
+use cryptanalysis::frequency_analysis;
+mod xor;
+use xor::{
+    crack_repeating_xor, detect_single_byte_xor, parse_detect_options, parse_xor_crack_options,
+    parse_xor_key, repeating_key_xor,
+};
+mod cribdrag;
+use cribdrag::{write_placements, Workbench};
+mod histogram;
+use histogram::{parse_histogram_options, render_histogram};
+mod identify;
+use identify::{identify, Statistics};
+mod ngram;
+mod enigma_cracker;
+use enigma_cracker::{crack_enigma, parse_attack_options};
+mod m209;
+use m209::M209;
+mod ita2;
+use ita2::{from_bletchley, ita2_to_text, text_to_ita2, to_bletchley};
+mod lorenz;
+use lorenz::Lorenz;
+mod solitaire;
+use solitaire::Solitaire;
+mod chaocipher;
+use chaocipher::Chaocipher;
+mod homophonic;
+use crypto_converter::rng::Rng;
+use homophonic::{
+    homophonic_decrypt, homophonic_encrypt, parse_solver_options, solve_homophonic, HomophonicKey,
+};
+mod checkerboard;
+use checkerboard::{digits_to_string, parse_digits, StraddlingCheckerboard};
+mod nihilist;
+use nihilist::{
+    nihilist_decrypt, nihilist_encrypt, nihilist_transposition_decrypt,
+    nihilist_transposition_encrypt, parse_nihilist_key,
+};
+mod vic;
+use vic::{parse_vic_settings, vic_decrypt, vic_encrypt, KeySchedule};
+mod morse;
+use morse::{morse_decode, morse_encode, parse_morse_options};
+mod baconian;
+use baconian::{bacon_decode, bacon_encode, bacon_hide, bacon_reveal, Variant};
+mod encoding;
+use encoding::Encoding;
+mod json;
+mod recipe;
+use recipe::Recipe;
+mod fractionated;
+use fractionated::{
+    fractionated_morse_decrypt, fractionated_morse_encrypt, morbit_decrypt, morbit_encrypt,
+    pollux_decrypt, pollux_encrypt,
+};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() != 4 {
-        eprintln!("Usage: {} <ceasar_e|ceasar_d|vigenere_e|vigenere_d> \"<input>\" \"<key>\"", args[0]);
+    if args.len() == 5 && args[1] == "run" && args[2] == "--recipe" {
+        run_recipe(&args[3], &args[4]);
+        return;
+    }
+    if args.len() != 3 && args.len() != 4 {
+        eprintln!("Usage: {} <ceasar_e|ceasar_d|mult_table|qr_table|cayley|math|encode|decode|decode_lenient|recipe_convert|vigenere_e|vigenere_d|enigma|enigma_trace|enigma_crack|m209_e|m209_d|lorenz_e|lorenz_d|solitaire_e|solitaire_d|solitaire_trace|chao_e|chao_d|chao_trace|homophonic_key|homophonic_e|homophonic_d|homophonic_solve|checkerboard_e|checkerboard_d|nihilist_e|nihilist_d|nihilist_trans_e|nihilist_trans_d|vic_keys|vic_e|vic_d|morse_e|morse_d|bacon_e|bacon_d|bacon_hide|bacon_reveal|fracmorse_e|fracmorse_d|morbit_e|morbit_d|pollux_e|pollux_d|xor|xor_crack|xor_detect|cribdrag|cribdrag_interactive|frequency|histogram|identify> \"<input>\" [\"<key>\"]\n       {} run --recipe <file> \"<input>\"", args[0], args[0]);
         process::exit(1);
     }
 
     let mode = &args[1];
     let input = &args[2];
-    let key = &args[3];
+    let key = args.get(3).map(String::as_str).unwrap_or("");
 
     match mode.as_str() {
-        "ceasar_e" => {
-            let numbers = string_to_numbers(input);
-            let numbers_str = numbers
-                .iter()
-                .map(|n| n.to_string())
-                .collect::<Vec<_>>()
-                .join(" ");
-            println!("{}", numbers_str);
-        }
-        "ceasar_d" => {
-            let numbers_result = input
-                .split_whitespace()
-                .map(|s| s.parse::<u8>())
-                .collect::<Result<Vec<u8>, _>>();
-
-            match numbers_result {
-                Ok(numbers) => {
-                    let string = numbers_to_string(&numbers);
-                    println!("{}", string);
+        "ceasar_e" | "ceasar_d" => match key.parse::<u8>() {
+            Ok(shift) if mode == "ceasar_e" => println!("{}", ceasar::encrypt(input, shift)),
+            Ok(shift) => println!("{}", ceasar::decrypt(input, shift)),
+            Err(e) => {
+                eprintln!("Error: Invalid shift '{}': {}", key, e);
+                process::exit(1);
+            }
+        },
+        "mult_table" => match input.parse::<u32>() {
+            Ok(n) if n > 0 => {
+                multiplication_table_mod_n(n);
+                let inverses = find_multiplicative_inverses(n);
+                println!("Multiplicative Inverses (mod {}):", n);
+                for (a, b) in &inverses {
+                    println!("{} * {} ≡ 1 (mod {})", a, b, n);
+                }
+                println!("Count: {}", inverses.len());
+                if key == "classify" {
+                    println!("{}", classify_modulus(n));
+                }
+            }
+            _ => {
+                eprintln!("Error: The modulus must be a positive number, found '{}'", input);
+                process::exit(1);
+            }
+        },
+        "qr_table" => match input.parse::<u32>() {
+            Ok(n) if n > 0 => quadratic_residue_table_mod_n(n),
+            _ => {
+                eprintln!("Error: The modulus must be a positive number, found '{}'", input);
+                process::exit(1);
+            }
+        },
+        "cayley" => match input.parse::<u32>() {
+            Ok(n) if n > 0 && n <= MAX_MODULUS => match parse_cayley_options(key) {
+                Ok(o) => {
+                    let table = CayleyTable::new(n, o.operation, o.units);
+                    print!("{}", table.render(o.format, o.highlight));
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
                 }
+            },
+            _ => {
+                eprintln!(
+                    "Error: The modulus must be from 1 to {}, found '{}'",
+                    MAX_MODULUS, input
+                );
+                process::exit(1);
+            }
+        },
+        "math" => match run_math(input, key) {
+            Ok(output) => println!("{}", output),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        },
+        "encode" => match Encoding::parse(key) {
+            Ok(encoding) => println!("{}", encoding.encode(input)),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        },
+        "decode" | "decode_lenient" => {
+            let strict = mode == "decode";
+            match Encoding::parse(key).and_then(|encoding| encoding.decode(input, strict)) {
+                Ok(output) => println!("{}", output),
                 Err(e) => {
                     eprintln!("Error: {}", e);
                     process::exit(1);
                 }
             }
         }
+        "recipe_convert" => {
+            let recipe = fs::read_to_string(input)
+                .map_err(|e| format!("Cannot read '{}': {}", input, e))
+                .and_then(|text| Recipe::parse(&text));
+            match (recipe, key) {
+                (Ok(recipe), "json") => println!("{}", recipe.to_json()),
+                (Ok(recipe), "text" | "") => print!("{}", recipe),
+                (Ok(_), _) => {
+                    eprintln!("Error: Unknown recipe format '{}', use text or json", key);
+                    process::exit(1);
+                }
+                (Err(e), _) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
         "vigenere_e" => {
             let encrypted = vigenere_encrypt(input, key);
             println!("{}", encrypted);
@@ -53,8 +193,439 @@ fn main() {
             let decrypted = vigenere_decrypt(input, key);
             println!("{}", decrypted);
         }
+        "enigma" | "enigma_trace" => {
+            let mut machine = match parse_settings(key).and_then(|s| Enigma::new(&s)) {
+                Ok(machine) => machine,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            };
+            if mode == "enigma" {
+                println!("{}", machine.encrypt(input));
+            } else {
+                let (output, steps) = machine.encrypt_with_trace(input);
+                for step in steps {
+                    println!("{}", step);
+                }
+                println!("{}", output);
+            }
+        }
+        "enigma_crack" => {
+            let progress = |done: usize, total: usize| {
+                eprintln!("Searched {}/{} rotor orders", done, total);
+            };
+            match parse_attack_options(key).and_then(|o| crack_enigma(input, &o, &progress)) {
+                Ok(candidates) => {
+                    for candidate in candidates {
+                        println!("{:.3} {}", candidate.score, candidate.settings);
+                        println!("{}", candidate.plaintext);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "m209_e" | "m209_d" => match M209::parse(key) {
+            Ok(mut machine) if mode == "m209_e" => println!("{}", machine.encrypt(input)),
+            Ok(mut machine) => println!("{}", machine.decrypt(input)),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        },
+        "lorenz_e" | "lorenz_d" => {
+            let result = Lorenz::parse(key).and_then(|mut machine| {
+                if mode == "lorenz_e" {
+                    Ok(to_bletchley(&machine.encrypt(&text_to_ita2(input))))
+                } else {
+                    Ok(ita2_to_text(&machine.encrypt(&from_bletchley(input)?)))
+                }
+            });
+            match result {
+                Ok(output) => println!("{}", output),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "solitaire_e" | "solitaire_d" | "solitaire_trace" => {
+            let mut deck = Solitaire::new(key);
+            let mut steps = vec![];
+            let output = if mode == "solitaire_d" {
+                deck.decrypt(input, &mut steps)
+            } else {
+                deck.encrypt(input, &mut steps)
+            };
+            if mode == "solitaire_trace" {
+                for step in steps {
+                    println!("{}", step);
+                }
+            }
+            println!("{}", output);
+        }
+        "chao_e" | "chao_d" | "chao_trace" => match Chaocipher::parse(key) {
+            Ok(mut chaocipher) => {
+                let steps = if mode == "chao_d" {
+                    chaocipher.decrypt(input)
+                } else {
+                    chaocipher.encrypt(input)
+                };
+                if mode == "chao_trace" {
+                    for step in &steps {
+                        println!("{}", step);
+                    }
+                }
+                let output: String = steps
+                    .iter()
+                    .map(|step| if mode == "chao_d" { step.plain } else { step.cipher })
+                    .collect();
+                println!("{}", output);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        },
+        "homophonic_key" => {
+            let generated = input
+                .parse::<u64>()
+                .map_err(|e| format!("Invalid seed '{}': {}", input, e))
+                .and_then(|seed| {
+                    let count = if key.is_empty() {
+                        Ok(100)
+                    } else {
+                        key.parse()
+                            .map_err(|_| format!("Invalid symbol count '{}'", key))
+                    }?;
+                    HomophonicKey::generate(count, &mut Rng::new(seed))
+                });
+            match generated {
+                Ok(key) => println!("{}", key),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "homophonic_e" | "homophonic_d" => {
+            let result = HomophonicKey::parse(key).and_then(|key| {
+                if mode == "homophonic_e" {
+                    Ok(homophonic_encrypt(input, &key))
+                } else {
+                    homophonic_decrypt(input, &key)
+                }
+            });
+            match result {
+                Ok(output) => println!("{}", output),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "homophonic_solve" => {
+            match parse_solver_options(key).and_then(|o| solve_homophonic(input, &o)) {
+                Ok(solution) => {
+                    let mapping: Vec<String> = solution
+                        .assignment
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(symbol, letter)| {
+                            letter.map(|l| format!("{:02}={}", symbol, (b'A' + l) as char))
+                        })
+                        .collect();
+                    println!("{:.3} {}", solution.score, mapping.join(" "));
+                    println!("{}", solution.plaintext);
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "checkerboard_e" | "checkerboard_d" => {
+            let result = StraddlingCheckerboard::parse(key).and_then(|board| {
+                if mode == "checkerboard_e" {
+                    Ok(digits_to_string(&board.encode(input)))
+                } else {
+                    board.decode(&parse_digits(input)?)
+                }
+            });
+            match result {
+                Ok(output) => println!("{}", output),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "nihilist_e" | "nihilist_d" => {
+            let result = parse_nihilist_key(key).and_then(|(square, key)| {
+                if mode == "nihilist_e" {
+                    nihilist_encrypt(input, &square, &key)
+                } else {
+                    nihilist_decrypt(input, &square, &key)
+                }
+            });
+            match result {
+                Ok(output) => println!("{}", output),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "nihilist_trans_e" | "nihilist_trans_d" => {
+            let result = if mode == "nihilist_trans_e" {
+                nihilist_transposition_encrypt(input, key)
+            } else {
+                nihilist_transposition_decrypt(input, key)
+            };
+            match result {
+                Ok(output) => println!("{}", output),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "vic_keys" => {
+            let result = parse_vic_settings(input).and_then(|settings| {
+                let indicator = settings
+                    .indicator
+                    .clone()
+                    .ok_or("An indicator is needed for the key schedule")?;
+                KeySchedule::new(&indicator, &settings)
+            });
+            match result {
+                Ok(schedule) => println!("{}", schedule),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "vic_e" | "vic_d" => {
+            let result = parse_vic_settings(key).and_then(|settings| {
+                if mode == "vic_e" {
+                    vic_encrypt(input, &settings)
+                } else {
+                    vic_decrypt(input, &settings)
+                }
+            });
+            match result {
+                Ok(output) => println!("{}", output),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "morse_e" | "morse_d" => {
+            let result = parse_morse_options(key).and_then(|options| {
+                if mode == "morse_e" {
+                    morse_encode(input, &options)
+                } else {
+                    morse_decode(input, &options)
+                }
+            });
+            match result {
+                Ok(output) => println!("{}", output),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "bacon_e" | "bacon_d" | "bacon_hide" | "bacon_reveal" => {
+            // The carrier takes the key's place when hiding, so Bacon's alphabet is used.
+            let variant = if mode == "bacon_hide" { "" } else { key };
+            let result = Variant::parse(variant).and_then(|variant| match mode.as_str() {
+                "bacon_e" => Ok(bacon_encode(input, variant)),
+                "bacon_d" => bacon_decode(input, variant),
+                "bacon_hide" => bacon_hide(input, key, variant),
+                _ => bacon_reveal(input, variant),
+            });
+            match result {
+                Ok(output) => println!("{}", output),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "fracmorse_e" => println!("{}", fractionated_morse_encrypt(input, key)),
+        "fracmorse_d" | "morbit_e" | "morbit_d" | "pollux_e" | "pollux_d" => {
+            let result = match mode.as_str() {
+                "fracmorse_d" => fractionated_morse_decrypt(input, key),
+                "morbit_e" => morbit_encrypt(input, key),
+                "morbit_d" => morbit_decrypt(input, key),
+                "pollux_e" => pollux_encrypt(input, key),
+                _ => pollux_decrypt(input, key),
+            };
+            match result {
+                Ok(output) => println!("{}", output),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "xor" => {
+            let result = fs::read(input)
+                .map_err(|e| format!("Cannot read '{}': {}", input, e))
+                .and_then(|data| Ok(repeating_key_xor(&data, &parse_xor_key(key)?)));
+            match result {
+                Ok(output) => {
+                    if let Err(e) = io::stdout().write_all(&output) {
+                        eprintln!("Error: {}", e);
+                        process::exit(1);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "xor_crack" => {
+            let result = fs::read(input)
+                .map_err(|e| format!("Cannot read '{}': {}", input, e))
+                .and_then(|data| crack_repeating_xor(&data, &parse_xor_crack_options(key)?));
+            match result {
+                Ok(candidates) => {
+                    for candidate in candidates {
+                        let hex: String =
+                            candidate.key.iter().map(|b| format!("{:02x}", b)).collect();
+                        println!(
+                            "{:.3} hex:{} \"{}\"",
+                            candidate.score,
+                            hex,
+                            candidate.key.escape_ascii()
+                        );
+                        let preview = &candidate.plaintext[..candidate.plaintext.len().min(64)];
+                        println!("{}", preview.escape_ascii());
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "xor_detect" => {
+            let result = fs::read_to_string(input)
+                .map_err(|e| format!("Cannot read '{}': {}", input, e))
+                .and_then(|text| detect_single_byte_xor(&text, &parse_detect_options(key)?));
+            match result {
+                Ok(lines) => {
+                    for line in lines {
+                        println!(
+                            "{}: {:.3} key {:02x} \"{}\"",
+                            line.line,
+                            line.score,
+                            line.key,
+                            line.plaintext.escape_ascii()
+                        );
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "cribdrag" | "cribdrag_interactive" => {
+            let mut workbench = match fs::read_to_string(input)
+                .map_err(|e| format!("Cannot read '{}': {}", input, e))
+                .and_then(|text| Workbench::parse(&text))
+            {
+                Ok(workbench) => workbench,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            };
+            let result = if mode == "cribdrag" {
+                let mut placements = workbench.drag(key.as_bytes());
+                placements.truncate(10);
+                write_placements(&mut io::stdout(), &placements)
+            } else {
+                workbench.interactive(io::stdin().lock(), &mut io::stdout(), 10)
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        }
+        "frequency" => {
+            let report = frequency_analysis(input);
+            match key {
+                "json" => println!("{}", report.to_json()),
+                "csv" => print!("{}", report.to_csv()),
+                "text" | "" => print!("{}", report),
+                _ => {
+                    eprintln!("Error: Unknown report format '{}', use text, json or csv", key);
+                    process::exit(1);
+                }
+            }
+        }
+        "histogram" => {
+            match parse_histogram_options(key).and_then(|o| render_histogram(input, &o)) {
+                Ok(chart) => print!("{}", chart),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "identify" => {
+            if input.trim().is_empty() {
+                eprintln!("Error: There is no ciphertext to identify");
+                process::exit(1);
+            }
+            let stats = Statistics::new(input);
+            print!("{}", stats);
+            println!("Likely cipher families:");
+            for (rank, candidate) in identify(&stats).iter().enumerate() {
+                println!("{:>2}. {} ({:.2})", rank + 1, candidate.family, candidate.score);
+                for reason in &candidate.reasons {
+                    println!("    - {}", reason);
+                }
+            }
+        }
         _ => {
-            eprintln!("Invalid mode. Use 'ceasar_e', 'ceasar_d', 'vigenere_e', or 'vigenere_d'.");
+            eprintln!("Invalid mode. Use 'ceasar_e', 'ceasar_d', 'mult_table', 'qr_table', 'cayley', 'math', 'encode', 'decode', 'decode_lenient', 'recipe_convert', 'vigenere_e', 'vigenere_d', 'enigma', 'enigma_trace', 'enigma_crack', 'm209_e', 'm209_d', 'lorenz_e', 'lorenz_d', 'solitaire_e', 'solitaire_d', 'solitaire_trace', 'chao_e', 'chao_d', 'chao_trace', 'homophonic_key', 'homophonic_e', 'homophonic_d', 'homophonic_solve', 'checkerboard_e', 'checkerboard_d', 'nihilist_e', 'nihilist_d', 'nihilist_trans_e', 'nihilist_trans_d', 'vic_keys', 'vic_e', 'vic_d', 'morse_e', 'morse_d', 'bacon_e', 'bacon_d', 'bacon_hide', 'bacon_reveal', 'fracmorse_e', 'fracmorse_d', 'morbit_e', 'morbit_d', 'pollux_e', 'pollux_d', 'xor', 'xor_crack', 'xor_detect', 'cribdrag', 'cribdrag_interactive', 'frequency', 'histogram' or 'identify'.");
+            process::exit(1);
+        }
+    }
+}
+
+// Prints each step of the recipe with its output; the last output is the result.
+fn run_recipe(path: &str, input: &str) {
+    let result = fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read '{}': {}", path, e))
+        .and_then(|text| Recipe::parse(&text));
+    let recipe = match result {
+        Ok(recipe) => recipe,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    };
+    match recipe.run(input) {
+        Ok(outputs) => {
+            for (number, output) in outputs.iter().enumerate() {
+                println!("{}. {}", number + 1, output.step);
+                println!("{}", output.output);
+            }
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
             process::exit(1);
         }
     }
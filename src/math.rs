@@ -0,0 +1,465 @@
+// src/math.rs
+//
+// The "math" command: number-theory routines from the library, run on numbers given on
+// the command line. Each operation takes its arguments as one whitespace-separated
+// string and returns the lines to print.
+
+use crypto_converter::bigint::BigUint;
+use crypto_converter::contfrac::{
+    convergents, expansion, format_expansion, small_exponent_bounds, wiener_attack,
+    within_boneh_durfee_bound, within_wiener_bound,
+};
+use crypto_converter::dlog::{
+    pohlig_hellman_residues, unit_order, MultiplicativeGroup, MAX_LOG_PRIME_BITS,
+};
+use crypto_converter::factor::{factorize, prime_factors};
+use crypto_converter::number_theory::{
+    carmichael_lambda, crt, crt_combine, euler_phi, format_factorization, jacobi, mod_pow,
+    order_dividing, UnitGroup,
+};
+use crypto_converter::primes::{
+    is_prime, next_prime, primes_between, random_prime, random_safe_prime,
+};
+use crypto_converter::residues::{
+    cipolla, hensel_sqrt, legendre, primitive_root, primitive_roots, tonelli_shanks,
+};
+use crypto_converter::rng::Rng;
+use crypto_converter::{extended_gcd, gcd, mod_inverse, Unsigned};
+
+// The unit group is listed element by element, so its modulus is kept to a size whose
+// listing is still worth reading.
+const MAX_UNIT_GROUP: u64 = 1_000_000;
+const MAX_PRIME_RANGE: u64 = 1_000_000;
+const MAX_PRIME_BITS: u64 = 4096;
+// Only about one odd q in a hundred thousand makes a 1024-bit safe prime 2q + 1, so even
+// with sieving that size takes seconds, and 2048 bits takes minutes.
+const MAX_SAFE_PRIME_BITS: u64 = 1024;
+// Hensel lifting takes a step per power of p, each an inversion modulo the power so far.
+const MAX_HENSEL_BITS: u64 = 4096;
+
+// Numbers are arbitrarily large, in decimal or in hex after "0x".
+fn numbers(args: &str, count: usize, usage: &str) -> Result<Vec<BigUint>, String> {
+    let numbers = args
+        .split_whitespace()
+        .map(BigUint::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+    if numbers.len() != count {
+        return Err(format!("Use: math {}", usage));
+    }
+    Ok(numbers)
+}
+
+// The bit length and seed for generating a prime of `min` to `max` bits.
+fn prime_parameters(n: &[BigUint], min: u64, max: u64) -> Result<(u64, Rng), String> {
+    let bits = n[0]
+        .to_u64()
+        .filter(|bits| (min..=max).contains(bits))
+        .ok_or(format!("The bit length must be from {} to {}", min, max))?;
+    let seed = n[1].to_u64().ok_or("The seed must fit in 64 bits")?;
+    Ok((bits, Rng::new(seed)))
+}
+
+fn modulus(n: &BigUint) -> Result<(), String> {
+    if n.is_zero() {
+        return Err("The modulus must be a positive number".to_string());
+    }
+    Ok(())
+}
+
+fn odd_prime(p: &BigUint) -> Result<(), String> {
+    if p.is_even() || !is_prime(p) {
+        return Err(format!("{} is not an odd prime", p));
+    }
+    Ok(())
+}
+
+// The pair of square roots r and n - r of a modulo n, or an error if there are none.
+fn square_roots(a: &BigUint, n: &BigUint, root: Option<BigUint>) -> Result<String, String> {
+    match root {
+        Some(r) if r.is_zero() => Ok(format!("x^2 ≡ {} (mod {}) for x ≡ 0", a, n)),
+        Some(r) => Ok(format!(
+            "x^2 ≡ {} (mod {}) for x ≡ {} or {}",
+            a,
+            n,
+            r,
+            n.clone() - r.clone()
+        )),
+        None => Err(format!("{} is not a square modulo {}", a, n)),
+    }
+}
+
+// a - b, which may be negative.
+fn difference(a: &BigUint, b: &BigUint) -> String {
+    if a >= b {
+        (a.clone() - b.clone()).to_string()
+    } else {
+        format!("-{}", b.clone() - a.clone())
+    }
+}
+
+// Where d stands against the bounds for the small-exponent attacks on n, as in
+//   Wiener: d < n^(1/4)/3 ≈ 2^8.2: yes
+fn exponent_bounds(d: &BigUint, n: &BigUint) -> Vec<String> {
+    let (wiener, boneh_durfee) = small_exponent_bounds(n);
+    let answer = |within: bool| if within { "yes" } else { "no" };
+    vec![
+        format!(
+            "Wiener: d < n^(1/4)/3 ≈ 2^{:.1}: {}",
+            wiener,
+            answer(within_wiener_bound(d, n))
+        ),
+        format!(
+            "Boneh-Durfee: d < n^0.292 ≈ 2^{:.1}: {}",
+            boneh_durfee,
+            answer(within_boneh_durfee_bound(d, n))
+        ),
+    ]
+}
+
+// Solves "a1 n1 a2 n2 ..." for x = a_i (mod n_i), merging one congruence at a time and
+// showing each step as it would be worked by hand.
+fn crt_derivation(args: &str) -> Result<String, String> {
+    let n = args
+        .split_whitespace()
+        .map(BigUint::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+    if n.is_empty() || !n.len().is_multiple_of(2) {
+        return Err("Use: math crt \"<a1> <n1> <a2> <n2> ...\"".to_string());
+    }
+    let mut lines = vec![];
+    let mut congruences = vec![];
+    for pair in n.chunks(2) {
+        modulus(&pair[1])?;
+        let a = pair[0].clone() % pair[1].clone();
+        lines.push(format!("x ≡ {} (mod {})", a, pair[1]));
+        congruences.push((a, pair[1].clone()));
+    }
+    let (mut a1, mut n1) = congruences[0].clone();
+    for (a2, n2) in congruences.into_iter().skip(1) {
+        lines.push(format!(
+            "Combine x ≡ {} (mod {}) with x ≡ {} (mod {}):",
+            a1, n1, a2, n2
+        ));
+        let g = gcd(n1.clone(), n2.clone());
+        let reduced = (a2.clone() + n2.clone() - a1.clone() % n2.clone()) % n2.clone();
+        if !(reduced.clone() % g.clone()).is_zero() {
+            return Err(format!(
+                "gcd({}, {}) = {} does not divide {} - {} = {}, so there is no solution",
+                n1,
+                n2,
+                g,
+                a2,
+                a1,
+                difference(&a2, &a1)
+            ));
+        }
+        lines.push(format!(
+            "  gcd({}, {}) = {}, which divides {} - {} = {}",
+            n1,
+            n2,
+            g,
+            a2,
+            a1,
+            difference(&a2, &a1)
+        ));
+        let m = n2.clone() / g.clone();
+        let c = n1.clone() / g.clone();
+        let d = reduced.clone() / g.clone() % m.clone();
+        let mut line = format!(
+            "  x = {} + {}t with {}t ≡ {} (mod {})",
+            a1, n1, n1, reduced, n2
+        );
+        if g != BigUint::one() {
+            line += &format!(", so {}t ≡ {} (mod {})", c, d, m);
+        }
+        lines.push(line);
+        let inverse = mod_inverse(c.clone() % m.clone(), m.clone()).unwrap();
+        let t = d.mul_mod(&inverse, &m);
+        lines.push(format!(
+            "  t ≡ {} * {}^-1 ≡ {} * {} ≡ {} (mod {})",
+            d, c, d, inverse, t, m
+        ));
+        let (x, l) = crt_combine(a1.clone(), n1.clone(), a2, n2).unwrap();
+        lines.push(format!(
+            "  x = {} + {} * {} = {}, so x ≡ {} (mod {})",
+            a1, n1, t, x, x, l
+        ));
+        (a1, n1) = (x, l);
+    }
+    lines.push(format!("x ≡ {} (mod {})", a1, n1));
+    Ok(lines.join("\n"))
+}
+
+pub fn run_math(operation: &str, args: &str) -> Result<String, String> {
+    match operation {
+        "gcd" => {
+            let n = numbers(args, 2, "gcd \"<a> <b>\"")?;
+            Ok(format!("{}", gcd(n[0].clone(), n[1].clone())))
+        }
+        "egcd" => {
+            let n = numbers(args, 2, "egcd \"<a> <b>\"")?;
+            let (g, x, y) = extended_gcd(n[0].clone(), n[1].clone());
+            Ok(format!(
+                "gcd({}, {}) = {} = {} * ({}) + {} * ({})",
+                n[0], n[1], g, n[0], x, n[1], y
+            ))
+        }
+        "inverse" => {
+            let n = numbers(args, 2, "inverse \"<a> <n>\"")?;
+            match mod_inverse(n[0].clone(), n[1].clone()) {
+                Some(inverse) => Ok(format!("{}^-1 ≡ {} (mod {})", n[0], inverse, n[1])),
+                None => Err(format!(
+                    "{} has no inverse modulo {} (the gcd is {})",
+                    n[0],
+                    n[1],
+                    gcd(n[0].clone(), n[1].clone())
+                )),
+            }
+        }
+        "pow" => {
+            let n = numbers(args, 3, "pow \"<a> <e> <n>\"")?;
+            modulus(&n[2])?;
+            let power = mod_pow(n[0].clone(), n[1].clone(), n[2].clone());
+            Ok(format!("{}^{} ≡ {} (mod {})", n[0], n[1], power, n[2]))
+        }
+        "phi" => {
+            let n = numbers(args, 1, "phi \"<n>\"")?;
+            modulus(&n[0])?;
+            Ok(format!(
+                "φ({}) = {}",
+                n[0],
+                euler_phi(&prime_factors(&n[0])?)
+            ))
+        }
+        "lambda" => {
+            let n = numbers(args, 1, "lambda \"<n>\"")?;
+            modulus(&n[0])?;
+            let lambda = carmichael_lambda(&prime_factors(&n[0])?);
+            Ok(format!("λ({}) = {}", n[0], lambda))
+        }
+        "order" => {
+            let n = numbers(args, 2, "order \"<a> <n>\"")?;
+            modulus(&n[1])?;
+            if gcd(n[0].clone(), n[1].clone()) != BigUint::one() {
+                return Err(format!("{} is not a unit modulo {}", n[0], n[1]));
+            }
+            let lambda = carmichael_lambda(&prime_factors(&n[1])?);
+            let lambda_factors = prime_factors(&lambda)?;
+            let a = n[0].clone() % n[1].clone();
+            let order = order_dividing(&a, &n[1], &lambda, &lambda_factors);
+            Ok(format!("ord_{}({}) = {}", n[1], n[0], order))
+        }
+        "units" => {
+            let n = numbers(args, 1, "units \"<n>\"")?;
+            let group = n[0]
+                .to_u64()
+                .filter(|n| (2..=MAX_UNIT_GROUP).contains(n))
+                .and_then(UnitGroup::new);
+            match group {
+                Some(group) => Ok(group.to_string()),
+                None => Err(format!(
+                    "The modulus must be from 2 to {} to list its units",
+                    MAX_UNIT_GROUP
+                )),
+            }
+        }
+        "crt" => crt_derivation(args),
+        "dlog" => {
+            let n = numbers(args, 3, "dlog \"<g> <h> <n>\"")?;
+            modulus(&n[2])?;
+            let (g, h, m) = (&n[0], n[1].clone() % n[2].clone(), &n[2]);
+            let (order, factors) = unit_order(g, m)?;
+            if factors.last().is_some_and(|(q, _)| q.bits() > MAX_LOG_PRIME_BITS) {
+                return Err(format!(
+                    "ord({}) = {} = {}, whose largest prime factor is beyond 2^{} and out of reach",
+                    g,
+                    order,
+                    format_factorization(&factors),
+                    MAX_LOG_PRIME_BITS
+                ));
+            }
+            let group = MultiplicativeGroup { modulus: m.clone() };
+            let no_solution = || format!("{} is not a power of {} modulo {}", h, g, m);
+            let residues = pohlig_hellman_residues(&group, &(g.clone() % m.clone()), &h, &factors)
+                .ok_or_else(no_solution)?;
+            let (x, _) = crt(&residues).ok_or_else(no_solution)?;
+            if mod_pow(g.clone(), x.clone(), m.clone()) != h {
+                return Err(no_solution());
+            }
+            let mut lines = vec![format!(
+                "ord({}) = {} = {}",
+                g,
+                order,
+                format_factorization(&factors)
+            )];
+            for (residue, modulus) in &residues {
+                lines.push(format!("x ≡ {} (mod {})", residue, modulus));
+            }
+            lines.push(format!("{}^{} ≡ {} (mod {})", g, x, h, m));
+            Ok(lines.join("\n"))
+        }
+        "legendre" => {
+            let n = numbers(args, 2, "legendre \"<a> <p>\"")?;
+            odd_prime(&n[1])?;
+            let symbol = legendre(n[0].clone(), n[1].clone());
+            Ok(format!("({}/{}) = {}", n[0], n[1], symbol))
+        }
+        "jacobi" => {
+            let n = numbers(args, 2, "jacobi \"<a> <n>\"")?;
+            if n[1].is_even() {
+                return Err("The Jacobi symbol needs an odd modulus".to_string());
+            }
+            let symbol = jacobi(n[0].clone(), n[1].clone());
+            Ok(format!("({}/{}) = {}", n[0], n[1], symbol))
+        }
+        "sqrt" | "cipolla" => {
+            let n = numbers(args, 2, &format!("{} \"<a> <p>\"", operation))?;
+            odd_prime(&n[1])?;
+            let root = if operation == "sqrt" {
+                tonelli_shanks(n[0].clone(), n[1].clone())
+            } else {
+                cipolla(n[0].clone(), n[1].clone())
+            };
+            square_roots(&(n[0].clone() % n[1].clone()), &n[1], root)
+        }
+        "hensel" => {
+            let n = numbers(args, 3, "hensel \"<a> <p> <k>\"")?;
+            odd_prime(&n[1])?;
+            // Hensel's lemma lifts only the roots of units, and a multiple of p may well be a square.
+            if (n[0].clone() % n[1].clone()).is_zero() {
+                return Err(format!("{} must be coprime to {}", n[0], n[1]));
+            }
+            let k = n[2]
+                .to_u64()
+                .filter(|&k| k > 0 && k <= MAX_HENSEL_BITS / n[1].bits())
+                .ok_or(format!(
+                    "The exponent must be a positive number with p^k of at most {} bits",
+                    MAX_HENSEL_BITS
+                ))? as u32;
+            let modulus = (0..k).fold(BigUint::one(), |m, _| m * n[1].clone());
+            let root = hensel_sqrt(n[0].clone(), n[1].clone(), k);
+            square_roots(&(n[0].clone() % modulus.clone()), &modulus, root)
+        }
+        "proot" | "proots" => {
+            let n = numbers(args, 1, &format!("{} \"<n>\"", operation))?;
+            let m = n[0]
+                .to_u64()
+                .filter(|&m| m > 0)
+                .ok_or("The modulus must be a positive 64-bit number")?;
+            let g = primitive_root(m).ok_or(format!("There is no primitive root modulo {}", m))?;
+            if operation == "proot" {
+                return Ok(format!("The smallest primitive root modulo {} is {}", m, g));
+            }
+            let roots = (m <= MAX_UNIT_GROUP)
+                .then(|| primitive_roots(m))
+                .flatten()
+                .ok_or(format!(
+                    "The modulus must be at most {} to list its primitive roots",
+                    MAX_UNIT_GROUP
+                ))?;
+            let list: Vec<String> = roots.iter().map(u64::to_string).collect();
+            Ok(format!("{}\nCount: {}", list.join(" "), roots.len()))
+        }
+        "cf" => {
+            let n = numbers(args, 2, "cf \"<a> <b>\"")?;
+            if n[1].is_zero() {
+                return Err("The denominator must be a positive number".to_string());
+            }
+            let quotients = expansion(n[0].clone(), n[1].clone());
+            let mut lines = vec![format!("{}/{} = {}", n[0], n[1], format_expansion(&quotients))];
+            for (h, k) in convergents(&quotients) {
+                lines.push(format!("{}/{}", h, k));
+            }
+            Ok(lines.join("\n"))
+        }
+        "wiener" => {
+            let n = numbers(args, 2, "wiener \"<e> <n>\"")?;
+            let (e, m) = (&n[0], &n[1]);
+            modulus(m)?;
+            if e.is_zero() || e >= m {
+                return Err("The exponent must be from 1 to n - 1".to_string());
+            }
+            let Some(key) = wiener_attack(e, m) else {
+                let (wiener, boneh_durfee) = small_exponent_bounds(m);
+                return Err(format!(
+                    "No convergent of e/n gives d, so d is likely above Wiener's bound n^(1/4)/3 ≈ 2^{:.1}; Boneh-Durfee's lattice attack reaches d < n^0.292 ≈ 2^{:.1}",
+                    wiener, boneh_durfee
+                ));
+            };
+            let mut lines = vec![
+                format!("Convergent k/d = {}/{}", key.k, key.d),
+                format!("φ(n) = (ed - 1)/k = {}", key.phi),
+                format!("n = {} * {}", key.p, key.q),
+                format!("d = {}", key.d),
+            ];
+            lines.extend(exponent_bounds(&key.d, m));
+            Ok(lines.join("\n"))
+        }
+        "dbound" => {
+            let n = numbers(args, 2, "dbound \"<d> <n>\"")?;
+            if n[0].is_zero() || n[1].is_zero() {
+                return Err("The exponent and modulus must be positive numbers".to_string());
+            }
+            let mut lines = vec![format!(
+                "log2 d = {:.1}, log2 n = {:.1}",
+                n[0].log2(),
+                n[1].log2()
+            )];
+            lines.extend(exponent_bounds(&n[0], &n[1]));
+            Ok(lines.join("\n"))
+        }
+        "factor" => {
+            let n = numbers(args, 1, "factor \"<n>\"")?;
+            if n[0] < BigUint::from(2) {
+                return Err("The number to factor must be at least 2".to_string());
+            }
+            Ok(factorize(&n[0]).to_string())
+        }
+        "isprime" => {
+            let n = numbers(args, 1, "isprime \"<n>\"")?;
+            Ok(if n[0] < BigUint::from(2) {
+                format!("{} is neither prime nor composite", n[0])
+            } else if !is_prime(&n[0]) {
+                format!("{} is composite", n[0])
+            } else if n[0].to_u64().is_some() {
+                format!("{} is prime", n[0])
+            } else {
+                format!("{} is a probable prime (Baillie-PSW)", n[0])
+            })
+        }
+        "nextprime" => {
+            let n = numbers(args, 1, "nextprime \"<n>\"")?;
+            Ok(next_prime(&n[0]).to_string())
+        }
+        "primes" => {
+            let n = numbers(args, 2, "primes \"<low> <high>\"")?;
+            let (low, high) = match (n[0].to_u64(), n[1].to_u64()) {
+                (Some(low), Some(high)) if low <= high => (low, high),
+                _ => return Err("The range must run upwards and fit in 64 bits".to_string()),
+            };
+            if high - low >= MAX_PRIME_RANGE {
+                return Err(format!(
+                    "The range must be less than {} wide",
+                    MAX_PRIME_RANGE
+                ));
+            }
+            let primes = primes_between(low, high);
+            let list: Vec<String> = primes.iter().map(u64::to_string).collect();
+            Ok(format!("{}\nCount: {}", list.join(" "), primes.len()))
+        }
+        "randprime" => {
+            let n = numbers(args, 2, "randprime \"<bits> <seed>\"")?;
+            let (bits, mut rng) = prime_parameters(&n, 2, MAX_PRIME_BITS)?;
+            Ok(random_prime(bits, &mut rng).to_string())
+        }
+        "safeprime" => {
+            let n = numbers(args, 2, "safeprime \"<bits> <seed>\"")?;
+            let (bits, mut rng) = prime_parameters(&n, 3, MAX_SAFE_PRIME_BITS)?;
+            Ok(random_safe_prime(bits, &mut rng).to_string())
+        }
+        _ => Err(format!(
+            "Unknown math operation '{}'. Use gcd, egcd, inverse, pow, phi, lambda, order, units, crt, dlog, legendre, jacobi, sqrt, cipolla, hensel, proot, proots, cf, wiener, dbound, factor, isprime, nextprime, primes, randprime or safeprime",
+            operation
+        )),
+    }
+}
@@ -0,0 +1,205 @@
+// src/morse.rs
+//
+// International Morse code. Letters are separated by one separator and words by
+// another, both configurable, as are the dot and dash characters. Procedural signs
+// such as <AR> and <SK> are letters run together without a break.
+
+// Letters, digits and the punctuation of ITU-R M.1677.
+const CODES: [(char, &str); 54] = [
+    ('A', ".-"),
+    ('B', "-..."),
+    ('C', "-.-."),
+    ('D', "-.."),
+    ('E', "."),
+    ('F', "..-."),
+    ('G', "--."),
+    ('H', "...."),
+    ('I', ".."),
+    ('J', ".---"),
+    ('K', "-.-"),
+    ('L', ".-.."),
+    ('M', "--"),
+    ('N', "-."),
+    ('O', "---"),
+    ('P', ".--."),
+    ('Q', "--.-"),
+    ('R', ".-."),
+    ('S', "..."),
+    ('T', "-"),
+    ('U', "..-"),
+    ('V', "...-"),
+    ('W', ".--"),
+    ('X', "-..-"),
+    ('Y', "-.--"),
+    ('Z', "--.."),
+    ('0', "-----"),
+    ('1', ".----"),
+    ('2', "..---"),
+    ('3', "...--"),
+    ('4', "....-"),
+    ('5', "....."),
+    ('6', "-...."),
+    ('7', "--..."),
+    ('8', "---.."),
+    ('9', "----."),
+    ('.', ".-.-.-"),
+    (',', "--..--"),
+    ('?', "..--.."),
+    ('\'', ".----."),
+    ('!', "-.-.--"),
+    ('/', "-..-."),
+    ('(', "-.--."),
+    (')', "-.--.-"),
+    ('&', ".-..."),
+    (':', "---..."),
+    (';', "-.-.-."),
+    ('=', "-...-"),
+    ('+', ".-.-."),
+    ('-', "-....-"),
+    ('_', "..--.-"),
+    ('"', ".-..-."),
+    ('$', "...-..-"),
+    ('@', ".--.-."),
+];
+
+// Prosigns that are read as such, rather than as punctuation, when decoding with
+// prosigns turned on.
+const PROSIGNS: [(&str, &str); 9] = [
+    ("AR", ".-.-."),
+    ("AS", ".-..."),
+    ("BT", "-...-"),
+    ("CT", "-.-.-"),
+    ("HH", "........"),
+    ("KN", "-.--."),
+    ("SK", "...-.-"),
+    ("SN", "...-."),
+    ("SOS", "...---..."),
+];
+
+pub fn letter_code(c: char) -> Option<&'static str> {
+    let c = c.to_ascii_uppercase();
+    CODES.iter().find(|(l, _)| *l == c).map(|(_, code)| *code)
+}
+
+pub fn code_letter(code: &str) -> Option<char> {
+    CODES.iter().find(|(_, c)| *c == code).map(|(l, _)| *l)
+}
+
+#[derive(Clone, Debug)]
+pub struct MorseOptions {
+    pub dot: char,
+    pub dash: char,
+    pub letter: String,
+    pub word: String,
+    pub prosigns: bool,
+}
+
+impl Default for MorseOptions {
+    fn default() -> Self {
+        MorseOptions {
+            dot: '.',
+            dash: '-',
+            letter: " ".to_string(),
+            word: " / ".to_string(),
+            prosigns: false,
+        }
+    }
+}
+
+// Parses "dot=. dash=- letter=space word=|| prosigns=on". Separators are written
+// literally, with "space" standing for a single space.
+pub fn parse_morse_options(s: &str) -> Result<MorseOptions, String> {
+    let mut options = MorseOptions::default();
+    let separator = |value: &str| value.replace("space", " ");
+    let symbol = |name: &str, value: &str| {
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(format!("The {} must be a single character", name)),
+        }
+    };
+    for field in s.split_whitespace() {
+        let (name, value) = field
+            .split_once('=')
+            .ok_or(format!("Expected name=value, found '{}'", field))?;
+        match name.to_ascii_lowercase().as_str() {
+            "dot" => options.dot = symbol(name, value)?,
+            "dash" => options.dash = symbol(name, value)?,
+            "letter" => options.letter = separator(value),
+            "word" => options.word = separator(value),
+            "prosigns" => options.prosigns = matches!(value, "on" | "yes" | "true"),
+            _ => return Err(format!("Unknown option '{}'", name)),
+        }
+    }
+    if options.dot == options.dash {
+        return Err("The dot and dash must differ".to_string());
+    }
+    if options.letter.is_empty() || options.word.is_empty() || options.letter == options.word {
+        return Err("The letter and word separators must differ and not be empty".to_string());
+    }
+    Ok(options)
+}
+
+// Encodes a text, treating "<AR>"-style groups as prosigns.
+pub fn morse_encode(text: &str, options: &MorseOptions) -> Result<String, String> {
+    let styled = |code: &str| -> String {
+        code.chars()
+            .map(|c| if c == '.' { options.dot } else { options.dash })
+            .collect()
+    };
+    let mut words = vec![];
+    for word in text.split_whitespace() {
+        let mut letters = vec![];
+        let mut rest = word;
+        while let Some(c) = rest.chars().next() {
+            if let Some(prosign) = rest.strip_prefix('<').and_then(|r| r.split_once('>')) {
+                let code = prosign
+                    .0
+                    .chars()
+                    .map(|c| letter_code(c).ok_or(format!("No Morse code for '{}'", c)))
+                    .collect::<Result<String, _>>()?;
+                letters.push(styled(&code));
+                rest = prosign.1;
+            } else {
+                let code = letter_code(c).ok_or(format!("No Morse code for '{}'", c))?;
+                letters.push(styled(code));
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+        words.push(letters.join(&options.letter));
+    }
+    Ok(words.join(&options.word))
+}
+
+pub fn morse_decode(code: &str, options: &MorseOptions) -> Result<String, String> {
+    let mut words = vec![];
+    for word in code.split(options.word.as_str()) {
+        let mut letters = String::new();
+        for letter in word.split(options.letter.as_str()).map(str::trim) {
+            if letter.is_empty() {
+                continue;
+            }
+            let plain: String = letter
+                .chars()
+                .map(|c| match c {
+                    c if c == options.dot => Ok('.'),
+                    c if c == options.dash => Ok('-'),
+                    _ => Err(format!("Unexpected '{}' in Morse code", c)),
+                })
+                .collect::<Result<_, _>>()?;
+            let prosign = PROSIGNS.iter().find(|(_, c)| *c == plain);
+            match (prosign, code_letter(&plain)) {
+                (Some((name, _)), _) if options.prosigns => {
+                    letters.push_str(&format!("<{}>", name))
+                }
+                (_, Some(c)) => letters.push(c),
+                (Some((name, _)), None) => letters.push_str(&format!("<{}>", name)),
+                (None, None) => return Err(format!("Unknown Morse code '{}'", letter)),
+            }
+        }
+        if !letters.is_empty() {
+            words.push(letters);
+        }
+    }
+    Ok(words.join(" "))
+}
@@ -1,24 +1,81 @@
 // src/mult_table.rs
+use crypto_converter::factor::prime_factors;
+use crypto_converter::number_theory::{format_factorization, jacobi};
+use crypto_converter::primes::is_prime_u64;
+use crypto_converter::{gcd, inverse_table, Unsigned};
+
 pub fn multiplication_table_mod_n(n: u32) {
     println!("Multiplication Table (mod {})", n);
     for i in 1..=n {
         for j in 1..=n {
-            print!("{:>4}", (i * j) % n);
+            // i * j itself overflows a u32 once n passes 65535.
+            print!("{:>4}", i.mul_mod(&j, &n));
         }
         println!();
     }
 }
-pub fn find_multiplicative_inverses(n: u32) -> Vec<(u32, u32)> {
-    let mut inverses = vec![];
 
-    for i in 1..n {
-        for j in 1..n {
-            if (i * j) % n == 1 {
-                inverses.push((i, j));
-                break;
-            }
+// The squares mod n in the same layout: each a from 1 to n, its square, and for odd n
+// the Jacobi symbol (a/n), which for a prime n is the Legendre symbol and marks exactly
+// the nonzero squares. Then the units that are squares, and those that are not.
+pub fn quadratic_residue_table_mod_n(n: u32) {
+    println!("Quadratic Residues (mod {})", n);
+    let row = |label: &str, cell: &dyn Fn(u32) -> String| {
+        print!("{:>6}", label);
+        for a in 1..=n {
+            print!("{:>4}", cell(a));
         }
+        println!();
+    };
+    row("a", &|a| (a % n).to_string());
+    row("a^2", &|a| a.mul_mod(&a, &n).to_string());
+    if !n.is_multiple_of(2) {
+        row("(a/n)", &|a| jacobi(a, n).to_string());
     }
+    let mut is_square = vec![false; n as usize];
+    for a in 1..n {
+        is_square[a.mul_mod(&a, &n) as usize] = true;
+    }
+    let (residues, non_residues): (Vec<u32>, Vec<u32>) = (1..n)
+        .filter(|&a| gcd(a, n) == 1)
+        .partition(|&a| is_square[a as usize]);
+    let list = |values: &[u32]| {
+        values
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    println!("Residues: {}", list(&residues));
+    println!("Non-residues: {}", list(&non_residues));
+}
 
-    inverses
+// Every unit with its inverse, from the linear-time table rather than a search. A u32
+// modulus always fits the table.
+pub fn find_multiplicative_inverses(n: u32) -> Vec<(u32, u32)> {
+    inverse_table(n).unwrap_or_default()
+}
+
+// What kind of modulus n is, which decides what the table above can show: for a prime
+// every nonzero row is a permutation, and otherwise the rows of the zero divisors repeat.
+pub fn classify_modulus(n: u32) -> String {
+    if n == 1 {
+        return "Modulus: 1 is neither prime nor composite".to_string();
+    }
+    if is_prime_u64(n as u64) {
+        return format!("Modulus: {} is prime, so Z/{}Z is a field", n, n);
+    }
+    let factors = prime_factors(&n).expect("A 32-bit modulus always factors");
+    let primes: Vec<String> = factors.iter().map(|(p, _)| p.to_string()).collect();
+    format!(
+        "Modulus: {} = {} is {}, so its zero divisors are the multiples of {}",
+        n,
+        format_factorization(&factors),
+        if factors.len() == 1 {
+            "a prime power"
+        } else {
+            "composite"
+        },
+        primes.join(" or ")
+    )
 }
@@ -0,0 +1,178 @@
+// src/ngram.rs
+//
+// Log-probability n-gram models for scoring candidate plaintexts. The tables are
+// built once from a sample of ordinary English prose, with unseen n-grams given a
+// floor probability so that scores stay finite.
+
+use crate::vigenere_cracker::{english_letter_frequencies, ngram_frequency_distribution};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+// Keep the tests' plaintexts out of this sample, or an attack scored against it is
+// partly being handed the answer.
+const ENGLISH_SAMPLE: &str = "\
+Four score and seven years ago our fathers brought forth on this continent a new nation, \
+conceived in liberty, and dedicated to the proposition that all men are created equal. \
+Now we are engaged in a great civil war, testing whether that nation, or any nation so \
+conceived and so dedicated, can long endure. We are met on a great battlefield of that war. \
+We have come to dedicate a portion of that field, as a final resting place for those who \
+here gave their lives that that nation might live. It is altogether fitting and proper that \
+we should do this. But, in a larger sense, we can not dedicate, we can not consecrate, we \
+can not hallow this ground. The brave men, living and dead, who struggled here, have \
+consecrated it, far above our poor power to add or detract. The world will little note, nor \
+long remember what we say here, but it can never forget what they did here. It is for us \
+the living, rather, to be dedicated here to the unfinished work which they who fought here \
+have thus far so nobly advanced. It is rather for us to be here dedicated to the great task \
+remaining before us, that from these honored dead we take increased devotion to that cause \
+for which they gave the last full measure of devotion, that we here highly resolve that these \
+dead shall not have died in vain, that this nation, under God, shall have a new birth of \
+freedom, and that government of the people, by the people, for the people, shall not perish \
+from the earth. \
+When in the course of human events it becomes necessary for one people to dissolve the \
+political bands which have connected them with another, and to assume among the powers of \
+the earth the separate and equal station to which the laws of nature entitle them, a decent \
+respect to the opinions of mankind requires that they should declare the causes which impel \
+them to the separation. We hold these truths to be self evident, that all men are created \
+equal, that they are endowed with certain unalienable rights, that among these are life, \
+liberty and the pursuit of happiness. That to secure these rights, governments are instituted \
+among men, deriving their just powers from the consent of the governed. \
+It was the best of times, it was the worst of times, it was the age of wisdom, it was the age \
+of foolishness, it was the epoch of belief, it was the epoch of incredulity, it was the season \
+of light, it was the season of darkness, it was the spring of hope, it was the winter of \
+despair, we had everything before us, we had nothing before us, we were all going direct to \
+heaven, we were all going direct the other way. \
+It is a truth universally acknowledged, that a single man in possession of a good fortune \
+must be in want of a wife. However little known the feelings or views of such a man may be on \
+his first entering a neighbourhood, this truth is so well fixed in the minds of the \
+surrounding families, that he is considered the rightful property of some one or other of \
+their daughters. \
+The old lighthouse keeper climbed the narrow stairs every evening just before sunset. He \
+carried a small lamp, a book of notes, and a flask of strong black coffee, and he never once \
+complained about the wind that howled through the cracks in the stone walls. From the top of \
+the tower he could see the whole of the bay, the fishing boats returning with their catch, \
+the children running along the harbour wall, and the distant hills where the farmers were \
+bringing their cattle down for the night. Over the years he had learned to read the weather \
+in the colour of the water and the shape of the clouds, and the sailors trusted his warnings \
+more than any forecast printed in the newspaper. \
+Mathematics is the study of patterns, quantities and structures, and it has been developed \
+over thousands of years by people in every part of the world. Early counting systems were \
+used to keep track of trade, taxes and the passing of the seasons. Later, scholars began to \
+ask why certain rules always worked, and they started to write down careful proofs. Today the \
+subject includes algebra, geometry, number theory, probability and many other fields, and \
+its methods are used by engineers, doctors, economists and computer scientists. A good proof \
+explains not only that something is true but also why it must be true, and that is one \
+reason the subject continues to attract curious students. \
+Secret writing has a long and colourful history. Generals sent orders in code so that an \
+enemy who captured a messenger would learn nothing, merchants protected their prices from \
+rivals, and lovers hid their letters from suspicious parents. The simplest methods replace \
+each letter of the message with another letter, while more complicated systems shuffle the \
+order of the letters or change the substitution as the message goes on. For every new \
+method, however, there were clever people working to break it, and the contest between the \
+makers and the breakers of ciphers has shaped the outcome of wars and the fate of nations. \
+During the second world war the German armed forces relied on the Enigma machine, which \
+used a set of rotating wheels to scramble each letter. The operators believed that the \
+number of possible settings was so large that the messages could never be read. A team of \
+mathematicians, first in Poland and then in Britain, found weaknesses in the way the machine \
+was used and built electrical devices to search through the settings much faster than any \
+person could. Their work was kept secret for decades after the war had ended. \
+The weather this morning was cold and grey, with a thin rain falling over the city. People \
+hurried along the streets with their collars turned up and their hands pushed deep into their \
+pockets. In the small cafe on the corner the windows were fogged with steam, and the owner \
+was busy serving hot bread, eggs and tea to a long line of customers who wanted nothing more \
+than a warm place to sit for a few minutes before work. By noon the clouds had started to \
+break, and a little pale sunshine was falling on the wet roofs and the puddles in the square. \
+She opened the letter slowly, as if she already knew what it would say. Her brother had \
+written from the other side of the ocean to tell her that he was well, that the work on the \
+new railway was hard but the pay was good, and that he hoped to send for the rest of the \
+family before the end of the next summer. She read it twice, then folded it carefully and \
+placed it in the drawer with all the others, thinking about the long journey and the strange \
+country that waited for them all. \
+The committee will meet again on Thursday afternoon to review the proposals that were \
+submitted during the last month. Each member should read the reports in advance and be \
+prepared to discuss the costs, the benefits and any questions about the schedule. If you are \
+unable to attend the meeting, please inform the secretary as soon as possible so that the \
+agenda can be changed. We expect to reach a decision about the new building by the end of the \
+quarter, and the results will be announced to the whole staff shortly afterwards. \
+In the forest the trees grew so close together that very little light reached the ground. \
+Moss covered the fallen logs, and small animals moved quietly through the ferns looking for \
+seeds and insects. The travellers followed a narrow path that wound between the trunks, \
+stopping now and then to listen for the sound of the river which, according to their map, \
+should have been somewhere to the east. When they finally heard it, they were so relieved \
+that they laughed out loud and ran the last few hundred yards down the slope to the water. \
+";
+
+pub struct NgramModel {
+    n: usize,
+    log_probs: Vec<f64>,
+}
+
+impl NgramModel {
+    fn from_sample(n: usize, sample: &str) -> NgramModel {
+        let letters: Vec<u8> = sample
+            .bytes()
+            .filter(|b| b.is_ascii_alphabetic())
+            .map(|b| b.to_ascii_uppercase() - b'A')
+            .collect();
+        let size = 26usize.pow(n as u32);
+        let mut counts = vec![0usize; size];
+        for window in letters.windows(n) {
+            counts[index(window)] += 1;
+        }
+        let total = letters.len().saturating_sub(n - 1) as f64;
+        // Unseen n-grams are treated as a tenth of a single occurrence.
+        let floor = (0.1 / total).log10();
+        let log_probs = counts
+            .iter()
+            .map(|&c| {
+                if c == 0 {
+                    floor
+                } else {
+                    (c as f64 / total).log10()
+                }
+            })
+            .collect();
+        NgramModel { n, log_probs }
+    }
+
+    // Returns the cached English model for n-grams of length 1 to 4.
+    pub fn english(n: usize) -> &'static NgramModel {
+        static MODELS: [OnceLock<NgramModel>; 4] = [
+            OnceLock::new(),
+            OnceLock::new(),
+            OnceLock::new(),
+            OnceLock::new(),
+        ];
+        assert!((1..=4).contains(&n), "Unsupported n-gram length");
+        MODELS[n - 1].get_or_init(|| NgramModel::from_sample(n, ENGLISH_SAMPLE))
+    }
+
+    // Sum of the log probabilities of every n-gram in a sequence of 0-25 letter numbers.
+    pub fn score_numbers(&self, numbers: &[u8]) -> f64 {
+        numbers
+            .windows(self.n)
+            .map(|window| self.log_probs[index(window)])
+            .sum()
+    }
+}
+
+// The letters of a text, so that its n-grams run across spaces and punctuation.
+pub fn letters(text: &str) -> String {
+    text.chars().filter(char::is_ascii_alphabetic).collect()
+}
+
+// The English reference distribution: the standard letter frequencies for single
+// letters, and the n-grams of the sample text's letters for longer ones.
+pub fn english_distribution(n: usize) -> HashMap<String, f32> {
+    if n == 1 {
+        ('A'..='Z')
+            .zip(english_letter_frequencies())
+            .map(|(c, &frequency)| (c.to_string(), frequency as f32))
+            .collect()
+    } else {
+        ngram_frequency_distribution(&letters(ENGLISH_SAMPLE), n)
+    }
+}
+
+fn index(window: &[u8]) -> usize {
+    window.iter().fold(0, |acc, &n| acc * 26 + n as usize)
+}
@@ -0,0 +1,160 @@
+// src/nihilist.rs
+//
+// The ciphers of the Russian Nihilists. The substitution cipher turns letters into
+// two-digit Polybius square coordinates and adds the coordinates of a repeating key
+// without carrying, so sums above 100 appear in the ciphertext. The transposition
+// cipher writes the text into a square and reorders both its rows and its columns by
+// the same key.
+
+use crate::ceasar::{letter_to_number, numbers_to_string, string_to_numbers};
+
+// A 5x5 square mixed by a keyword, with I and J sharing a cell.
+#[derive(Clone, Debug)]
+pub struct PolybiusSquare {
+    letters: Vec<u8>,
+}
+
+impl PolybiusSquare {
+    pub fn new(keyword: &str) -> PolybiusSquare {
+        let j = letter_to_number('j').unwrap();
+        let mut letters = vec![];
+        for n in string_to_numbers(keyword).into_iter().chain(0..26) {
+            let n = if n == j { j - 1 } else { n };
+            if !letters.contains(&n) {
+                letters.push(n);
+            }
+        }
+        PolybiusSquare { letters }
+    }
+
+    // The row and column, each 1-5, written as a two-digit number.
+    pub fn coordinates(&self, n: u8) -> u32 {
+        let n = if n == letter_to_number('j').unwrap() {
+            n - 1
+        } else {
+            n
+        };
+        let i = self.letters.iter().position(|&l| l == n).unwrap() as u32;
+        (i / 5 + 1) * 10 + i % 5 + 1
+    }
+
+    pub fn letter_at(&self, coordinates: u32) -> Option<u8> {
+        let (row, column) = (coordinates / 10, coordinates % 10);
+        if !(1..=5).contains(&row) || !(1..=5).contains(&column) {
+            return None;
+        }
+        Some(self.letters[(row as usize - 1) * 5 + column as usize - 1])
+    }
+}
+
+// Parses "square=ZEBRAS key=RUSSIAN" into the mixed square and the additive key.
+pub fn parse_nihilist_key(s: &str) -> Result<(PolybiusSquare, String), String> {
+    let mut square = PolybiusSquare::new("");
+    let mut key = None;
+    for field in s.split_whitespace() {
+        let (name, value) = field
+            .split_once('=')
+            .ok_or(format!("Expected name=value, found '{}'", field))?;
+        match name.to_ascii_lowercase().as_str() {
+            "square" => square = PolybiusSquare::new(value),
+            "key" => key = Some(value.to_string()),
+            _ => return Err(format!("Unknown setting '{}'", name)),
+        }
+    }
+    Ok((square, key.ok_or("Missing key")?))
+}
+
+pub fn nihilist_encrypt(
+    plaintext: &str,
+    square: &PolybiusSquare,
+    key: &str,
+) -> Result<String, String> {
+    let key = string_to_numbers(key);
+    if key.is_empty() {
+        return Err("The key must contain letters".to_string());
+    }
+    Ok(string_to_numbers(plaintext)
+        .iter()
+        .zip(key.iter().cycle())
+        .map(|(&p, &k)| (square.coordinates(p) + square.coordinates(k)).to_string())
+        .collect::<Vec<_>>()
+        .join(" "))
+}
+
+pub fn nihilist_decrypt(
+    ciphertext: &str,
+    square: &PolybiusSquare,
+    key: &str,
+) -> Result<String, String> {
+    let key = string_to_numbers(key);
+    if key.is_empty() {
+        return Err("The key must contain letters".to_string());
+    }
+    let numbers = ciphertext
+        .split_whitespace()
+        .zip(key.iter().cycle())
+        .map(|(number, &k)| {
+            number
+                .parse::<u32>()
+                .ok()
+                .and_then(|c| c.checked_sub(square.coordinates(k)))
+                .and_then(|p| square.letter_at(p))
+                .ok_or(format!("'{}' cannot be deciphered with this key", number))
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+    Ok(numbers_to_string(&numbers))
+}
+
+// The rank of each key letter in alphabetical order, ties broken left to right.
+fn key_order(key: &str) -> Result<Vec<usize>, String> {
+    let key = string_to_numbers(key);
+    if key.len() < 2 {
+        return Err("The key needs at least two letters".to_string());
+    }
+    let mut order: Vec<usize> = (0..key.len()).collect();
+    order.sort_by_key(|&i| key[i]);
+    Ok(order)
+}
+
+// Each block of n*n letters, n being the key length, is written into a square by rows,
+// the rows and columns are taken in key order, and the square is read by rows. The last
+// block is padded with Xs.
+pub fn nihilist_transposition_encrypt(plaintext: &str, key: &str) -> Result<String, String> {
+    let order = key_order(key)?;
+    let n = order.len();
+    let mut numbers = string_to_numbers(plaintext);
+    while numbers.is_empty() || !numbers.len().is_multiple_of(n * n) {
+        numbers.push(letter_to_number('x').unwrap());
+    }
+    let encrypted: Vec<u8> = numbers
+        .chunks(n * n)
+        .flat_map(|block| {
+            order
+                .iter()
+                .flat_map(|&row| order.iter().map(move |&column| block[row * n + column]))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    Ok(numbers_to_string(&encrypted))
+}
+
+pub fn nihilist_transposition_decrypt(ciphertext: &str, key: &str) -> Result<String, String> {
+    let order = key_order(key)?;
+    let n = order.len();
+    let numbers = string_to_numbers(ciphertext);
+    if !numbers.len().is_multiple_of(n * n) {
+        return Err(format!(
+            "The ciphertext must be a multiple of {} letters",
+            n * n
+        ));
+    }
+    let mut decrypted = vec![0; numbers.len()];
+    for (block, output) in numbers.chunks(n * n).zip(decrypted.chunks_mut(n * n)) {
+        for (i, &row) in order.iter().enumerate() {
+            for (j, &column) in order.iter().enumerate() {
+                output[row * n + column] = block[i * n + j];
+            }
+        }
+    }
+    Ok(numbers_to_string(&decrypted))
+}
@@ -0,0 +1,284 @@
+// src/number_theory.rs
+//
+// The structure of the multiplicative group of units of Z/nZ: powers, the group's size
+// (Euler's phi), the exponent that sends every unit to 1 (Carmichael's lambda), and
+// the order of each element. Both functions are computed from the prime factorization
+// of n, and an order is found by dividing the primes of lambda out of lambda for as
+// long as the power stays 1.
+
+use crate::factor::prime_factors;
+use crate::{gcd, inverse_table, mod_inverse, Unsigned};
+use std::fmt;
+
+fn two<T: Unsigned>() -> T {
+    T::from_u64(2).unwrap()
+}
+
+// base^exp mod n by square-and-multiply, reading the exponent from its lowest bit.
+pub fn mod_pow<T: Unsigned>(base: T, exp: T, n: T) -> T {
+    if n == T::one() {
+        return T::zero();
+    }
+    let mut result = T::one();
+    let mut square = base % n.clone();
+    let mut exp = exp;
+    while !exp.is_zero() {
+        if exp.clone() % two() == T::one() {
+            result = result.mul_mod(&square, &n);
+        }
+        square = square.mul_mod(&square, &n);
+        exp = exp / two();
+    }
+    result
+}
+
+// The lcm of a and b, or None if it does not fit in the type.
+pub fn lcm<T: Unsigned>(a: T, b: T) -> Option<T> {
+    if a.is_zero() || b.is_zero() {
+        return Some(T::zero());
+    }
+    let g = gcd(a.clone(), b.clone());
+    (a / g).checked_mul(&b)
+}
+
+// Merges x = a1 (mod n1) and x = a2 (mod n2) into one congruence modulo lcm(n1, n2).
+// Writing x = a1 + n1 t, the second becomes n1 t = a2 - a1 (mod n2), which is solvable
+// exactly when g = gcd(n1, n2) divides a2 - a1, and then t = (a2 - a1)/g * (n1/g)^-1
+// modulo n2/g. The residues must be below their moduli. None if there is no solution or
+// the lcm does not fit in the type.
+pub fn crt_combine<T: Unsigned>(a1: T, n1: T, a2: T, n2: T) -> Option<(T, T)> {
+    let g = gcd(n1.clone(), n2.clone());
+    // a2 - a1 reduced mod n2, which g divides as a2 - a1 does, since g divides n2.
+    let r1 = a1.clone() % n2.clone();
+    let difference = if a2 >= r1 {
+        a2 - r1
+    } else {
+        n2.clone() - (r1 - a2)
+    };
+    if !(difference.clone() % g.clone()).is_zero() {
+        return None;
+    }
+    let m = n2 / g.clone();
+    let inverse = mod_inverse(n1.clone() / g.clone(), m.clone())?;
+    let t = (difference / g % m.clone()).mul_mod(&inverse, &m);
+    let x = n1.checked_mul(&t)?.checked_add(&a1)?;
+    Some((x, n1.checked_mul(&m)?))
+}
+
+// The solution x of x = a_i (mod n_i) for every i, with the lcm of the moduli, which
+// it is unique modulo; None if the congruences contradict each other, a modulus is 0 or
+// the lcm does not fit in the type.
+pub fn crt<T: Unsigned>(congruences: &[(T, T)]) -> Option<(T, T)> {
+    let mut solution = (T::zero(), T::one());
+    for (a, n) in congruences {
+        if n.is_zero() {
+            return None;
+        }
+        solution = crt_combine(solution.0, solution.1, a.clone() % n.clone(), n.clone())?;
+    }
+    Some(solution)
+}
+
+fn pow<T: Unsigned>(p: &T, k: u32) -> T {
+    (0..k).fold(T::one(), |acc, _| acc * p.clone())
+}
+
+// The number of units mod n, from the factorization of n.
+pub fn euler_phi<T: Unsigned>(factors: &[(T, u32)]) -> T {
+    factors.iter().fold(T::one(), |phi, (p, k)| {
+        phi * pow(p, k - 1) * (p.clone() - T::one())
+    })
+}
+
+// The smallest m with a^m = 1 for every unit a mod n, from the factorization of n. It
+// is phi for the odd prime powers, whose unit groups are cyclic, and half of it or less
+// for the powers of 2 from 8 up.
+pub fn carmichael_lambda<T: Unsigned>(factors: &[(T, u32)]) -> T {
+    factors.iter().fold(T::one(), |lambda, (p, k)| {
+        let part = if *p == two() && *k >= 3 {
+            pow(p, k - 2)
+        } else {
+            pow(p, k - 1) * (p.clone() - T::one())
+        };
+        // lambda(n) divides phi(n), so it fits wherever n does.
+        lcm(lambda, part).expect("lambda(n) is at most n")
+    })
+}
+
+// The order of a unit, given a multiple m of it, such as lambda(n), and the
+// factorization of m.
+pub fn order_dividing<T: Unsigned>(a: &T, n: &T, m: &T, m_factors: &[(T, u32)]) -> T {
+    let mut order = m.clone();
+    for (q, k) in m_factors {
+        for _ in 0..*k {
+            let smaller = order.clone() / q.clone();
+            if mod_pow(a.clone(), smaller.clone(), n.clone()) != T::one() {
+                break;
+            }
+            order = smaller;
+        }
+    }
+    order
+}
+
+// The smallest m > 0 with a^m = 1 mod n, or None if a is not a unit or n or lambda(n)
+// is too hard to factor.
+pub fn multiplicative_order<T: Unsigned>(a: T, n: T) -> Option<T> {
+    if n.is_zero() || gcd(a.clone() % n.clone(), n.clone()) != T::one() {
+        return None;
+    }
+    if n == T::one() {
+        return Some(T::one());
+    }
+    let lambda = carmichael_lambda(&prime_factors(&n).ok()?);
+    Some(order_dividing(
+        &(a % n.clone()),
+        &n,
+        &lambda,
+        &prime_factors(&lambda).ok()?,
+    ))
+}
+
+// The Jacobi symbol (a/n) for odd n: 1, -1, or 0 when a and n share a factor. For a
+// prime n it is the Legendre symbol, 1 exactly for the nonzero squares mod n.
+pub fn jacobi<T: Unsigned>(a: T, n: T) -> i32 {
+    let eight = T::from_u64(8).unwrap();
+    let four = T::from_u64(4).unwrap();
+    let three = T::from_u64(3).unwrap();
+    let (mut a, mut n) = (a % n.clone(), n);
+    let mut result = 1;
+    while !a.is_zero() {
+        while (a.clone() % two()).is_zero() {
+            a = a / two();
+            let r = n.clone() % eight.clone();
+            if r == three || r == T::from_u64(5).unwrap() {
+                result = -result;
+            }
+        }
+        std::mem::swap(&mut a, &mut n);
+        if a.clone() % four.clone() == three && n.clone() % four.clone() == three {
+            result = -result;
+        }
+        a = a % n.clone();
+    }
+    if n == T::one() {
+        result
+    } else {
+        0
+    }
+}
+
+// Writes a factorization as "2^3 * 3 * 5^2".
+pub fn format_factorization<T: Unsigned>(factors: &[(T, u32)]) -> String {
+    if factors.is_empty() {
+        return "1".to_string();
+    }
+    factors
+        .iter()
+        .map(|(p, k)| {
+            if *k == 1 {
+                p.to_string()
+            } else {
+                format!("{}^{}", p, k)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" * ")
+}
+
+// The group of units of Z/nZ, element by element. n must be at least 2; the group is None
+// when n does not fit in a u64.
+pub struct UnitGroup<T> {
+    pub modulus: T,
+    pub factors: Vec<(T, u32)>,
+    pub phi: T,
+    pub lambda: T,
+    // Each unit with its inverse and its order, in increasing order.
+    pub units: Vec<(T, T, T)>,
+}
+
+impl<T: Unsigned> UnitGroup<T> {
+    pub fn new(n: T) -> Option<UnitGroup<T>> {
+        let table = inverse_table(n.clone())?;
+        let factors = prime_factors(&n).ok()?;
+        let lambda = carmichael_lambda(&factors);
+        let lambda_factors = prime_factors(&lambda).ok()?;
+        let units = table
+            .into_iter()
+            .map(|(a, inverse)| {
+                let order = order_dividing(&a, &n, &lambda, &lambda_factors);
+                (a, inverse, order)
+            })
+            .collect();
+        Some(UnitGroup {
+            phi: euler_phi(&factors),
+            modulus: n,
+            factors,
+            lambda,
+            units,
+        })
+    }
+
+    // The group is cyclic when some unit's order is the whole group's size, which is
+    // when lambda reaches phi.
+    pub fn is_cyclic(&self) -> bool {
+        self.lambda == self.phi
+    }
+
+    // Each order that occurs with the number of units that have it, smallest first.
+    pub fn order_counts(&self) -> Vec<(T, usize)> {
+        let mut orders: Vec<T> = self
+            .units
+            .iter()
+            .map(|(_, _, order)| order.clone())
+            .collect();
+        orders.sort();
+        let mut counts: Vec<(T, usize)> = vec![];
+        for order in orders {
+            match counts.last_mut() {
+                Some((last, count)) if *last == order => *count += 1,
+                _ => counts.push((order, 1)),
+            }
+        }
+        counts
+    }
+}
+
+impl<T: Unsigned> fmt::Display for UnitGroup<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let n = &self.modulus;
+        writeln!(f, "Units of Z/{}Z", n)?;
+        writeln!(f, "{} = {}", n, format_factorization(&self.factors))?;
+        writeln!(
+            f,
+            "φ({}) = {}, λ({}) = {}, {}",
+            n,
+            self.phi,
+            n,
+            self.lambda,
+            if self.is_cyclic() {
+                "cyclic"
+            } else {
+                "not cyclic"
+            }
+        )?;
+        let width = n.to_string().len().max(5);
+        writeln!(
+            f,
+            "{:>w$} {:>w$} {:>w$}",
+            "unit",
+            "inverse",
+            "order",
+            w = width + 2
+        )?;
+        for (a, inverse, order) in &self.units {
+            writeln!(f, "{:>w$} {:>w$} {:>w$}", a, inverse, order, w = width + 2)?;
+        }
+        let counts: Vec<String> = self
+            .order_counts()
+            .iter()
+            .map(|(order, count)| format!("{}: {}", order, count))
+            .collect();
+        write!(f, "Units of each order: {}", counts.join(", "))
+    }
+}
@@ -0,0 +1,363 @@
+// src/primes.rs
+//
+// Primality tests, sieving and prime generation. Numbers that fit in 64 bits are tested
+// with Miller-Rabin against the first twelve primes as bases, which no composite below
+// 2^64 passes. Larger numbers get the Baillie-PSW test, a base-2 Miller-Rabin test
+// followed by a strong Lucas test: no composite is known to pass both, because the
+// numbers that fool one tend to be exactly those the other catches.
+
+use crate::bigint::BigUint;
+use crate::number_theory::{jacobi, mod_pow};
+use crate::rng::Rng;
+use crate::Unsigned;
+use std::sync::OnceLock;
+
+const BASES_U64: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+// Whether odd n > 2 passes Miller-Rabin to base a: writing n - 1 = d * 2^s with d odd,
+// either a^d = 1 or one of its s successive squares is -1.
+fn strong_probable_prime<T: Unsigned>(n: &T, a: &T) -> bool {
+    let two = T::from_u64(2).unwrap();
+    let minus_one = n.clone() - T::one();
+    let mut d = minus_one.clone();
+    let mut s = 0;
+    while (d.clone() % two.clone()).is_zero() {
+        d = d / two.clone();
+        s += 1;
+    }
+    let mut x = mod_pow(a.clone(), d, n.clone());
+    if x == T::one() || x == minus_one {
+        return true;
+    }
+    for _ in 1..s {
+        x = x.mul_mod(&x, n);
+        if x == minus_one {
+            return true;
+        }
+    }
+    false
+}
+
+// Deterministic for every 64-bit n.
+pub fn is_prime_u64(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in BASES_U64 {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    BASES_U64.iter().all(|a| strong_probable_prime(&n, a))
+}
+
+// Miller-Rabin with random bases. A composite passes each round with probability at
+// most 1/4, so it is wrongly called prime with probability at most 4^-rounds.
+pub fn miller_rabin(n: &BigUint, rounds: usize, rng: &mut Rng) -> bool {
+    let three = BigUint::from(3);
+    if *n <= three {
+        return *n >= BigUint::from(2);
+    }
+    if n.is_even() {
+        return false;
+    }
+    // The bases run from 2 to n - 2, which for n = 3 would leave none to draw.
+    let range = n.clone() - three;
+    (0..rounds).all(|_| {
+        let a = BigUint::random_below(&range, rng) + BigUint::from(2);
+        strong_probable_prime(n, &a)
+    })
+}
+
+// (a - b) mod n for a and b below n.
+fn sub_mod(a: &BigUint, b: &BigUint, n: &BigUint) -> BigUint {
+    if a >= b {
+        a.clone() - b.clone()
+    } else {
+        a.clone() + n.clone() - b.clone()
+    }
+}
+
+// x / 2 mod odd n.
+fn half_mod(x: BigUint, n: &BigUint) -> BigUint {
+    if x.is_even() {
+        x >> 1
+    } else {
+        (x + n.clone()) >> 1
+    }
+}
+
+// The strong Lucas test with Selfridge's parameters: D is the first of 5, -7, 9, -11, ...
+// with (D/n) = -1, P = 1 and Q = (1 - D) / 4. Writing n + 1 = d * 2^s with d odd, a prime
+// n has U_d = 0 or V_(d*2^r) = 0 for some r < s. n must be odd and above 2.
+fn strong_lucas_probable_prime(n: &BigUint) -> bool {
+    let sqrt = n.sqrt();
+    if sqrt.clone() * sqrt == *n {
+        return false;
+    }
+    let mut d_abs = 5u64;
+    let mut negative = false;
+    let d = loop {
+        let d = BigUint::from(d_abs) % n.clone();
+        let d = if negative {
+            sub_mod(&BigUint::zero(), &d, n)
+        } else {
+            d
+        };
+        match jacobi(d.clone(), n.clone()) {
+            -1 => break d,
+            0 if BigUint::from(d_abs) != *n => return false,
+            _ => {}
+        }
+        d_abs += 2;
+        negative = !negative;
+    };
+    // Q = (1 - D) / 4, which is (1 + |D|) / 4 when D is negative.
+    let q = if negative {
+        BigUint::from(d_abs.div_ceil(4)) % n.clone()
+    } else {
+        sub_mod(
+            &BigUint::zero(),
+            &(BigUint::from((d_abs - 1) / 4) % n.clone()),
+            n,
+        )
+    };
+
+    let mut k = n.clone() + BigUint::one();
+    let mut s = 0;
+    while k.is_even() {
+        k = k >> 1;
+        s += 1;
+    }
+    // Walk the bits of k from the top, doubling the index and adding one where a bit is
+    // set, keeping U_k, V_k and Q^k.
+    let (mut u, mut v, mut qk) = (BigUint::one(), BigUint::one(), q.clone());
+    for i in (0..k.bits() - 1).rev() {
+        u = u.mul_mod(&v, n);
+        v = sub_mod(
+            &v.mul_mod(&v, n),
+            &(qk.clone() + qk.clone()).div_rem(n).1,
+            n,
+        );
+        qk = qk.mul_mod(&qk, n);
+        if k.bit(i) {
+            let next_u = half_mod(u.clone() + v.clone(), n);
+            v = half_mod(d.mul_mod(&u, n) + v, n);
+            u = next_u;
+            qk = qk.mul_mod(&q, n);
+        }
+    }
+    let u = u % n.clone();
+    let mut v = v % n.clone();
+    if u.is_zero() || v.is_zero() {
+        return true;
+    }
+    for _ in 1..s {
+        v = sub_mod(
+            &v.mul_mod(&v, n),
+            &(qk.clone() + qk.clone()).div_rem(n).1,
+            n,
+        );
+        if v.is_zero() {
+            return true;
+        }
+        qk = qk.mul_mod(&qk, n);
+    }
+    false
+}
+
+fn small_primes() -> &'static [u64] {
+    static PRIMES: OnceLock<Vec<u64>> = OnceLock::new();
+    PRIMES.get_or_init(|| primes_between(2, 1_000))
+}
+
+// Whether some small prime other than n itself divides n, a cheap filter before the
+// full tests.
+fn has_small_factor(n: &BigUint) -> bool {
+    small_primes()
+        .iter()
+        .any(|&p| n.rem_u64(p) == 0 && *n != BigUint::from(p))
+}
+
+pub fn baillie_psw(n: &BigUint) -> bool {
+    if *n < BigUint::from(2) {
+        return false;
+    }
+    if has_small_factor(n) {
+        return false;
+    }
+    if *n < BigUint::from(1_000_000) {
+        return true;
+    }
+    strong_probable_prime(n, &BigUint::from(2)) && strong_lucas_probable_prime(n)
+}
+
+// Certain below 2^64, and Baillie-PSW above.
+pub fn is_prime(n: &BigUint) -> bool {
+    match n.to_u64() {
+        Some(n) => is_prime_u64(n),
+        None => baillie_psw(n),
+    }
+}
+
+// The smallest prime above n.
+pub fn next_prime(n: &BigUint) -> BigUint {
+    let two = BigUint::from(2);
+    if *n < two {
+        return two;
+    }
+    let mut candidate = n.clone() + BigUint::one();
+    if candidate.is_even() {
+        candidate = candidate + BigUint::one();
+    }
+    while !is_prime(&candidate) {
+        candidate = candidate + two.clone();
+    }
+    candidate
+}
+
+fn isqrt(n: u64) -> u64 {
+    let mut r = (n as f64).sqrt() as u64;
+    while r.checked_mul(r).is_none_or(|square| square > n) {
+        r -= 1;
+    }
+    while (r + 1).checked_mul(r + 1).is_some_and(|square| square <= n) {
+        r += 1;
+    }
+    r
+}
+
+// Every prime p with low <= p <= high. The primes up to the square root of high are
+// sieved first, and then strike out their multiples from one window of the range at a
+// time, so the memory used grows with that square root rather than with the range.
+// Above 2^48 even the square root is too many primes to keep, and each number in the
+// range is tested on its own instead.
+pub fn primes_between(low: u64, high: u64) -> Vec<u64> {
+    const WINDOW: u64 = 1 << 16;
+    if high >= 1 << 48 {
+        return (low..=high).filter(|&n| is_prime_u64(n)).collect();
+    }
+    let root = isqrt(high);
+    let mut sieve = vec![true; root as usize + 1];
+    let mut base = vec![];
+    for p in 2..=root {
+        if sieve[p as usize] {
+            base.push(p);
+            for k in (p * p..=root).step_by(p as usize) {
+                sieve[k as usize] = false;
+            }
+        }
+    }
+
+    let mut primes = vec![];
+    let mut start = low.max(2);
+    while start <= high {
+        let end = start.saturating_add(WINDOW - 1).min(high);
+        let mut window = vec![true; (end - start + 1) as usize];
+        for &p in &base {
+            let first = match start.div_ceil(p).checked_mul(p) {
+                Some(multiple) if multiple <= end => multiple.max(p * p),
+                _ => continue,
+            };
+            for k in (first..=end).step_by(p as usize) {
+                window[(k - start) as usize] = false;
+            }
+        }
+        primes.extend(
+            window
+                .iter()
+                .enumerate()
+                .filter(|(_, &prime)| prime)
+                .map(|(i, _)| start + i as u64),
+        );
+        match end.checked_add(1) {
+            Some(next) => start = next,
+            None => break,
+        }
+    }
+    primes
+}
+
+// A random odd number of exactly `bits` bits.
+fn random_odd(bits: u64, rng: &mut Rng) -> BigUint {
+    let top = BigUint::one() << (bits - 1);
+    let x = BigUint::random_bits(bits - 1, rng) + top;
+    if x.is_even() {
+        x + BigUint::one()
+    } else {
+        x
+    }
+}
+
+// A random prime of exactly `bits` bits, at least 2.
+pub fn random_prime(bits: u64, rng: &mut Rng) -> BigUint {
+    assert!(bits >= 2, "A prime has at least 2 bits");
+    if bits == 2 {
+        return BigUint::from(2 + rng.below(2));
+    }
+    loop {
+        let candidate = random_odd(bits, rng);
+        if !has_small_factor(&candidate) && is_prime(&candidate) {
+            return candidate;
+        }
+    }
+}
+
+// The odd primes that sieve a window of safe-prime candidates, and the window's length.
+const SAFE_SIEVE_BOUND: u64 = 1 << 16;
+const SAFE_SIEVE_WINDOW: usize = 1 << 12;
+
+fn safe_sieve_primes() -> &'static [u64] {
+    static PRIMES: OnceLock<Vec<u64>> = OnceLock::new();
+    PRIMES.get_or_init(|| primes_between(3, SAFE_SIEVE_BOUND))
+}
+
+// Which of q, q + 2, q + 4, ... have neither q + 2k nor p = 2(q + 2k) + 1 divisible by a
+// sieving prime r. Only one division of q by each r is needed: q + 2k = 0 (mod r) for
+// k = -q / 2, and p = 0 for k = -(2q + 1) / 4, and the multiples of r follow from there.
+fn sieve_safe_candidates(q: &BigUint, primes: &[u64]) -> Vec<bool> {
+    let mut candidates = vec![true; SAFE_SIEVE_WINDOW];
+    for &r in primes {
+        // (r + 1) / 2, the inverse of 2 modulo r.
+        let half = r.div_ceil(2);
+        let quarter = half * half % r;
+        let q_mod = q.rem_u64(r);
+        let p_mod = (2 * q_mod + 1) % r;
+        for first in [(r - q_mod) * half % r, (r - p_mod) * quarter % r] {
+            for k in (first as usize..SAFE_SIEVE_WINDOW).step_by(r as usize) {
+                candidates[k] = false;
+            }
+        }
+    }
+    candidates
+}
+
+// A random prime p = 2q + 1 of exactly `bits` bits with q also prime, at least 3. Few
+// random q give a safe prime, so each draw starts a window of consecutive odd q, and
+// the pairs in it are sieved together before any is fully tested.
+pub fn random_safe_prime(bits: u64, rng: &mut Rng) -> BigUint {
+    assert!(bits >= 3, "A safe prime has at least 3 bits");
+    if bits == 3 {
+        return BigUint::from(if rng.below(2) == 0 { 5 } else { 7 });
+    }
+    // q is at least 2^(bits - 2), so no prime below that can be q or p itself.
+    let primes: Vec<u64> = safe_sieve_primes()
+        .iter()
+        .copied()
+        .take_while(|&r| bits - 2 >= 64 || r < 1 << (bits - 2))
+        .collect();
+    loop {
+        let start = random_odd(bits - 1, rng);
+        let candidates = sieve_safe_candidates(&start, &primes);
+        for k in (0..SAFE_SIEVE_WINDOW).filter(|&k| candidates[k]) {
+            let q = start.clone() + BigUint::from(2 * k as u64);
+            if q.bits() != bits - 1 {
+                break;
+            }
+            let p = (q.clone() << 1) + BigUint::one();
+            if is_prime(&q) && is_prime(&p) {
+                return p;
+            }
+        }
+    }
+}
@@ -0,0 +1,256 @@
+// src/recipe.rs
+//
+// Recipes chain the crate's operations, each with its parameters, so that layered
+// puzzles (base64, then Vigenère, then letter numbers) can be peeled in one run. A
+// recipe is written one step per line as "operation name=value ...", or as a JSON array
+// of objects with an "op" field and the same parameters.
+
+use crate::ceasar;
+use crate::encoding::Encoding;
+use crate::json::{self, Value};
+use crate::morse::{morse_decode, morse_encode, MorseOptions};
+use crate::vigenere::{vigenere_decrypt, vigenere_encrypt};
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operation {
+    CaesarEncrypt(u8),
+    CaesarDecrypt(u8),
+    VigenereEncrypt(String),
+    VigenereDecrypt(String),
+    Encode(Encoding),
+    // The decoder is strict unless `lenient=true` is given.
+    Decode(Encoding, bool),
+    MorseEncode,
+    MorseDecode,
+}
+
+impl Operation {
+    fn from_params(name: &str, params: &[(String, String)]) -> Result<Operation, String> {
+        let param = |key: &str| {
+            params
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+                .ok_or(format!("'{}' needs the parameter '{}'", name, key))
+        };
+        let shift = || {
+            param("shift").and_then(|s| {
+                s.parse::<u8>()
+                    .map_err(|e| format!("Invalid shift '{}': {}", s, e))
+            })
+        };
+        let key = || {
+            param("key").and_then(|k| {
+                if ceasar::string_to_numbers(k).is_empty() {
+                    Err("A Vigenère key needs at least one letter".to_string())
+                } else {
+                    Ok(k.to_string())
+                }
+            })
+        };
+        let operation = match name {
+            "caesar_encrypt" => Operation::CaesarEncrypt(shift()?),
+            "caesar_decrypt" => Operation::CaesarDecrypt(shift()?),
+            "vigenere_encrypt" => Operation::VigenereEncrypt(key()?),
+            "vigenere_decrypt" => Operation::VigenereDecrypt(key()?),
+            "encode" => Operation::Encode(Encoding::parse(param("encoding")?)?),
+            "decode" => {
+                let lenient = match params.iter().find(|(k, _)| k == "lenient") {
+                    None => false,
+                    Some((_, v)) => v
+                        .parse::<bool>()
+                        .map_err(|_| format!("Invalid lenient flag '{}'", v))?,
+                };
+                Operation::Decode(Encoding::parse(param("encoding")?)?, !lenient)
+            }
+            "morse_encode" => Operation::MorseEncode,
+            "morse_decode" => Operation::MorseDecode,
+            _ => return Err(format!("Unknown operation '{}'", name)),
+        };
+        let allowed: &[&str] = match operation {
+            Operation::CaesarEncrypt(_) | Operation::CaesarDecrypt(_) => &["shift"],
+            Operation::VigenereEncrypt(_) | Operation::VigenereDecrypt(_) => &["key"],
+            Operation::Encode(_) => &["encoding"],
+            Operation::Decode(..) => &["encoding", "lenient"],
+            Operation::MorseEncode | Operation::MorseDecode => &[],
+        };
+        if let Some((k, _)) = params.iter().find(|(k, _)| !allowed.contains(&k.as_str())) {
+            return Err(format!("'{}' has no parameter '{}'", name, k));
+        }
+        Ok(operation)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Operation::CaesarEncrypt(_) => "caesar_encrypt",
+            Operation::CaesarDecrypt(_) => "caesar_decrypt",
+            Operation::VigenereEncrypt(_) => "vigenere_encrypt",
+            Operation::VigenereDecrypt(_) => "vigenere_decrypt",
+            Operation::Encode(_) => "encode",
+            Operation::Decode(..) => "decode",
+            Operation::MorseEncode => "morse_encode",
+            Operation::MorseDecode => "morse_decode",
+        }
+    }
+
+    fn params(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Operation::CaesarEncrypt(shift) | Operation::CaesarDecrypt(shift) => {
+                vec![("shift", shift.to_string())]
+            }
+            Operation::VigenereEncrypt(key) | Operation::VigenereDecrypt(key) => {
+                vec![("key", key.clone())]
+            }
+            Operation::Encode(encoding) => vec![("encoding", encoding.name().to_string())],
+            Operation::Decode(encoding, strict) => {
+                let mut params = vec![("encoding", encoding.name().to_string())];
+                if !strict {
+                    params.push(("lenient", "true".to_string()));
+                }
+                params
+            }
+            Operation::MorseEncode | Operation::MorseDecode => vec![],
+        }
+    }
+
+    pub fn apply(&self, input: &str) -> Result<String, String> {
+        match self {
+            Operation::CaesarEncrypt(shift) => Ok(ceasar::encrypt(input, *shift)),
+            Operation::CaesarDecrypt(shift) => Ok(ceasar::decrypt(input, *shift)),
+            Operation::VigenereEncrypt(key) => Ok(vigenere_encrypt(input, key)),
+            Operation::VigenereDecrypt(key) => Ok(vigenere_decrypt(input, key)),
+            Operation::Encode(encoding) => Ok(encoding.encode(input)),
+            Operation::Decode(encoding, strict) => encoding.decode(input, *strict),
+            Operation::MorseEncode => morse_encode(input, &MorseOptions::default()),
+            Operation::MorseDecode => morse_decode(input, &MorseOptions::default()),
+        }
+    }
+}
+
+// The text form of a step, "decode encoding=base64 lenient=true".
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())?;
+        for (key, value) in self.params() {
+            write!(f, " {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Recipe {
+    pub steps: Vec<Operation>,
+}
+
+// What one step made of its input.
+pub struct StepOutput<'a> {
+    pub step: &'a Operation,
+    pub output: String,
+}
+
+impl Recipe {
+    // Reads either format: JSON when the text starts with '[', one step per line
+    // otherwise. Blank lines and lines starting with '#' are skipped.
+    pub fn parse(text: &str) -> Result<Recipe, String> {
+        if text.trim_start().starts_with('[') {
+            return Recipe::from_json(text);
+        }
+        let mut steps = vec![];
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let name = fields.next().unwrap();
+            let step = fields
+                .map(|field| {
+                    field
+                        .split_once('=')
+                        .map(|(k, v)| (k.to_string(), v.to_string()))
+                        .ok_or(format!("Expected name=value, found '{}'", field))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .and_then(|params| Operation::from_params(name, &params))
+                .map_err(|e| format!("Line {}: {}", number + 1, e))?;
+            steps.push(step);
+        }
+        Ok(Recipe { steps })
+    }
+
+    pub fn from_json(text: &str) -> Result<Recipe, String> {
+        let Value::Array(items) = json::parse(text)? else {
+            return Err("A JSON recipe is an array of steps".to_string());
+        };
+        let mut steps = vec![];
+        for (number, item) in items.iter().enumerate() {
+            let step = (|| {
+                let Value::Object(fields) = item else {
+                    return Err("A step must be an object".to_string());
+                };
+                let Some(Value::String(name)) = item.get("op") else {
+                    return Err("A step needs an \"op\" string".to_string());
+                };
+                let params = fields
+                    .iter()
+                    .filter(|(k, _)| k != "op")
+                    .map(|(k, v)| match v {
+                        Value::String(s) => Ok((k.clone(), s.clone())),
+                        Value::Number(_) | Value::Bool(_) => Ok((k.clone(), v.to_string())),
+                        _ => Err(format!(
+                            "Parameter '{}' must be a string, number or boolean",
+                            k
+                        )),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Operation::from_params(name, &params)
+            })();
+            steps.push(step.map_err(|e| format!("Step {}: {}", number + 1, e))?);
+        }
+        Ok(Recipe { steps })
+    }
+
+    pub fn to_json(&self) -> String {
+        let steps: Vec<String> = self
+            .steps
+            .iter()
+            .map(|step| {
+                let mut fields = vec![("op".to_string(), Value::String(step.name().to_string()))];
+                for (key, value) in step.params() {
+                    let value = match key {
+                        "shift" => Value::Number(value.parse().unwrap()),
+                        "lenient" => Value::Bool(value == "true"),
+                        _ => Value::String(value),
+                    };
+                    fields.push((key.to_string(), value));
+                }
+                format!("  {}", Value::Object(fields))
+            })
+            .collect();
+        format!("[\n{}\n]", steps.join(",\n"))
+    }
+
+    // Runs every step in turn, keeping each step's output. An error names the step.
+    pub fn run(&self, input: &str) -> Result<Vec<StepOutput<'_>>, String> {
+        let mut outputs: Vec<StepOutput> = vec![];
+        for (number, step) in self.steps.iter().enumerate() {
+            let previous = outputs.last().map_or(input, |o| o.output.as_str());
+            let output = step
+                .apply(previous)
+                .map_err(|e| format!("Step {} ({}): {}", number + 1, step, e))?;
+            outputs.push(StepOutput { step, output });
+        }
+        Ok(outputs)
+    }
+}
+
+impl fmt::Display for Recipe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{}", step)?;
+        }
+        Ok(())
+    }
+}
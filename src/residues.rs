@@ -0,0 +1,208 @@
+// src/residues.rs
+//
+// Primitive roots and square roots modulo n. A primitive root generates every unit, and
+// exists only for n = 1, 2, 4, p^k and 2p^k with p an odd prime. A square root of a mod
+// an odd prime p exists when the Legendre symbol (a/p) is 1; Tonelli-Shanks finds it by
+// correcting a guess inside the 2-power part of the unit group, and Cipolla's method by
+// working in the field with a square root of a non-residue adjoined. Hensel's lemma then
+// lifts a root mod p to one mod p^k, a digit at a time.
+
+use crate::factor::prime_factors;
+use crate::number_theory::{euler_phi, mod_pow};
+use crate::primes::is_prime;
+use crate::{gcd, mod_inverse, Unsigned};
+
+fn small<T: Unsigned>(n: u64) -> T {
+    T::from_u64(n).unwrap()
+}
+
+// (a - b) mod n for a and b below n.
+fn sub_mod<T: Unsigned>(a: T, b: T, n: &T) -> T {
+    if a >= b {
+        a - b
+    } else {
+        n.clone() - (b - a)
+    }
+}
+
+// The Legendre symbol (a/p) for an odd prime p by Euler's criterion: a^((p-1)/2) is 1
+// for a nonzero square, -1 for a non-square and 0 for a multiple of p.
+pub fn legendre<T: Unsigned>(a: T, p: T) -> i32 {
+    let power = mod_pow(a, (p.clone() - T::one()) / small(2), p.clone());
+    if power.is_zero() {
+        0
+    } else if power == T::one() {
+        1
+    } else {
+        -1
+    }
+}
+
+pub fn has_primitive_root<T: Unsigned>(n: T) -> bool {
+    if n <= small(4) {
+        return !n.is_zero();
+    }
+    let odd = if (n.clone() % small(2)).is_zero() {
+        n / small(2)
+    } else {
+        n
+    };
+    !(odd.clone() % small(2)).is_zero()
+        && (is_prime(&odd.to_biguint()) || prime_factors(&odd).is_ok_and(|f| f.len() == 1))
+}
+
+// phi(n), without factoring n when it is prime.
+fn totient<T: Unsigned>(n: &T) -> Option<T> {
+    if is_prime(&n.to_biguint()) {
+        return Some(n.clone() - T::one());
+    }
+    Some(euler_phi(&prime_factors(n).ok()?))
+}
+
+// Whether g generates the units mod n, given the primes dividing phi(n).
+fn generates<T: Unsigned>(g: &T, n: &T, phi: &T, primes: &[(T, u32)]) -> bool {
+    gcd(g.clone(), n.clone()) == T::one()
+        && primes
+            .iter()
+            .all(|(q, _)| mod_pow(g.clone(), phi.clone() / q.clone(), n.clone()) != T::one())
+}
+
+// The smallest primitive root mod n, if there is one. None also when phi(n) is beyond
+// the factorization toolkit, which never happens for n below 2^64.
+pub fn primitive_root<T: Unsigned>(n: T) -> Option<T> {
+    if !has_primitive_root(n.clone()) {
+        return None;
+    }
+    if n <= small(2) {
+        return Some(n - T::one());
+    }
+    let phi = totient(&n)?;
+    let primes = prime_factors(&phi).ok()?;
+    let mut g: T = small(2);
+    while !generates(&g, &n, &phi, &primes) {
+        g = g + T::one();
+    }
+    Some(g)
+}
+
+// Every primitive root mod n in increasing order: the powers g^k of one of them with k
+// coprime to phi(n). None when there are too many to list, phi(n) not fitting in a u64.
+pub fn primitive_roots<T: Unsigned>(n: T) -> Option<Vec<T>> {
+    let Some(g) = primitive_root(n.clone()) else {
+        return Some(vec![]);
+    };
+    let phi = totient(&n)?.to_u64()?;
+    let mut roots: Vec<T> = (1..=phi)
+        .filter(|&k| gcd(k, phi) == 1)
+        .map(|k| mod_pow(g.clone(), small(k), n.clone()))
+        .collect();
+    roots.sort();
+    Some(roots)
+}
+
+// The smaller of the two square roots r and p - r.
+fn smaller_root<T: Unsigned>(r: T, p: &T) -> T {
+    let other = sub_mod(T::zero(), r.clone(), p);
+    r.min(other)
+}
+
+// A square root of a modulo an odd prime p by Tonelli-Shanks, or None if a is not a
+// square. Writing p - 1 = q 2^s with q odd, r = a^((q+1)/2) is a root up to the factor
+// t = a^q, which lies in the subgroup of order 2^s; each step finds the order 2^i of t
+// and multiplies r by a power of a non-residue that halves it.
+pub fn tonelli_shanks<T: Unsigned>(a: T, p: T) -> Option<T> {
+    let a = a % p.clone();
+    if a.is_zero() || p == small(2) {
+        return Some(a);
+    }
+    if legendre(a.clone(), p.clone()) != 1 {
+        return None;
+    }
+    let mut q = p.clone() - T::one();
+    let mut s = 0;
+    while (q.clone() % small(2)).is_zero() {
+        q = q / small(2);
+        s += 1;
+    }
+    let mut z: T = small(2);
+    while legendre(z.clone(), p.clone()) != -1 {
+        z = z + T::one();
+    }
+    let mut m = s;
+    let mut c = mod_pow(z, q.clone(), p.clone());
+    let mut t = mod_pow(a.clone(), q.clone(), p.clone());
+    let mut r = mod_pow(a, (q + T::one()) / small(2), p.clone());
+    while t != T::one() {
+        let mut i = 0;
+        let mut square = t.clone();
+        while square != T::one() {
+            square = square.mul_mod(&square, &p);
+            i += 1;
+        }
+        let mut b = c;
+        for _ in 0..m - i - 1 {
+            b = b.mul_mod(&b, &p);
+        }
+        m = i;
+        c = b.mul_mod(&b, &p);
+        t = t.mul_mod(&c, &p);
+        r = r.mul_mod(&b, &p);
+    }
+    Some(smaller_root(r, &p))
+}
+
+// A square root of a modulo an odd prime p by Cipolla's method, or None if a is not a
+// square. With t chosen so that w = t^2 - a is not a square, (t + sqrt(w))^((p+1)/2)
+// in F_p(sqrt(w)) is a square root of a that lies in F_p itself.
+pub fn cipolla<T: Unsigned>(a: T, p: T) -> Option<T> {
+    let a = a % p.clone();
+    if a.is_zero() || p == small(2) {
+        return Some(a);
+    }
+    if legendre(a.clone(), p.clone()) != 1 {
+        return None;
+    }
+    let mut t = T::one();
+    let w = loop {
+        let w = sub_mod(t.mul_mod(&t, &p), a.clone(), &p);
+        if legendre(w.clone(), p.clone()) == -1 {
+            break w;
+        }
+        t = t + T::one();
+    };
+    // (x1 + y1 sqrt(w)) (x2 + y2 sqrt(w))
+    let multiply = |(x1, y1): &(T, T), (x2, y2): &(T, T)| {
+        let x = (x1.mul_mod(x2, &p) + y1.mul_mod(y2, &p).mul_mod(&w, &p)) % p.clone();
+        let y = (x1.mul_mod(y2, &p) + x2.mul_mod(y1, &p)) % p.clone();
+        (x, y)
+    };
+    let mut result = (T::one(), T::zero());
+    let mut base = (t, T::one());
+    let mut exp = (p.clone() + T::one()) / small(2);
+    while !exp.is_zero() {
+        if exp.clone() % small(2) == T::one() {
+            result = multiply(&result, &base);
+        }
+        base = multiply(&base, &base);
+        exp = exp / small(2);
+    }
+    Some(smaller_root(result.0, &p))
+}
+
+// A square root of a modulo p^k for an odd prime p and a coprime to p. A root r mod p^i
+// lifts to r - (r^2 - a) / (2r) mod p^(i+1), as Newton's method would.
+pub fn hensel_sqrt<T: Unsigned>(a: T, p: T, k: u32) -> Option<T> {
+    if k == 0 || (a.clone() % p.clone()).is_zero() {
+        return None;
+    }
+    let mut r = tonelli_shanks(a.clone(), p.clone())?;
+    let mut modulus = p.clone();
+    for _ in 1..k {
+        modulus = modulus * p.clone();
+        let square = r.mul_mod(&r, &modulus);
+        let error = sub_mod(square, a.clone() % modulus.clone(), &modulus);
+        let inverse = mod_inverse((r.clone() + r.clone()) % modulus.clone(), modulus.clone())?;
+        r = sub_mod(r, error.mul_mod(&inverse, &modulus), &modulus);
+    }
+    Some(smaller_root(r, &modulus))
+}
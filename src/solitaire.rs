@@ -0,0 +1,158 @@
+// src/solitaire.rs
+//
+// Bruce Schneier's Solitaire (Pontifex) keystream cipher. The deck holds the cards
+// 1-52 in bridge order (clubs, diamonds, hearts, spades) and the two jokers, written
+// here as 53 (joker A) and 54 (joker B).
+
+use crate::ceasar::{letter_to_number, numbers_to_string, string_to_numbers};
+use std::fmt;
+
+const JOKER_A: u8 = 53;
+const JOKER_B: u8 = 54;
+
+// A joker counts as 53 wherever a card's value is used.
+fn value(card: u8) -> usize {
+    card.min(JOKER_A) as usize
+}
+
+fn card_name(card: u8) -> String {
+    match card {
+        JOKER_A => "A".to_string(),
+        JOKER_B => "B".to_string(),
+        _ => card.to_string(),
+    }
+}
+
+// The deck after each operation of one round, and the keystream card it produced, if any.
+#[derive(Clone, Debug)]
+pub struct SolitaireStep {
+    pub operations: Vec<(&'static str, Vec<u8>)>,
+    pub output: Option<u8>,
+}
+
+impl fmt::Display for SolitaireStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (operation, deck) in &self.operations {
+            let cards: Vec<String> = deck.iter().map(|&c| card_name(c)).collect();
+            writeln!(f, "{:<10} {}", operation, cards.join(" "))?;
+        }
+        match self.output {
+            Some(card) => write!(f, "Output     {}", card_name(card)),
+            None => write!(f, "Output     joker, no key letter"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Solitaire {
+    deck: Vec<u8>,
+}
+
+impl Solitaire {
+    // Keys the deck by running the algorithm once per passphrase letter, replacing the
+    // output step with a count cut at the letter's value. An empty passphrase leaves the
+    // deck in its initial order.
+    pub fn new(passphrase: &str) -> Solitaire {
+        let mut solitaire = Solitaire {
+            deck: (1..=JOKER_B).collect(),
+        };
+        for n in string_to_numbers(passphrase) {
+            solitaire.round(&mut vec![]);
+            solitaire.count_cut(n as usize + 1);
+        }
+        solitaire
+    }
+
+    // Moves a card down the deck, wrapping past the bottom to just below the top card.
+    fn move_down(&mut self, card: u8, places: usize) {
+        for _ in 0..places {
+            let i = self.deck.iter().position(|&c| c == card).unwrap();
+            if i == self.deck.len() - 1 {
+                self.deck.pop();
+                self.deck.insert(1, card);
+            } else {
+                self.deck.swap(i, i + 1);
+            }
+        }
+    }
+
+    fn triple_cut(&mut self) {
+        let a = self.deck.iter().position(|&c| c == JOKER_A).unwrap();
+        let b = self.deck.iter().position(|&c| c == JOKER_B).unwrap();
+        let (first, second) = (a.min(b), a.max(b));
+        let mut deck = self.deck[second + 1..].to_vec();
+        deck.extend_from_slice(&self.deck[first..=second]);
+        deck.extend_from_slice(&self.deck[..first]);
+        self.deck = deck;
+    }
+
+    // Cuts `count` cards from the top and puts them just above the bottom card.
+    fn count_cut(&mut self, count: usize) {
+        let bottom = self.deck.pop().unwrap();
+        let count = count.min(self.deck.len());
+        self.deck.rotate_left(count);
+        self.deck.push(bottom);
+    }
+
+    // The four deck operations, recording the deck after each one.
+    fn round(&mut self, operations: &mut Vec<(&'static str, Vec<u8>)>) {
+        self.move_down(JOKER_A, 1);
+        operations.push(("Joker A", self.deck.clone()));
+        self.move_down(JOKER_B, 2);
+        operations.push(("Joker B", self.deck.clone()));
+        self.triple_cut();
+        operations.push(("Triple", self.deck.clone()));
+        self.count_cut(value(*self.deck.last().unwrap()));
+        operations.push(("Count", self.deck.clone()));
+    }
+
+    pub fn step(&mut self) -> SolitaireStep {
+        let mut operations = vec![];
+        self.round(&mut operations);
+        let card = self.deck[value(self.deck[0])];
+        let output = if card >= JOKER_A { None } else { Some(card) };
+        SolitaireStep { operations, output }
+    }
+
+    // The next keystream value, 1-26, skipping jokers.
+    fn next_key(&mut self, steps: &mut Vec<SolitaireStep>) -> u8 {
+        loop {
+            let step = self.step();
+            let output = step.output;
+            steps.push(step);
+            if let Some(card) = output {
+                return (card - 1) % 26 + 1;
+            }
+        }
+    }
+
+    // Pads the plaintext with Xs to a multiple of five letters and prints groups of five.
+    // Every round of the deck is appended to `steps`.
+    pub fn encrypt(&mut self, plaintext: &str, steps: &mut Vec<SolitaireStep>) -> String {
+        let mut numbers = string_to_numbers(plaintext);
+        while !numbers.len().is_multiple_of(5) {
+            numbers.push(letter_to_number('x').unwrap());
+        }
+        let encrypted: Vec<u8> = numbers
+            .iter()
+            .map(|&n| (n + self.next_key(steps)) % 26)
+            .collect();
+        group_in_fives(&numbers_to_string(&encrypted).to_ascii_uppercase())
+    }
+
+    pub fn decrypt(&mut self, ciphertext: &str, steps: &mut Vec<SolitaireStep>) -> String {
+        let decrypted: Vec<u8> = string_to_numbers(ciphertext)
+            .iter()
+            .map(|&n| (n + 26 - self.next_key(steps)) % 26)
+            .collect();
+        group_in_fives(&numbers_to_string(&decrypted).to_ascii_uppercase())
+    }
+}
+
+fn group_in_fives(text: &str) -> String {
+    text.as_bytes()
+        .chunks(5)
+        .map(|group| String::from_utf8_lossy(group).into_owned())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
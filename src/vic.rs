@@ -0,0 +1,314 @@
+// src/vic.rs
+//
+// The VIC cipher carried by the Soviet agent Reino Häyhänen. A key schedule mixes a
+// five-digit message indicator, a date, a phrase from a song and the agent's personal
+// number into the column digits of a straddling checkerboard and the keys of two
+// transpositions: a plain columnar one and a disrupted one, where parts of the grid
+// shaped as triangles are filled last.
+
+use crate::ceasar::string_to_numbers;
+use crate::checkerboard::{digits_to_string, parse_digits, StraddlingCheckerboard};
+use std::fmt;
+
+// Extends a digit sequence by adding each pair of neighbouring digits mod 10, a lagged
+// Fibonacci generator with lags of the seed length and one less. Returns only the new
+// digits.
+pub fn chain_addition(seed: &[u8], count: usize) -> Vec<u8> {
+    let mut digits = seed.to_vec();
+    for i in 0..count {
+        digits.push((digits[i] + digits[i + 1]) % 10);
+    }
+    digits.split_off(seed.len())
+}
+
+// The rank of each item in sorted order, 0-based, with ties numbered left to right.
+pub fn sequentialize<T: Ord>(items: &[T]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by(|&a, &b| items[a].cmp(&items[b]));
+    let mut ranks = vec![0; items.len()];
+    for (rank, &i) in order.iter().enumerate() {
+        ranks[i] = rank;
+    }
+    ranks
+}
+
+// Ranks written as the digits 1-9 then 0, the usual way of numbering ten columns.
+fn rank_digits(ranks: &[usize]) -> Vec<u8> {
+    ranks.iter().map(|&r| ((r + 1) % 10) as u8).collect()
+}
+
+// Marks the cells of the disrupted areas. The first area starts in the top row under the
+// column ranked first and runs to the end of the row; each row below starts one column
+// further right until the area runs out, and the next area begins on the following row
+// under the column ranked next.
+fn disrupted_cells(length: usize, key: &[usize]) -> Vec<bool> {
+    let width = key.len();
+    let rows = length.div_ceil(width);
+    let mut disrupted = vec![false; length];
+    let (mut row, mut rank) = (0, 0);
+    while row < rows {
+        let start = key.iter().position(|&r| r == rank % width).unwrap();
+        for i in 0..width - start {
+            for column in start + i..width {
+                if let Some(cell) = disrupted.get_mut((row + i) * width + column) {
+                    *cell = true;
+                }
+            }
+        }
+        row += width - start;
+        rank += 1;
+    }
+    disrupted
+}
+
+// The grid cells in the order they are written and the order they are read.
+fn cell_orders(length: usize, key: &[usize], disrupted: bool) -> (Vec<usize>, Vec<usize>) {
+    let write = if disrupted {
+        let cells = disrupted_cells(length, key);
+        let (mut plain, triangles): (Vec<usize>, Vec<usize>) =
+            (0..length).partition(|&i| !cells[i]);
+        plain.extend(triangles);
+        plain
+    } else {
+        (0..length).collect()
+    };
+    let width = key.len();
+    let read = (0..width)
+        .flat_map(|rank| {
+            let column = key.iter().position(|&r| r == rank).unwrap();
+            (column..length).step_by(width)
+        })
+        .collect();
+    (write, read)
+}
+
+// Writes the digits into rows under the key and reads them out by columns in key order.
+pub fn transpose(digits: &[u8], key: &[usize], disrupted: bool) -> Vec<u8> {
+    let (write, read) = cell_orders(digits.len(), key, disrupted);
+    let mut grid = vec![0; digits.len()];
+    for (&cell, &d) in write.iter().zip(digits) {
+        grid[cell] = d;
+    }
+    read.iter().map(|&cell| grid[cell]).collect()
+}
+
+pub fn untranspose(digits: &[u8], key: &[usize], disrupted: bool) -> Vec<u8> {
+    let (write, read) = cell_orders(digits.len(), key, disrupted);
+    let mut grid = vec![0; digits.len()];
+    for (&cell, &d) in read.iter().zip(digits) {
+        grid[cell] = d;
+    }
+    write.iter().map(|&cell| grid[cell]).collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct VicSettings {
+    // Needed to encrypt; a received message carries its own.
+    pub indicator: Option<Vec<u8>>,
+    pub date: Vec<u8>,
+    pub phrase: Vec<u8>,
+    pub personal: usize,
+    pub top: String,
+    pub rows: Option<String>,
+}
+
+// Parses "indicator=77651 date=391945 personal=6 phrase=IDREAMOFJEANIEWITHTH
+// top=ET_AON_RIS rows=...". The phrase is its first 20 letters, written without spaces;
+// the checkerboard rows are optional as for the straddling checkerboard.
+pub fn parse_vic_settings(s: &str) -> Result<VicSettings, String> {
+    let mut settings = VicSettings {
+        indicator: None,
+        date: vec![],
+        phrase: vec![],
+        personal: 0,
+        top: "ET_AON_RIS".to_string(),
+        rows: None,
+    };
+    for field in s.split_whitespace() {
+        let (name, value) = field
+            .split_once('=')
+            .ok_or(format!("Expected name=value, found '{}'", field))?;
+        match name.to_ascii_lowercase().as_str() {
+            "indicator" => settings.indicator = Some(parse_digits(value)?),
+            "date" => settings.date = parse_digits(value)?,
+            "phrase" => settings.phrase = string_to_numbers(value),
+            "personal" => {
+                settings.personal = value
+                    .parse()
+                    .map_err(|e| format!("Invalid personal number '{}': {}", value, e))?
+            }
+            "top" => settings.top = value.to_string(),
+            "rows" => settings.rows = Some(value.to_string()),
+            _ => return Err(format!("Unknown setting '{}'", name)),
+        }
+    }
+    if settings.indicator.as_ref().is_some_and(|i| i.len() != 5) {
+        return Err("The indicator must be five digits".to_string());
+    }
+    if settings.date.len() < 6 {
+        return Err("The date needs at least six digits".to_string());
+    }
+    if settings.phrase.len() < 20 {
+        return Err("The phrase needs at least 20 letters".to_string());
+    }
+    if settings.personal == 0 {
+        return Err("Missing personal number".to_string());
+    }
+    settings.phrase.truncate(20);
+    Ok(settings)
+}
+
+// The lines of Häyhänen's key derivation and the keys they yield.
+#[derive(Clone, Debug)]
+pub struct KeySchedule {
+    lines: Vec<(&'static str, String)>,
+    pub first_key: Vec<usize>,
+    pub second_key: Vec<usize>,
+    pub columns: [u8; 10],
+}
+
+fn format_line(digits: &[u8]) -> String {
+    digits
+        .chunks(5)
+        .map(digits_to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl KeySchedule {
+    pub fn new(indicator: &[u8], settings: &VicSettings) -> Result<KeySchedule, String> {
+        let mut lines = vec![];
+        // A: the indicator, B: the start of the date, C: their difference without borrows.
+        let b = &settings.date[..5];
+        let c: Vec<u8> = indicator
+            .iter()
+            .zip(b)
+            .map(|(a, b)| (a + 10 - b) % 10)
+            .collect();
+        lines.push(("A", format_line(indicator)));
+        lines.push(("B", format_line(b)));
+        lines.push(("C", format_line(&c)));
+
+        // D: the phrase in two halves, E: each half sequentialized.
+        let (d1, d2) = settings.phrase.split_at(10);
+        let e1 = rank_digits(&sequentialize(d1));
+        let e2 = rank_digits(&sequentialize(d2));
+        let letters =
+            |half: &[u8]| -> String { half.iter().map(|&n| (b'A' + n) as char).collect() };
+        lines.push(("D", format!("{} {}", letters(d1), letters(d2))));
+        lines.push(("E", format!("{} {}", format_line(&e1), format_line(&e2))));
+
+        // F: C chain-added to ten digits, G: E1 plus F, H: G enciphered from the digits
+        // 1234567890 to E2, J: H sequentialized.
+        let mut f = c.clone();
+        f.extend(chain_addition(&c, 5));
+        let g: Vec<u8> = e1.iter().zip(&f).map(|(e, f)| (e + f) % 10).collect();
+        let h: Vec<u8> = g.iter().map(|&d| e2[(d as usize + 9) % 10]).collect();
+        let j = sequentialize(&h);
+        lines.push(("F", format!("{} 12345 67890", format_line(&f))));
+        lines.push(("G", format_line(&g)));
+        lines.push(("H", format_line(&h)));
+        lines.push(("J", format_line(&rank_digits(&j))));
+
+        // K-P: five rows of H chain-added.
+        let block = chain_addition(&h, 50);
+        for (label, row) in ["K", "L", "M", "N", "P"].iter().zip(block.chunks(10)) {
+            lines.push((label, format_line(row)));
+        }
+
+        // The transposition widths are the personal number plus the last two unequal
+        // digits of P.
+        let p = &block[40..];
+        let last = p[9];
+        let before = p[..9]
+            .iter()
+            .rev()
+            .find(|&&d| d != last)
+            .copied()
+            .unwrap_or(last);
+        let (width1, width2) = (
+            settings.personal + before as usize,
+            settings.personal + last as usize,
+        );
+        if width1 < 2 || width2 < 2 || width1 + width2 > 50 {
+            return Err("The personal number gives unusable transposition widths".to_string());
+        }
+
+        // Q and R: the block read by columns in the order of J. S: P sequentialized.
+        let columns: Vec<u8> = (0..10)
+            .flat_map(|rank| {
+                let column = j.iter().position(|&r| r == rank).unwrap();
+                block.iter().skip(column).step_by(10).copied()
+            })
+            .collect();
+        let (q, r) = (&columns[..width1], &columns[width1..width1 + width2]);
+        let s = rank_digits(&sequentialize(p));
+        lines.push(("Q", format_line(q)));
+        lines.push(("R", format_line(r)));
+        lines.push(("S", format_line(&s)));
+
+        Ok(KeySchedule {
+            lines,
+            first_key: sequentialize(q),
+            second_key: sequentialize(r),
+            columns: s.try_into().unwrap(),
+        })
+    }
+}
+
+impl fmt::Display for KeySchedule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines: Vec<String> = self
+            .lines
+            .iter()
+            .map(|(label, line)| format!("{}  {}", label, line))
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+// The indicator goes in as the nth group from the end, n being the sixth digit of the
+// date with 0 counting as 10.
+fn indicator_position(groups: usize, date: &[u8]) -> usize {
+    let n = if date[5] == 0 { 10 } else { date[5] as usize };
+    groups.saturating_sub(n)
+}
+
+fn checkerboard(
+    schedule: &KeySchedule,
+    settings: &VicSettings,
+) -> Result<StraddlingCheckerboard, String> {
+    StraddlingCheckerboard::new(schedule.columns, &settings.top, settings.rows.as_deref())
+}
+
+pub fn vic_encrypt(plaintext: &str, settings: &VicSettings) -> Result<String, String> {
+    let indicator = settings
+        .indicator
+        .as_ref()
+        .ok_or("An indicator is needed to encrypt")?;
+    let schedule = KeySchedule::new(indicator, settings)?;
+    let digits = checkerboard(&schedule, settings)?.encode(plaintext);
+    let digits = transpose(&digits, &schedule.first_key, false);
+    let digits = transpose(&digits, &schedule.second_key, true);
+
+    let mut groups: Vec<String> = digits.chunks(5).map(digits_to_string).collect();
+    let position = indicator_position(groups.len() + 1, &settings.date);
+    groups.insert(position, digits_to_string(indicator));
+    Ok(groups.join(" "))
+}
+
+pub fn vic_decrypt(ciphertext: &str, settings: &VicSettings) -> Result<String, String> {
+    let mut groups: Vec<&str> = ciphertext.split_whitespace().collect();
+    if groups.len() < 2 {
+        return Err("The message must hold the indicator and at least one group".to_string());
+    }
+    let indicator = parse_digits(groups.remove(indicator_position(groups.len(), &settings.date)))?;
+    if indicator.len() != 5 {
+        return Err("The indicator group is not five digits".to_string());
+    }
+    let schedule = KeySchedule::new(&indicator, settings)?;
+    let digits = parse_digits(&groups.concat())?;
+    let digits = untranspose(&digits, &schedule.second_key, true);
+    let digits = untranspose(&digits, &schedule.first_key, false);
+    checkerboard(&schedule, settings)?.decode(&digits)
+}
@@ -2,6 +2,7 @@
 
 use crate::vigenere::vigenere_decrypt;
 use std::collections::HashMap;
+use std::sync::OnceLock;
 
 // You should replace these with the actual n-gram frequencies.
 // Standard English letter frequencies
@@ -33,6 +34,19 @@ X 0.00150
 Y 0.01974
 Z 0.00074";
 
+// ENGLISH_SINGLE_FREQUENCIES as numbers, indexed A to Z.
+pub fn english_letter_frequencies() -> &'static [f64; 26] {
+    static FREQUENCIES: OnceLock<[f64; 26]> = OnceLock::new();
+    FREQUENCIES.get_or_init(|| {
+        let mut frequencies = [0.0; 26];
+        for line in ENGLISH_SINGLE_FREQUENCIES.lines() {
+            let (letter, frequency) = line.split_once(' ').unwrap();
+            frequencies[(letter.as_bytes()[0] - b'A') as usize] = frequency.parse().unwrap();
+        }
+        frequencies
+    })
+}
+
 const ENGLISH_BIGRAM_FREQUENCIES: &str = "\
 TH 0.0387
 HE 0.0367
@@ -77,7 +91,7 @@ fn correlation_score(freq1: &HashMap<String, f32>, freq2: &HashMap<String, f32>)
 }
 
 // This function returns the n-gram frequency distribution of a given text.
-fn ngram_frequency_distribution(text: &str, n: usize) -> HashMap<String, f32> {
+pub fn ngram_frequency_distribution(text: &str, n: usize) -> HashMap<String, f32> {
     let mut ngram_freqs = HashMap::new();
     let mut count = 0;
 
@@ -0,0 +1,276 @@
+// src/xor.rs
+//
+// Repeating-key XOR over raw bytes, and its cryptanalysis. Bytes enciphered with the
+// same key byte sit a key length apart, so the key length shows up as the block size
+// at which blocks of ciphertext differ in the fewest bits: English bytes are closer to
+// each other than random ones, and XOR with a common key keeps that distance. Once the
+// length is known, each column is a single-byte XOR, broken by trying all 256 keys
+// against a model of English text. The same test picks out the one line of many that
+// is single-byte XORed English.
+
+use crate::encoding::Encoding;
+use crate::vigenere_cracker::english_letter_frequencies;
+use std::sync::OnceLock;
+
+pub fn repeating_key_xor(data: &[u8], key: &[u8]) -> Vec<u8> {
+    data.iter()
+        .zip(key.iter().cycle())
+        .map(|(&b, &k)| b ^ k)
+        .collect()
+}
+
+// Reads a key given as text, or as hex bytes after "hex:".
+pub fn parse_xor_key(key: &str) -> Result<Vec<u8>, String> {
+    let bytes = match key.strip_prefix("hex:") {
+        Some(hex) => {
+            if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(format!("Invalid hex key '{}'", hex));
+            }
+            if !hex.len().is_multiple_of(2) {
+                return Err("A hex key needs two digits for each byte".to_string());
+            }
+            (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+                .collect()
+        }
+        None => key.as_bytes().to_vec(),
+    };
+    if bytes.is_empty() {
+        return Err("The key needs at least one byte".to_string());
+    }
+    Ok(bytes)
+}
+
+pub fn hamming_distance(a: &[u8], b: &[u8]) -> u32 {
+    a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum()
+}
+
+// The log10 probability of each byte in English text: mostly lowercase letters in
+// their usual proportions and spaces, some capitals and punctuation, and almost never
+// a control character or a byte above ASCII.
+fn byte_model() -> &'static [f64; 256] {
+    static MODEL: OnceLock<[f64; 256]> = OnceLock::new();
+    MODEL.get_or_init(|| {
+        std::array::from_fn(|b| {
+            let c = b as u8;
+            let english = english_letter_frequencies();
+            let p = match c {
+                b' ' => 0.17,
+                b'a'..=b'z' => 0.75 * english[(c - b'a') as usize],
+                b'A'..=b'Z' => 0.03 * english[(c - b'A') as usize],
+                b'.' | b',' | b'\'' | b'\n' => 0.008,
+                b'0'..=b'9' | b'"' | b'!' | b'?' | b'-' | b';' | b':' | b'(' | b')' => 0.001,
+                b'\r' | b'\t' | 0x20..=0x7e => 0.0001,
+                _ => 0.000001,
+            };
+            // Letters rarer than this are still far likelier than binary junk.
+            p.max(0.00005).log10()
+        })
+    })
+}
+
+// The average log10 probability per byte under the English byte model. Higher is more
+// English-like, and texts of different lengths can be compared.
+pub fn english_score(bytes: &[u8]) -> f64 {
+    let model = byte_model();
+    bytes.iter().map(|&b| model[b as usize]).sum::<f64>() / bytes.len().max(1) as f64
+}
+
+// The best key for a single-byte XOR, with the score of its plaintext.
+pub fn break_single_byte_xor(data: &[u8]) -> (u8, f64) {
+    (0..=255u8)
+        .map(|key| {
+            let plaintext: Vec<u8> = data.iter().map(|b| b ^ key).collect();
+            (key, english_score(&plaintext))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+}
+
+// Key sizes with the average Hamming distance per byte between their blocks, closest
+// first. Up to 16 blocks are compared pairwise to smooth out the noise.
+pub fn key_size_distances(data: &[u8], min: usize, max: usize) -> Vec<(usize, f64)> {
+    let mut sizes: Vec<(usize, f64)> = (min.max(1)..=max)
+        .filter(|&size| data.len() >= 2 * size)
+        .map(|size| {
+            let blocks: Vec<&[u8]> = data.chunks_exact(size).take(16).collect();
+            let mut total = 0.0;
+            let mut pairs = 0;
+            for i in 0..blocks.len() {
+                for j in i + 1..blocks.len() {
+                    total += hamming_distance(blocks[i], blocks[j]) as f64 / size as f64;
+                    pairs += 1;
+                }
+            }
+            (size, total / pairs as f64)
+        })
+        .collect();
+    sizes.sort_by(|a, b| a.1.total_cmp(&b.1));
+    sizes
+}
+
+#[derive(Clone, Debug)]
+pub struct XorCrackOptions {
+    pub min_size: usize,
+    pub max_size: usize,
+    // How many of the most likely key sizes to solve.
+    pub sizes: usize,
+}
+
+impl Default for XorCrackOptions {
+    fn default() -> Self {
+        XorCrackOptions {
+            min_size: 1,
+            max_size: 40,
+            sizes: 5,
+        }
+    }
+}
+
+// Parses "min=2 max=40 sizes=5".
+pub fn parse_xor_crack_options(s: &str) -> Result<XorCrackOptions, String> {
+    let mut options = XorCrackOptions::default();
+    for field in s.split_whitespace() {
+        let (name, value) = field
+            .split_once('=')
+            .ok_or(format!("Expected name=value, found '{}'", field))?;
+        let number = value
+            .parse::<usize>()
+            .ok()
+            .filter(|&n| n > 0)
+            .ok_or(format!("The {} must be a positive number", name))?;
+        match name.to_ascii_lowercase().as_str() {
+            "min" => options.min_size = number,
+            "max" => options.max_size = number,
+            "sizes" => options.sizes = number,
+            _ => return Err(format!("Unknown option '{}'", name)),
+        }
+    }
+    if options.min_size > options.max_size {
+        return Err("The minimum key size is larger than the maximum".to_string());
+    }
+    Ok(options)
+}
+
+pub struct XorKeyCandidate {
+    pub key: Vec<u8>,
+    pub score: f64,
+    pub plaintext: Vec<u8>,
+}
+
+// The shortest key that repeats to make this one, so that a key found at twice its
+// true length is reported once at its own length.
+fn primitive_key(key: &[u8]) -> &[u8] {
+    let len = (1..=key.len())
+        .find(|&p| {
+            key.len().is_multiple_of(p) && key.iter().zip(key.iter().skip(p)).all(|(a, b)| a == b)
+        })
+        .unwrap();
+    &key[..len]
+}
+
+// Solves the most likely key sizes column by column and ranks the keys by the English
+// score of their plaintexts.
+pub fn crack_repeating_xor(
+    data: &[u8],
+    options: &XorCrackOptions,
+) -> Result<Vec<XorKeyCandidate>, String> {
+    let sizes = key_size_distances(data, options.min_size, options.max_size);
+    if sizes.is_empty() {
+        return Err("The data is too short for the key sizes to be measured".to_string());
+    }
+    let mut candidates: Vec<XorKeyCandidate> = vec![];
+    for &(size, _) in sizes.iter().take(options.sizes) {
+        let key: Vec<u8> = (0..size)
+            .map(|column| {
+                let bytes: Vec<u8> = data.iter().skip(column).step_by(size).copied().collect();
+                break_single_byte_xor(&bytes).0
+            })
+            .collect();
+        let key = primitive_key(&key).to_vec();
+        if candidates.iter().any(|c| c.key == key) {
+            continue;
+        }
+        let plaintext = repeating_key_xor(data, &key);
+        candidates.push(XorKeyCandidate {
+            score: english_score(&plaintext),
+            key,
+            plaintext,
+        });
+    }
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(candidates)
+}
+
+#[derive(Clone, Debug)]
+pub struct DetectOptions {
+    pub encoding: Encoding,
+    // How many of the best lines to report.
+    pub top: usize,
+}
+
+impl Default for DetectOptions {
+    fn default() -> Self {
+        DetectOptions {
+            encoding: Encoding::Hex,
+            top: 5,
+        }
+    }
+}
+
+// Parses "encoding=base64 top=3".
+pub fn parse_detect_options(s: &str) -> Result<DetectOptions, String> {
+    let mut options = DetectOptions::default();
+    for field in s.split_whitespace() {
+        let (name, value) = field
+            .split_once('=')
+            .ok_or(format!("Expected name=value, found '{}'", field))?;
+        match name.to_ascii_lowercase().as_str() {
+            "encoding" => options.encoding = Encoding::parse(value)?,
+            "top" => {
+                options.top = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or(format!("The {} must be a positive number", name))?
+            }
+            _ => return Err(format!("Unknown option '{}'", name)),
+        }
+    }
+    Ok(options)
+}
+
+pub struct XorLine {
+    // Counted from 1, including blank lines, as an editor would.
+    pub line: usize,
+    pub key: u8,
+    pub score: f64,
+    pub plaintext: Vec<u8>,
+}
+
+// Breaks every line as a single-byte XOR and ranks the lines by how English their best
+// plaintext looks. Blank lines are skipped; a line that does not decode is an error.
+pub fn detect_single_byte_xor(text: &str, options: &DetectOptions) -> Result<Vec<XorLine>, String> {
+    let mut lines = vec![];
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let data = options
+            .encoding
+            .decode_bytes(line, true)
+            .map_err(|e| format!("Line {}: {}", number + 1, e))?;
+        let (key, score) = break_single_byte_xor(&data);
+        lines.push(XorLine {
+            line: number + 1,
+            key,
+            score,
+            plaintext: data.iter().map(|b| b ^ key).collect(),
+        });
+    }
+    lines.sort_by(|a, b| b.score.total_cmp(&a.score));
+    lines.truncate(options.top);
+    Ok(lines)
+}
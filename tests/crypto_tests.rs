@@ -1,5 +1,6 @@
 // tests/crypto_tests.rs
 
+use std::io::Write;
 use std::process::Command;
 
 fn run_with_args(args: &[&str]) -> String {
@@ -47,3 +48,1180 @@ Multiplicative Inverses (mod 5):
 Count: 4";
     assert_eq!(output.trim(), expected_output);
 }
+
+#[test]
+fn test_enigma_historical_messages() {
+    let output = run_with_args(&["enigma", "AAAAA", "model=I rotors=I,II,III positions=AAA"]);
+    assert_eq!(output.trim(), "BDZGO");
+
+    // Operation Barbarossa, 1941.
+    let output = run_with_args(&[
+        "enigma",
+        "EDPUD NRGYS ZRCXN UYTPO MRMBO FKTBZ REZKM LXLVE FGUEY SIOZV EQMIK UBPMM YLKLT TDEIS MDICA",
+        "model=M3 reflector=B rotors=II,IV,V rings=02,21,12 positions=BLA plugboard=AV,BS,CG,DL,FU,HZ,IN,KM,OW,RX",
+    ]);
+    assert_eq!(
+        output.trim(),
+        "AUFKLXABTEILUNGXVONXKURTINOWAXKURTINOWAXNORDWESTLXSEBEZXSEBEZXUAFFLIEGERSTR"
+    );
+
+    // U-534, M4 with the thin reflector and the Beta wheel.
+    let output = run_with_args(&[
+        "enigma",
+        "NCZWVUSXPNYMINHZXMQXSFWXWLKJAHSHNMCOCCAKUQPMKCSMHKSEINJUSBLKIOSXCKUBHMLLXCSJUSRRDVKOHULXWCCBGVLIYXEOAHXRHKKFVDREWEZLXOBAFGYUJQUKGRTVUKAMEURBVEKSUHHVOYHABCJWMAKLFKLMYFVNRIZRVVRTKOFDANJMOLBGFFLEOPRGTFLVRHOWOPBEKVWMUQFMPWPARMFHAGKXIIBG",
+        "model=M4 reflector=B-thin rotors=Beta,II,IV,I rings=AAAV positions=VJNA plugboard=AT,BL,DF,GJ,HM,NW,OP,QY,RZ,VX",
+    ]);
+    assert!(output
+        .trim()
+        .starts_with("VONVONJLOOKSJHFFTTTEINSEINSDREIZWOYYQNNSNEUNINHALTXX"));
+    assert!(output.trim().ends_with("YNNNNNNOOOVIERYSICHTEINSNULL"));
+}
+
+#[test]
+fn test_enigma_double_stepping_trace() {
+    let output = run_with_args(&["enigma_trace", "AAA", "model=I positions=ADU"]);
+    let positions: Vec<&str> = output.lines().take(3).map(|line| &line[3..6]).collect();
+    assert_eq!(positions, vec!["ADV", "AEW", "BFX"]);
+    assert_eq!(output.lines().last().unwrap(), "EQI");
+
+    let output = run_with_args(&["enigma", "AAA", "model=M3 reflector=A"]);
+    assert_eq!(output, "");
+}
+
+#[test]
+fn test_enigma_ciphertext_only_attack() {
+    let plaintext = "THE CONVOY LEFT THE HARBOUR AT NOON AND IS NOW STEAMING NORTHWEST AT ELEVEN KNOTS WITH SIX MERCHANT SHIPS AND TWO ESCORTS A SUBMARINE WAS SIGHTED NEAR THE ISLAND THIS MORNING BUT DIVED BEFORE OUR AIRCRAFT COULD ATTACK THE WEATHER IS CLEAR WITH A LIGHT SWELL FROM THE SOUTH AND VISIBILITY OF TEN MILES ALL UNITS ARE TO REPORT THEIR FUEL AND AMMUNITION BEFORE MIDNIGHT AND AWAIT FURTHER ORDERS FROM HEADQUARTERS";
+    let ciphertext = run_with_args(&[
+        "enigma",
+        plaintext,
+        "model=I rotors=III,I,II rings=1,5,17 positions=KDQ plugboard=AR,GK",
+    ]);
+
+    let output = run_with_args(&[
+        "enigma_crack",
+        ciphertext.trim(),
+        "model=I rotors=I,II,III candidates=1",
+    ]);
+    let mut lines = output.lines();
+    let settings = lines.next().unwrap();
+    assert!(settings.contains("rotors=III,I,II"));
+    assert_eq!(lines.next().unwrap(), plaintext.replace(' ', ""));
+}
+
+#[test]
+fn test_m209() {
+    let key = "pins=ABDHIKMNSTVW/ADEGJKLORSUX/ABGHJLMNRSTUX/CEFHIMNPSTU/BDEFHIJLNPQ/AEFGHIMNPQ \
+               lugs=3-6,0-6,1-6,1-5,4-5,0-4,0-4,0-4,0-4,2-0,2-0,2-0,2-0,2-0,2-0,2-0,2-0,2-0,2-0,2-5,2-5,0-5,0-5,0-5,0-5,0-5,0-5 \
+               start=AAAAAA";
+    let encrypted = run_with_args(&["m209_e", "ATTACK AT DAWN", key]);
+    assert_eq!(encrypted.trim(), "YLTUT CVIXL XAQD");
+
+    let decrypted = run_with_args(&["m209_d", encrypted.trim(), key]);
+    assert_eq!(decrypted.trim(), "ATTACK AT DAWN");
+
+    // With no active pins the drum never shifts and the machine is a plain reversed alphabet.
+    let encrypted = run_with_args(&["m209_e", "ABCXYZ", "pins=///// lugs=1-2,3-4"]);
+    assert_eq!(encrypted.trim(), "ZYXCB A");
+}
+
+fn lorenz_key(pin: impl Fn(&str, usize) -> char) -> String {
+    let wheels = [
+        ("chi1", 41),
+        ("chi2", 31),
+        ("chi3", 29),
+        ("chi4", 26),
+        ("chi5", 23),
+        ("psi1", 43),
+        ("psi2", 47),
+        ("psi3", 51),
+        ("psi4", 53),
+        ("psi5", 59),
+        ("mu61", 61),
+        ("mu37", 37),
+    ];
+    wheels
+        .iter()
+        .map(|&(name, size)| {
+            let pins: String = (0..size).map(|i| pin(name, i)).collect();
+            format!("{}={}", name, pins)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Wheel patterns in the style of the German pin rules (about half crosses, no more than
+// four of a kind in a row), with the message enciphered independently of this crate.
+const LORENZ_KEY: &str = concat!(
+    "chi1=x..xx.x.xx...xx.xxx...x..xxx.xxx.x..x..x. ",
+    "chi2=x..x.x.x.xx.x...x...xx.x.xx.xx. ",
+    "chi3=x.xx...x...xx.x.xxxx.xxx....x ",
+    "chi4=xx...x..xxxx....x.xx.x.x.x ",
+    "chi5=.x....xx...xxxx.xxxx.x. ",
+    "psi1=..x..xx.x.xxxx..x..xxx..xx.x.x..x.xxx..xx.. ",
+    "psi2=..x.xx.x...xx.x..x..xx.xxx..x....x.x.xxx..xx.xx ",
+    "psi3=x.x.x..xx..xxx.x.x..x..xxx.x....x..x.xx.x...xxxx.xx ",
+    "psi4=x.xxx.x.xx.xx....x..x.xx..x..xx.x.x.x.x.x.x..x.x..x.x ",
+    "psi5=.x..x..xxx....xx..x..xx...x.xx.xxxx...xxxx.xxx.x....xxxx..x ",
+    "mu61=..x.x.xx..xx.x.x.x..xxx..xx.xxx.xxx..xx...x.x...x.xxx..x..x.x ",
+    "mu37=...x.x...x.x.xxx.xx.x.xx.x.xxx.xx..x. ",
+    "start=40,22,1,4,5,32,21,27,46,1,41,7",
+);
+
+#[test]
+fn test_lorenz() {
+    let pins = lorenz_key(|name, i| {
+        if (i * 7 + name.len()) % 3 == 0 {
+            'x'
+        } else {
+            '.'
+        }
+    });
+    let key = format!("{} start=3,5,7,1,1,1,1,1,1,20,9,2 limitation=chi2", pins);
+    let encrypted = run_with_args(&["lorenz_e", "ATTACK AT 0400, HOLD THE BRIDGE.", &key]);
+    let decrypted = run_with_args(&["lorenz_d", encrypted.trim(), &key]);
+    assert_eq!(decrypted.trim(), "ATTACK AT 0400, HOLD THE BRIDGE.");
+
+    // Only chi1 crossed: every key character is E (10000), and A (11000) + E is CR, written 4.
+    let key = lorenz_key(|name, _| if name == "chi1" { 'x' } else { '.' });
+    let encrypted = run_with_args(&["lorenz_e", "AAA", &key]);
+    assert_eq!(encrypted.trim(), "444");
+
+    // psi1 crossed at its first pin and the motor always on, so the psi wheels move off it
+    // after the first letter unless the limitation holds them. For the first letter, chi2
+    // one back is chi2's last pin, which is crossed here.
+    let key = lorenz_key(|name, i| match name {
+        "psi1" if i == 0 => 'x',
+        "chi2" if i == 30 => 'x',
+        "mu37" => 'x',
+        _ => '.',
+    });
+    let encrypted = run_with_args(&["lorenz_e", "AA", &key]);
+    assert_eq!(encrypted.trim(), "4A");
+    let encrypted = run_with_args(&["lorenz_e", "AA", &format!("{} limitation=chi2", key)]);
+    assert_eq!(encrypted.trim(), "44");
+
+    let plaintext = "ANGRIFF AUF DEN BRUECKENKOPF IST FUER MORGEN FRUEH VORGESEHEN";
+    let encrypted = run_with_args(&["lorenz_e", plaintext, LORENZ_KEY]);
+    assert_eq!(
+        encrypted.trim(),
+        "CIKHCNCTLXDDYO5MQKE4FTG3R93/JVRL99X/H8OMT5UHAXPSG4OJBEHU4IWKI"
+    );
+    // The SZ42A limitation holds the psi wheels back from the eleventh letter on.
+    let key = format!("{} limitation=chi2", LORENZ_KEY);
+    let encrypted = run_with_args(&["lorenz_e", plaintext, &key]);
+    assert_eq!(
+        encrypted.trim(),
+        "CIKHCNCTLXHPRWTQMHDSIOMJFAK3F5KQECQRUKHUJ/MN5EFCWEIYZH/WPWGRA"
+    );
+    let decrypted = run_with_args(&["lorenz_d", encrypted.trim(), &key]);
+    assert_eq!(decrypted.trim(), plaintext);
+}
+
+#[test]
+fn test_solitaire() {
+    let encrypted = run_with_args(&["solitaire_e", "AAAAAAAAAA", ""]);
+    assert_eq!(encrypted.trim(), "EXKYI ZSGEH");
+
+    let encrypted = run_with_args(&["solitaire_e", "AAAAAAAAAAAAAAA", "FOO"]);
+    assert_eq!(encrypted.trim(), "ITHZU JIWGR FARMW");
+
+    let encrypted = run_with_args(&["solitaire_e", "SOLITAIRE", "CRYPTONOMICON"]);
+    assert_eq!(encrypted.trim(), "KIRAK SFJAN");
+
+    let decrypted = run_with_args(&["solitaire_d", "KIRAK SFJAN", "CRYPTONOMICON"]);
+    assert_eq!(decrypted.trim(), "SOLIT AIREX");
+
+    let trace = run_with_args(&["solitaire_trace", "A", ""]);
+    assert!(trace.starts_with("Joker A    1 2 3"));
+    assert!(trace.contains("Output     4\n"));
+}
+
+#[test]
+fn test_chaocipher() {
+    let key = "left=HXUCZVAMDSLKPEFJRIGTWOBNYQ right=PTLNBQDEOYSFAVZKGJRIHWXUMC";
+    let encrypted = run_with_args(&["chao_e", "WELLDONEISBETTERTHANWELLSAID", key]);
+    assert_eq!(encrypted.trim(), "OAHQHCNYNXTSZJRRHJBYHQKSOUJY");
+
+    let decrypted = run_with_args(&["chao_d", "OAHQHCNYNXTSZJRRHJBYHQKSOUJY", key]);
+    assert_eq!(decrypted.trim(), "WELLDONEISBETTERTHANWELLSAID");
+
+    let trace = run_with_args(&["chao_trace", "WE", key]);
+    assert_eq!(
+        trace.lines().nth(1).unwrap(),
+        "ONYQHXUCZVAMDBSLKPEFJRIGTW XUCPTLNBQDEOYMSFAVZKGJRIHW  E -> A"
+    );
+}
+
+#[test]
+fn test_homophonic() {
+    let plaintext = "OUR PATROL CROSSED THE FROZEN LAKE BEFORE SUNRISE AND FOUND THE VILLAGE EMPTY EXCEPT FOR AN OLD FARMER WHO SAID THAT THE SOLDIERS HAD MARCHED WEST TWO DAYS AGO TAKING THEIR HORSES AND WAGONS THE ROAD BEYOND THE CHURCH IS BLOCKED BY SNOW SO WE WILL WAIT HERE FOR FRESH SUPPLIES AND SEND A RUNNER BACK TO THE CAMP WITH THIS MESSAGE AS SOON AS THE STORM HAS PASSED";
+    let key = run_with_args(&["homophonic_key", "7", "30"]);
+    let key = key.trim();
+    assert_eq!(key.split([' ', ',']).count(), 30);
+    assert_eq!(key, run_with_args(&["homophonic_key", "7", "30"]).trim());
+
+    let encrypted = run_with_args(&["homophonic_e", plaintext, key]);
+    let decrypted = run_with_args(&["homophonic_d", encrypted.trim(), key]);
+    assert_eq!(
+        decrypted.trim(),
+        plaintext.replace(' ', "").to_ascii_lowercase()
+    );
+
+    let solved = run_with_args(&[
+        "homophonic_solve",
+        encrypted.trim(),
+        "restarts=4 iterations=200 seed=2",
+    ]);
+    let solved = solved.lines().nth(1).unwrap();
+    assert!(solved.contains("crossedthefro"));
+    assert!(solved.contains("thesoldiershad"));
+    assert!(solved.contains("wewillwaithereforfresh"));
+}
+
+#[test]
+fn test_straddling_checkerboard() {
+    let board = "top=ET_AON_RIS rows=BCDFGHJKLMPQ/UVWXYZ.";
+    let encoded = run_with_args(&["checkerboard_e", "ATTACK AT DAWN", board]);
+    assert_eq!(encoded.trim(), "3113212731223655");
+
+    let encoded = run_with_args(&["checkerboard_e", "MEET AT 1530.", board]);
+    assert_eq!(encoded.trim(), "2900131621115553330006269");
+    let decoded = run_with_args(&["checkerboard_d", encoded.trim(), board]);
+    assert_eq!(decoded.trim(), "MEETAT1530.");
+}
+
+#[test]
+fn test_nihilist() {
+    let key = "square=ZEBRAS key=RUSSIAN";
+    let encrypted = run_with_args(&["nihilist_e", "DYNAMITE WINTER PALACE", key]);
+    assert_eq!(
+        encrypted.trim(),
+        "37 106 62 36 67 47 86 26 104 53 62 77 27 55 57 66 55 36 54 27"
+    );
+    let decrypted = run_with_args(&["nihilist_d", encrypted.trim(), key]);
+    assert_eq!(decrypted.trim(), "dynamitewinterpalace");
+
+    let encrypted = run_with_args(&["nihilist_trans_e", "ATTACKATDAWNNOW", "KEY"]);
+    assert_eq!(encrypted.trim(), "caktattadonwwanxxx");
+    let decrypted = run_with_args(&["nihilist_trans_d", encrypted.trim(), "KEY"]);
+    assert_eq!(decrypted.trim(), "attackatdawnnowxxx");
+}
+
+#[test]
+fn test_vic() {
+    let settings =
+        "indicator=77651 date=391945 personal=6 phrase=IDREAMOFJEANIEWITHTHELIGHTBROWNHAIR";
+    let schedule = run_with_args(&["vic_keys", settings]);
+    let expected = [
+        "A  77651",
+        "B  39194",
+        "C  48567",
+        "D  IDREAMOFJE ANIEWITHTH",
+        "E  62031 89574 17520 68394",
+        "F  48567 23139 12345 67890",
+        "G  00598 02603",
+        "H  44093 47645",
+        "J  34102 59867",
+        "K  84927 13093",
+        "L  23198 43925",
+        "M  54072 72170",
+        "N  94799 93879",
+        "P  31688 21562",
+        "Q  91076 78298 82",
+        "R  59343 441",
+        "S  51790 32684",
+    ];
+    assert_eq!(schedule.lines().collect::<Vec<_>>(), expected);
+
+    let plaintext = "WE ARE DISCOVERED. TAKE WHAT YOU CAN. MOVE TO SAFEHOUSE 3.";
+    let encrypted = run_with_args(&["vic_e", plaintext, settings]);
+    // The sixth digit of the date puts the indicator fifth from the end.
+    assert_eq!(
+        encrypted.trim(),
+        "32147 92135 27912 25913 72065 87774 89695 44420 20287 08730 21255 77651 27792 \
+         90779 65730 453"
+    );
+
+    let received = "date=391945 personal=6 phrase=IDREAMOFJEANIEWITHTHELIGHTBROWNHAIR";
+    let decrypted = run_with_args(&["vic_d", encrypted.trim(), received]);
+    assert_eq!(
+        decrypted.trim(),
+        "WEAREDISCOVERED.TAKEWHATYOUCAN.MOVETOSAFEHOUSE3."
+    );
+}
+
+#[test]
+fn test_morse() {
+    let encoded = run_with_args(&["morse_e", "SOS Help <AR>", ""]);
+    assert_eq!(encoded.trim(), "... --- ... / .... . .-.. .--. / .-.-.");
+
+    let decoded = run_with_args(&["morse_d", encoded.trim(), ""]);
+    assert_eq!(decoded.trim(), "SOS HELP +");
+    let decoded = run_with_args(&["morse_d", encoded.trim(), "prosigns=on"]);
+    assert_eq!(decoded.trim(), "SOS HELP <AR>");
+
+    let options = "dot=* dash=_ letter=| word=space";
+    let encoded = run_with_args(&["morse_e", "hi there", options]);
+    assert_eq!(encoded.trim(), "****|** _|****|*|*_*|*");
+    let decoded = run_with_args(&["morse_d", encoded.trim(), options]);
+    assert_eq!(decoded.trim(), "HI THERE");
+}
+
+#[test]
+fn test_baconian() {
+    let encoded = run_with_args(&["bacon_e", "STEGANOGRAPHY", "24"]);
+    assert_eq!(
+        encoded.trim(),
+        "BAAAB BAABA AABAA AABBA AAAAA ABBAA ABBAB AABBA BAAAA AAAAA ABBBA AABBB BABBA"
+    );
+    let encoded = run_with_args(&["bacon_e", "JUDGE VERDICT", "24"]);
+    let decoded = run_with_args(&["bacon_d", encoded.trim(), "24"]);
+    assert_eq!(decoded.trim(), "iudgeuerdict");
+    let encoded = run_with_args(&["bacon_e", "JUDGE VERDICT", "26"]);
+    let decoded = run_with_args(&["bacon_d", encoded.trim(), "26"]);
+    assert_eq!(decoded.trim(), "judgeverdict");
+
+    let carrier = "Meet me tonight by the old oak tree near the gate";
+    let hidden = run_with_args(&["bacon_hide", "HELP", carrier]);
+    assert_eq!(
+        hidden.trim(),
+        "meET Me tOnigHt By tHE Old oak tree near the gate"
+    );
+    let revealed = run_with_args(&["bacon_reveal", hidden.trim(), ""]);
+    assert!(revealed.trim().starts_with("help"));
+}
+
+#[test]
+fn test_fractionated_morse() {
+    let encrypted = run_with_args(&["fracmorse_e", "Come at once", "ROUNDTABLE"]);
+    assert_eq!(encrypted.trim(), "CBIILTMHVVFL");
+    let decrypted = run_with_args(&["fracmorse_d", encrypted.trim(), "ROUNDTABLE"]);
+    assert_eq!(decrypted.trim(), "COME AT ONCE");
+
+    let encrypted = run_with_args(&["morbit_e", "Once upon a time", "WISECRACK"]);
+    assert_eq!(encrypted.trim(), "27435881512827465679378");
+    let decrypted = run_with_args(&["morbit_d", encrypted.trim(), "WISECRACK"]);
+    assert_eq!(decrypted.trim(), "ONCE UPON A TIME");
+
+    let encrypted = run_with_args(&["pollux_e", "Luck helps", "x.-x.-x.-x"]);
+    let decrypted = run_with_args(&["pollux_d", encrypted.trim(), "x.-x.-x.-x"]);
+    assert_eq!(decrypted.trim(), "LUCK HELPS");
+}
+
+#[test]
+fn test_encodings() {
+    let text = "Héllo, World!";
+    let expected = [
+        ("a1z26", "8 12 12 15 23 15 18 12 4"),
+        ("decimal", "72 233 108 108 111 44 32 87 111 114 108 100 33"),
+        ("octal", "110 303 251 154 154 157 054 040 127 157 162 154 144 041"),
+        ("hex", "48c3a96c6c6f2c20576f726c6421"),
+        ("base32", "JDB2S3DMN4WCAV3POJWGIII="),
+        ("base58", "TkY8H8SddKzpzF6DL8p"),
+        ("base64", "SMOpbGxvLCBXb3JsZCE="),
+        ("base64url", "SMOpbGxvLCBXb3JsZCE"),
+        ("url", "H%C3%A9llo%2C%20World%21"),
+        ("qp", "H=C3=A9llo, World!"),
+    ];
+    for (encoding, encoded) in expected {
+        assert_eq!(run_with_args(&["encode", text, encoding]).trim(), encoded);
+        let decoded = run_with_args(&["decode", encoded, encoding]);
+        if encoding == "a1z26" {
+            assert_eq!(decoded.trim(), "hlloworld");
+        } else {
+            assert_eq!(decoded.trim(), text);
+        }
+    }
+    let binary = run_with_args(&["encode", "Hi", "binary"]);
+    assert_eq!(binary.trim(), "01001000 01101001");
+
+    // Strict decoders reject what lenient ones accept.
+    let lenient = [
+        ("SGVs bG8", "base64"),
+        ("0x48 0x65 0x6c 0x6c 0x6f", "hex"),
+        ("jbswy3dp", "base32"),
+        ("0100100001100101011011000110110001101111", "binary"),
+        ("8-5-12-12-15", "a1z26"),
+    ];
+    for (encoded, encoding) in lenient {
+        assert_eq!(run_with_args(&["decode", encoded, encoding]), "");
+        let decoded = run_with_args(&["decode_lenient", encoded, encoding]);
+        assert_eq!(decoded.trim().to_lowercase(), "hello");
+    }
+    assert_eq!(run_with_args(&["decode_lenient", "a%2+b", "url"]).trim(), "a%2 b");
+    assert_eq!(run_with_args(&["decode", "27", "a1z26"]), "");
+    // A sign is not a digit, though Rust's integer parsing accepts a leading '+'.
+    let signed = [
+        ("+72 105", "decimal"),
+        ("+77 151", "octal"),
+        ("a%+1", "url"),
+        ("a=+1", "qp"),
+    ];
+    for (encoded, encoding) in signed {
+        assert_eq!(run_with_args(&["decode", encoded, encoding]), "");
+    }
+}
+
+#[test]
+fn test_recipe() {
+    let dir = std::env::temp_dir();
+    let text_path = dir.join("crypto_converter_recipe.txt");
+    let json_path = dir.join("crypto_converter_recipe.json");
+    std::fs::write(
+        &text_path,
+        "# peel the layers\ndecode encoding=base64\nvigenere_decrypt key=LEMON\ncaesar_decrypt shift=3\n",
+    )
+    .unwrap();
+    let text_path = text_path.to_str().unwrap();
+
+    let output = run_with_args(&["run", "--recipe", text_path, "b2FpcnN5aGl1cWt1"]);
+    assert_eq!(
+        output,
+        "1. decode encoding=base64\noairsyhiuqku\n\
+         2. vigenere_decrypt key=LEMON\ndwwdfndwgdzq\n\
+         3. caesar_decrypt shift=3\nattackatdawn\n"
+    );
+
+    let json = run_with_args(&["recipe_convert", text_path, "json"]);
+    assert_eq!(
+        json,
+        "[\n  {\"op\": \"decode\", \"encoding\": \"base64\"},\n  \
+         {\"op\": \"vigenere_decrypt\", \"key\": \"LEMON\"},\n  \
+         {\"op\": \"caesar_decrypt\", \"shift\": 3}\n]\n"
+    );
+    std::fs::write(&json_path, json).unwrap();
+    let json_path = json_path.to_str().unwrap();
+    let output = run_with_args(&["run", "--recipe", json_path, "b2FpcnN5aGl1cWt1"]);
+    assert_eq!(output.lines().last(), Some("attackatdawn"));
+    let text = run_with_args(&["recipe_convert", json_path, "text"]);
+    assert_eq!(
+        text,
+        "decode encoding=base64\nvigenere_decrypt key=LEMON\ncaesar_decrypt shift=3\n"
+    );
+}
+
+#[test]
+fn test_identify() {
+    let plaintext = "It was the best of times, it was the worst of times, it was the age of \
+        wisdom, it was the age of foolishness, it was the epoch of belief, it was the epoch \
+        of incredulity, it was the season of light, it was the season of darkness, it was \
+        the spring of hope, it was the winter of despair, we had everything before us, we \
+        had nothing before us";
+    let best = |ciphertext: &str| {
+        let output = run_with_args(&["identify", ciphertext]);
+        let line = output.lines().find(|l| l.starts_with(" 1. ")).unwrap();
+        line[4..].to_string()
+    };
+
+    let vigenere = run_with_args(&["vigenere_e", plaintext, "lemon"]);
+    let output = run_with_args(&["identify", vigenere.trim()]);
+    assert!(output.contains("peak at period 5"));
+    assert!(best(vigenere.trim()).starts_with("Periodic polyalphabetic"));
+
+    let caesar = run_with_args(&["ceasar_e", plaintext, "7"]);
+    let output = run_with_args(&["identify", caesar.trim()]);
+    assert!(best(caesar.trim()).starts_with("Monoalphabetic substitution"));
+    assert!(output.contains("shifting by 7 matches English"));
+
+    let reversed: String = plaintext.chars().rev().collect();
+    assert!(best(&reversed).starts_with("Transposition"));
+
+    let key = run_with_args(&["homophonic_key", "7", "40"]);
+    let homophonic = run_with_args(&["homophonic_e", plaintext, key.trim()]);
+    assert!(best(homophonic.trim()).starts_with("Homophonic substitution"));
+
+    let checkerboard = run_with_args(&["checkerboard_e", plaintext, "top=ET_AON_RIS"]);
+    assert!(best(checkerboard.trim()).starts_with("Straddling checkerboard"));
+
+    let nihilist = run_with_args(&["nihilist_e", plaintext, "square=ZEBRAS key=RUSSIAN"]);
+    assert!(best(nihilist.trim()).starts_with("Nihilist substitution"));
+
+    let morse = run_with_args(&["morse_e", "sos"]);
+    assert!(best(morse.trim()).starts_with("Morse code"));
+
+    // A single letter has no pairs to coincide and no spread to measure.
+    let output = run_with_args(&["identify", "A"]);
+    assert!(output.contains("Index of coincidence: n/a\nPeriodic IoC: n/a\n"));
+    assert!(output.contains("Normalized entropy: 0.000\n"));
+    assert!(!output.contains("NaN"));
+}
+
+#[test]
+fn test_frequency_report() {
+    let text = run_with_args(&["frequency", "Hello World"]);
+    assert_eq!(
+        text,
+        "For the ciphertext given below, the least common letters are d and e with a frequency of 0.100 and 0.100.\n\
+         There are 1 letters tied for the most-frequent, with a frequency of 0.300.\n\
+         One of the most frequent letters is l.\n\
+         The index of coincidence is 0.089\n"
+    );
+
+    // Case is folded, and a single letter no longer panics.
+    let text = run_with_args(&["frequency", "aA", "text"]);
+    assert!(text.starts_with("For the ciphertext given below, the only letter is a"));
+    assert!(text.contains("The index of coincidence is 1.000"));
+
+    let json = run_with_args(&["frequency", "abca", "json"]);
+    assert!(json.starts_with("{\"total\": 4, \"ioc\": 0.16666666666666666, \"entropy\": 1.5,"));
+    assert!(json.contains("\"most_common\": [\"a\"]"));
+    assert!(json.contains("{\"letter\": \"c\", \"count\": 1, \"frequency\": 0.25, \"rank\": 2}"));
+
+    let csv = run_with_args(&["frequency", "abca", "csv"]);
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows.len(), 27);
+    assert_eq!(
+        rows[..4],
+        [
+            "letter,count,frequency,rank",
+            "a,2,0.50000,1",
+            "b,1,0.25000,2",
+            "c,1,0.25000,2"
+        ]
+    );
+    assert_eq!(rows[26], "z,0,0.00000,");
+
+    let json = run_with_args(&["frequency", "", "json"]);
+    assert!(json.starts_with("{\"total\": 0, \"ioc\": null, \"entropy\": 0,"));
+    assert!(json.contains("{\"letter\": \"a\", \"count\": 0, \"frequency\": 0, \"rank\": null}"));
+}
+
+#[test]
+fn test_histogram() {
+    let chart = run_with_args(&["histogram", "Khoor Zruog", "shift=3 width=10"]);
+    let lines: Vec<&str> = chart.lines().collect();
+    assert_eq!(lines.len(), 27);
+    assert_eq!(
+        lines[0],
+        "   ciphertext          english             english+3"
+    );
+    assert_eq!(
+        lines[18],
+        "R   20.00% ██████▋       5.99% ██            7.51% ██▌"
+    );
+
+    let chart = run_with_args(&["histogram", "abab", "n=2 style=ascii width=4"]);
+    assert_eq!(
+        chart,
+        "    ciphertext    english\nAB   66.67% ####    0.15%\nBA   33.33% ##      0.07%\n"
+    );
+    assert_eq!(run_with_args(&["histogram", "abab", "style=plain"]), "");
+
+    // BC and CA tie behind AB and are listed alphabetically.
+    let chart = run_with_args(&["histogram", "abcab", "n=2 top=2"]);
+    let rows: Vec<&str> = chart.lines().skip(1).map(|l| &l[..2]).collect();
+    assert_eq!(rows, ["AB", "BC"]);
+}
+
+#[test]
+fn test_repeating_key_xor() {
+    let plaintext = "It is a truth universally acknowledged, that a single man in possession \
+        of a good fortune, must be in want of a wife. However little known the feelings or \
+        views of such a man may be on his first entering a neighbourhood, this truth is so \
+        well fixed in the minds of the surrounding families, that he is considered the \
+        rightful property of some one or other of their daughters.";
+    let ciphertext: Vec<u8> = plaintext
+        .bytes()
+        .zip(b"ICE cold KEY".iter().cycle())
+        .map(|(b, k)| b ^ k)
+        .collect();
+    let path = std::env::temp_dir().join("crypto_converter_xor.bin");
+    std::fs::write(&path, ciphertext).unwrap();
+    let path = path.to_str().unwrap();
+
+    let output = run_with_args(&["xor_crack", path, "max=20"]);
+    let mut lines = output.lines();
+    let best = lines.next().unwrap();
+    assert!(best.ends_with(" hex:49434520636f6c64204b4559 \"ICE cold KEY\""));
+    assert_eq!(
+        lines.next(),
+        Some("It is a truth universally acknowledged, that a single man in pos")
+    );
+
+    let decrypted = run_with_args(&["xor", path, "hex:49434520636f6c64204b4559"]);
+    assert_eq!(decrypted, plaintext);
+    assert_eq!(run_with_args(&["xor", path, "hex:4"]), "");
+    assert_eq!(run_with_args(&["xor", path, "hex:+1"]), "");
+    assert_eq!(run_with_args(&["xor", path, "hex:aé1"]), "");
+}
+
+#[test]
+fn test_single_byte_xor_detection() {
+    let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    let mut state = 12345u32;
+    let mut lines: Vec<String> = (0..40)
+        .map(|_| {
+            let bytes: Vec<u8> = (0..30)
+                .map(|_| {
+                    state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                    (state >> 16) as u8
+                })
+                .collect();
+            hex(&bytes)
+        })
+        .collect();
+    let secret: Vec<u8> = b"Now that the party is jumping".iter().map(|b| b ^ 0x35).collect();
+    lines[17] = hex(&secret);
+    let path = std::env::temp_dir().join("crypto_converter_xor_lines.txt");
+    std::fs::write(&path, lines.join("\n")).unwrap();
+    let path = path.to_str().unwrap();
+
+    let output = run_with_args(&["xor_detect", path, "top=2"]);
+    let results: Vec<&str> = output.lines().collect();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].starts_with("18: "));
+    assert!(results[0].ends_with(" key 35 \"Now that the party is jumping\""));
+
+    std::fs::write(path, "SGVsbG8=\nnot base64!\n").unwrap();
+    assert_eq!(run_with_args(&["xor_detect", path, "encoding=base64"]), "");
+}
+
+#[test]
+fn test_crib_dragging() {
+    let messages: [&[u8]; 3] = [
+        b"Attack the north bridge at dawn and hold it until noon",
+        b"Send more fuel to the depot before the convoy leaves",
+        b"The weather will be clear over the channel tomorrow",
+    ];
+    let mut state = 99u32;
+    let keystream: Vec<u8> = (0..60)
+        .map(|_| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            (state >> 16) as u8
+        })
+        .collect();
+    let lines: Vec<String> = messages
+        .iter()
+        .map(|m| {
+            m.iter()
+                .zip(&keystream)
+                .map(|(p, k)| format!("{:02x}", p ^ k))
+                .collect()
+        })
+        .collect();
+    let path = std::env::temp_dir().join("crypto_converter_cribdrag.txt");
+    std::fs::write(&path, lines.join("\n")).unwrap();
+    let path = path.to_str().unwrap();
+
+    let output = run_with_args(&["cribdrag", path, " the "]);
+    let top: Vec<&str> = output.lines().take(4).collect();
+    assert!(top[0].ends_with(" message 1 at 6"));
+    assert_eq!(top[1..], ["   1:  the ", "   2: ore f", "   3: ather"]);
+
+    let mut child = Command::new("cargo")
+        .args(["run", "cribdrag_interactive", path])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"drag  the \naccept 1\nset 1 0 Attack\nquit\n")
+        .unwrap();
+    let output = String::from_utf8(child.wait_with_output().unwrap().stdout).unwrap();
+    let shown: Vec<&str> = output.lines().rev().take(3).collect();
+    assert_eq!(
+        shown,
+        [
+            "3: The weather________________________________________",
+            "2: Send more f_________________________________________",
+            "1: Attack the ___________________________________________",
+        ]
+    );
+}
+
+#[test]
+fn test_extended_gcd_and_inverses() {
+    use crypto_converter::bigint::BigUint;
+    use crypto_converter::{extended_gcd, inverse_table, mod_inverse};
+
+    let output = run_with_args(&["math", "egcd", "240 46"]);
+    assert_eq!(output.trim(), "gcd(240, 46) = 2 = 240 * (-9) + 46 * (47)");
+    let output = run_with_args(&["math", "inverse", "17 3120"]);
+    assert_eq!(output.trim(), "17^-1 ≡ 2753 (mod 3120)");
+    assert_eq!(run_with_args(&["math", "inverse", "6 9"]), "");
+
+    // The same routines at every width, including products that overflow the type.
+    assert_eq!(mod_inverse(3u8, 250), Some(167));
+    assert_eq!(mod_inverse(65_521u16, 65_535), Some(4_681));
+    assert_eq!(mod_inverse(4_000_000_000u32, 4_294_967_291), Some(1_033_586_229));
+    assert_eq!(mod_inverse(10u64, 20), None);
+    let m = u128::MAX - 158; // the largest 128-bit prime
+    assert_eq!(
+        mod_inverse(u128::MAX - 1, m),
+        Some(303_436_505_534_594_808_183_900_923_824_506_685_233)
+    );
+
+    let (g, x, y) = extended_gcd(1_071u32, 462);
+    assert_eq!((g, x.to_string(), y.to_string()), (21, "-3".to_string(), "7".to_string()));
+
+    let table = inverse_table(1_000u32).unwrap();
+    assert_eq!(table.len(), 400);
+    assert!(table.iter().all(|&(a, b)| a * b % 1_000 == 1));
+    assert_eq!(table[..3], [(1, 1), (3, 667), (7, 143)]);
+    assert_eq!(inverse_table(BigUint::from(1) << 64), None);
+}
+
+#[test]
+fn test_big_integers() {
+    use crypto_converter::bigint::BigUint;
+    use crypto_converter::{gcd, mod_inverse, Unsigned};
+
+    // An RSA-2048 modulus from two 1024-bit primes, with the private exponent for e = 65537.
+    let p = "143810891872532733122227371593647189381447673275381791041256343498063205568143224868658727363706696873728844402768727626971507556241814136552962691099295070135522483828268001364206848594966471768963502752236493416054197716057951080417588017059320013675167904228568117021150795618134482832696769000008630234237";
+    let q = "120531206940442136093099952901066716714216267757688663717402731924743198844347322021578171308809535714127813938019098691937471438388687039139092674733274032556642262944482978541743163835348068998325833412970938142174581884349785215088256999869986882823507923232603417010716351315288414923039261312726594131167";
+    let n = "17333700368577790943756721031771091833285986219570318152291545848179090533425577760955471841415538581462635374876832542086439951294703706643205903022558091161309293211447013499242631488116872391954673748114600071035648429193900306065958672740234154880869710643892754631060091120045097672427392326750067971061074885512220482914021081538810184929235395104738509881441710563324144204273308358754514184815124717082764992235309431539946663159632654830258347327685721905950546449222707198348360592472023217479147409271632857624224346171963321483720558441808152699224349816639425257603075972285389044199382039491200712164579";
+    let phi = "17333700368577790943756721031771091833285986219570318152291545848179090533425577760955471841415538581462635374876832542086439951294703706643205903022558091161309293211447013499242631488116872391954673748114600071035648429193900306065958672740234154880869710643892754631060091120045097672427392326750067971060810543413407508044805754214315471023139731163705439426683051487901337799860817811864277286142608484494908333894521605221037684165002153654566291961853152803258381702449956218442410580041708676711858073106425426065995566571555585188214713424878845802725673989178253723571208825351966146443646009178465487799176";
+    let d = "12995316140648811819743697929033077897015024290284389155663103494276995197664408467076401932589393360416026466106142944029710553838503012377851882739679406306662966151200658578692760662482786946401283854004039853674497610815464510707612698573609792421329208886232610678586241620646288799259158865717653229322395978456053290511825166357419112154217427575224728943812579944253541227983603496774942401656025226622714284718150427253772152734534931682308622446155497044956243443675727433922050161584590599532484467766469427717573678501103378589769774774829443057679223427747445236338980643374635772759847155270682565230705";
+
+    let big = |s: &str| s.parse::<BigUint>().unwrap();
+    let (bp, bq, bn) = (big(p), big(q), big(n));
+    assert_eq!(bp.clone() * bq.clone(), bn);
+    assert_eq!(bn.to_string(), n);
+    assert_eq!(bn.bits(), 2048);
+    assert_eq!(bn.clone() / bq.clone(), bp);
+    assert!((bn.clone() % bp.clone()).is_zero());
+    assert_eq!((bn.clone() - BigUint::from(1)) % bp.clone(), bp.clone() - BigUint::from(1));
+    assert_eq!(gcd(bn.clone(), bp.clone() * bp.clone()), bp);
+    let one = BigUint::from(1);
+    let bphi = (bp - one.clone()) * (bq - one);
+    assert_eq!(bphi.to_string(), phi);
+    assert_eq!(mod_inverse(BigUint::from(65_537), bphi), Some(big(d)));
+    assert_eq!(big(&format!("0x{}", bn.to_hex())), bn);
+    assert_eq!(BigUint::from_bytes_be(&bn.to_bytes_be()), bn);
+    assert_eq!(BigUint::from_u128(u128::MAX).to_string(), u128::MAX.to_string());
+    assert!("12a".parse::<BigUint>().is_err());
+
+    let output = run_with_args(&["math", "inverse", &format!("65537 {}", phi)]);
+    assert_eq!(output.trim(), format!("65537^-1 ≡ {} (mod {})", d, phi));
+    let output = run_with_args(&["math", "gcd", &format!("{} {}", n, p)]);
+    assert_eq!(output.trim(), p);
+
+    // The multiplication table's products no longer overflow past 65535.
+    assert_eq!(69_999u32.mul_mod(&69_999, &70_000), 1);
+}
+
+#[test]
+fn test_unit_groups() {
+    use crypto_converter::bigint::BigUint;
+    use crypto_converter::factor::prime_factors;
+    use crypto_converter::number_theory::{
+        carmichael_lambda, euler_phi, mod_pow, multiplicative_order, UnitGroup,
+    };
+
+    let output = run_with_args(&["math", "units", "12"]);
+    assert!(output.contains("12 = 2^2 * 3"));
+    assert!(output.contains("φ(12) = 4, λ(12) = 2, not cyclic"));
+    assert!(output.contains("      5       5       2"));
+    assert!(output.contains("Units of each order: 1: 1, 2: 3"));
+    let output = run_with_args(&["math", "pow", "4 13 497"]);
+    assert_eq!(output.trim(), "4^13 ≡ 445 (mod 497)");
+    let output = run_with_args(&["math", "order", "3 7"]);
+    assert_eq!(output.trim(), "ord_7(3) = 6");
+    assert_eq!(run_with_args(&["math", "order", "2 6"]), "");
+
+    let factors = prime_factors(&720u64).unwrap();
+    assert_eq!(factors, [(2, 4), (3, 2), (5, 1)]);
+    assert_eq!(euler_phi(&factors), 192);
+    assert_eq!(carmichael_lambda(&factors), 12);
+    assert_eq!(multiplicative_order(10u32, 49), Some(42));
+    assert_eq!(mod_pow(u64::MAX - 1, u64::MAX, u64::MAX - 58), 2_012_073_826_774_673_798);
+
+    // Fermat's little theorem for the Mersenne prime 2^521 - 1.
+    let m = (BigUint::from(1) << 521) - BigUint::from(1);
+    let power = mod_pow(BigUint::from(3), m.clone() - BigUint::from(1), m);
+    assert_eq!(power, BigUint::from(1));
+
+    let group = UnitGroup::new(15u32).unwrap();
+    assert!(!group.is_cyclic());
+    assert_eq!(group.order_counts(), [(1, 1), (2, 3), (4, 4)]);
+    assert!(UnitGroup::new(2u32 * 27).unwrap().is_cyclic());
+    assert!(UnitGroup::new(BigUint::from(1) << 64).is_none());
+}
+
+#[test]
+fn test_primality_and_prime_generation() {
+    use crypto_converter::bigint::BigUint;
+    use crypto_converter::primes::{
+        baillie_psw, is_prime, is_prime_u64, miller_rabin, next_prime, primes_between,
+        random_prime, random_safe_prime,
+    };
+    use crypto_converter::rng::Rng;
+
+    let output = run_with_args(&["math", "isprime", "3825123056546413051"]);
+    assert_eq!(output.trim(), "3825123056546413051 is composite");
+    let output = run_with_args(&["math", "isprime", "1"]);
+    assert_eq!(output.trim(), "1 is neither prime nor composite");
+    let output = run_with_args(&["math", "isprime", "0"]);
+    assert_eq!(output.trim(), "0 is neither prime nor composite");
+    let output = run_with_args(&["math", "primes", "90 130"]);
+    assert_eq!(output.trim(), "97 101 103 107 109 113 127\nCount: 7");
+    let output = run_with_args(&["mult_table", "5", "classify"]);
+    assert!(output.ends_with("Count: 4\nModulus: 5 is prime, so Z/5Z is a field\n"));
+    let output = run_with_args(&["mult_table", "12", "classify"]);
+    assert!(output.ends_with("Modulus: 12 = 2^2 * 3 is composite, so its zero divisors are the multiples of 2 or 3\n"));
+
+    // 3825123056546413051 is a strong pseudoprime to every prime base up to 23.
+    assert!(!is_prime_u64(3_825_123_056_546_413_051));
+    assert!(is_prime_u64(18_446_744_073_709_551_557));
+    assert!(!is_prime_u64(561));
+    let m521 = (BigUint::from(1) << 521) - BigUint::from(1);
+    let m523 = (BigUint::from(1) << 523) - BigUint::from(1);
+    assert!(baillie_psw(&m521));
+    assert!(!baillie_psw(&m523));
+    assert!(!baillie_psw(&(m521.clone() * m521.clone())));
+    let mut rng = Rng::new(1);
+    assert!(miller_rabin(&m521, 10, &mut rng));
+    assert!(!miller_rabin(&m523, 10, &mut rng));
+    for n in 0..100 {
+        assert_eq!(miller_rabin(&BigUint::from(n), 4, &mut rng), is_prime_u64(n));
+    }
+    assert_eq!(next_prime(&BigUint::from(1_000_000_000_000)), BigUint::from(1_000_000_000_039));
+
+    assert_eq!(primes_between(0, 30), [2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+    assert_eq!(primes_between(0, 1_000_000).len(), 78_498);
+    assert_eq!(
+        primes_between(u64::MAX - 100, u64::MAX),
+        [18_446_744_073_709_551_521, 18_446_744_073_709_551_533, 18_446_744_073_709_551_557]
+    );
+
+    // Generation is reproducible from the seed.
+    let p = random_prime(256, &mut Rng::new(7));
+    assert_eq!(p.bits(), 256);
+    assert!(is_prime(&p));
+    assert_eq!(p, random_prime(256, &mut Rng::new(7)));
+    let safe = random_safe_prime(64, &mut Rng::new(7));
+    assert_eq!(safe.bits(), 64);
+    assert!(is_prime(&safe) && is_prime(&(safe >> 1)));
+    // At these sizes q and p are themselves among the sieving primes, and must not be
+    // struck out.
+    for bits in 4..=20 {
+        let safe = random_safe_prime(bits, &mut Rng::new(bits));
+        assert_eq!(safe.bits(), bits);
+        assert!(is_prime(&safe) && is_prime(&(safe >> 1)));
+    }
+    let output = run_with_args(&["math", "safeprime", "2048 1"]);
+    assert_eq!(output, "");
+}
+
+#[test]
+fn test_factorization() {
+    use crypto_converter::bigint::BigUint;
+    use crypto_converter::factor::{
+        factorize, fermat, pollard_p_minus_1, pollard_rho_brent, prime_factors,
+        trial_division, williams_p_plus_1,
+    };
+    use crypto_converter::primes::next_prime;
+
+    let output = run_with_args(&["math", "factor", "1000000016000000063"]);
+    assert_eq!(
+        output.trim(),
+        "Fermat: 1000000016000000063 = 1000000007 * 1000000009\n1000000016000000063 = 1000000007 * 1000000009"
+    );
+    let output = run_with_args(&["math", "factor", "161020"]);
+    assert_eq!(output.lines().last(), Some("161020 = 2^2 * 5 * 83 * 97"));
+
+    // Each of these keys falls to one method and not the others: p - 1 smooth, p + 1
+    // smooth, and neither but with a 32-bit factor.
+    let big = |s: &str| s.parse::<BigUint>().unwrap();
+    let n = big("14949549824101095796617398894241755249");
+    assert_eq!(pollard_p_minus_1(&n, 10_000), Some(big("1169261762275008203")));
+    assert_eq!(williams_p_plus_1(&n, 2_000, &[3, 5, 7]), None);
+    let n = big("599235240421401493575083044573530859447");
+    assert_eq!(williams_p_plus_1(&n, 2_000, &[3, 5, 7]), Some(big("33081942479464007281")));
+    assert_eq!(pollard_p_minus_1(&n, 10_000), None);
+    let n = big("162041195201438231093");
+    assert_eq!(pollard_rho_brent(&n, 1, 1 << 26), Some(big("2602669973")));
+    assert_eq!(fermat(&n, 100), None);
+    assert_eq!(fermat(&big("1000000016000000063"), 1), Some(big("1000000007")));
+
+    let (small, rest) = trial_division(&big("162041195201438231093000"), 10_000);
+    assert_eq!(small, [(big("2"), 3), (big("5"), 3)]);
+    assert_eq!(rest, n);
+
+    let cube = big("62259601441") * big("62259601441") * big("62259601441");
+    let factorization = factorize(&(cube * big("2602669973") * big("12")));
+    assert_eq!(
+        factorization.factors,
+        [(big("2"), 2), (big("3"), 1), (big("2602669973"), 1), (big("62259601441"), 3)]
+    );
+
+    // An RSA modulus is given up on within the work limit and reported as composite.
+    let p = next_prime(&(BigUint::from(1) << 1023));
+    let q = next_prime(&(BigUint::from(3) << 1022));
+    let n = p * q * BigUint::from(6);
+    let factorization = factorize(&n);
+    assert_eq!(factorization.unsplit, [n.clone() / BigUint::from(6)]);
+    let last = factorization.to_string().lines().last().unwrap().to_string();
+    assert!(last.starts_with(&format!("{} = 2 * 3 * ", n)) && last.ends_with(" (composite)"));
+    assert!(factorization.complete().is_err());
+    assert!(prime_factors(&(u128::MAX - 4)).is_ok());
+}
+
+#[test]
+fn test_chinese_remainder_theorem() {
+    use crypto_converter::bigint::BigUint;
+    use crypto_converter::number_theory::{crt, lcm};
+
+    let output = run_with_args(&["math", "crt", "2 3 3 5 2 7"]);
+    assert!(output.contains("  t ≡ 1 * 3^-1 ≡ 1 * 2 ≡ 2 (mod 5)\n"));
+    assert!(output.contains("  x = 2 + 3 * 2 = 8, so x ≡ 8 (mod 15)\n"));
+    assert!(output.ends_with("x ≡ 23 (mod 105)\n"));
+    let output = run_with_args(&["math", "crt", "3 6 1 4 7 10"]);
+    assert!(output.contains("  x = 3 + 6t with 6t ≡ 2 (mod 4), so 3t ≡ 1 (mod 2)\n"));
+    assert!(output.ends_with("x ≡ 57 (mod 60)\n"));
+    assert_eq!(run_with_args(&["math", "crt", "2 6 1 4"]), "");
+
+    assert_eq!(crt(&[(2u32, 3), (3, 5), (2, 7)]), Some((23, 105)));
+    assert_eq!(crt(&[(3u64, 6), (1, 4), (7, 10)]), Some((57, 60)));
+    assert_eq!(crt(&[(2u64, 6), (1, 4)]), None);
+    assert_eq!(crt(&[(5u8, 0)]), None);
+    assert_eq!(crt::<u32>(&[]), Some((0, 1)));
+    // Residues above their moduli are reduced, and a repeated modulus must agree.
+    assert_eq!(crt(&[(17u16, 5), (2, 5)]), Some((2, 5)));
+    // Moduli whose lcm needs all 64 bits.
+    let (x, l) = crt(&[(1u64, 4_294_967_291), (2, 4_294_967_279)]).unwrap();
+    assert_eq!((x % 4_294_967_291, x % 4_294_967_279, l), (1, 2, 18_446_743_979_220_271_189));
+    // Coprime moduli whose lcm does not fit in the type.
+    assert_eq!(crt(&[(1u64, u64::MAX), (0, u64::MAX - 1)]), None);
+    assert_eq!(crt(&[(1u8, 15), (2, 17), (0, 2)]), None);
+    assert_eq!(lcm(u64::MAX, u64::MAX - 1), None);
+    assert_eq!(lcm(1u64 << 32, 1 << 63), Some(1 << 63));
+
+    let big = |s: &str| s.parse::<BigUint>().unwrap();
+    let p = big("170141183460469231731687303715884105727");
+    let q = big("618970019642690137449562111");
+    let (x, l) = crt(&[(big("5"), p.clone()), (big("7"), q.clone())]).unwrap();
+    assert_eq!(l, p.clone() * q.clone());
+    assert_eq!((x.clone() % p, x % q), (big("5"), big("7")));
+}
+
+#[test]
+fn test_discrete_logarithms() {
+    use crypto_converter::bigint::BigUint;
+    use crypto_converter::dlog::{
+        baby_step_giant_step, discrete_log, pohlig_hellman, pollard_rho_log, AdditiveGroup,
+        MultiplicativeGroup,
+    };
+    use crypto_converter::rng::Rng;
+
+    let output = run_with_args(&["math", "dlog", "3 13 17"]);
+    assert_eq!(output.trim(), "ord(3) = 16 = 2^4\nx ≡ 4 (mod 16)\n3^4 ≡ 13 (mod 17)");
+    assert_eq!(run_with_args(&["math", "dlog", "2 3 7"]), "");
+    assert_eq!(run_with_args(&["math", "dlog", "2 8 0"]), "");
+    // Modulo a 96-bit safe prime the order of 2 has a 95-bit prime factor.
+    assert_eq!(
+        run_with_args(&["math", "dlog", "2 12345 44538373714773845339207313983"]),
+        ""
+    );
+
+    let big = |s: &str| s.parse::<BigUint>().unwrap();
+    // Diffie-Hellman modulo a 100-bit prime whose p - 1 has only 14-bit factors.
+    let p = big("909428993122710748795389383159");
+    let h = big("820573116504778318225651558214");
+    assert_eq!(discrete_log(&big("7"), &h, &p), Some(big("304428441122895878829422060984")));
+    assert_eq!(discrete_log(&big("2"), &big("3"), &big("7")), None);
+    // Not every modulus is prime: 3 generates the units mod 2 * 5^3.
+    assert_eq!(discrete_log(&big("3"), &big("27"), &big("250")), Some(big("3")));
+
+    // The subgroup of squares modulo a safe prime has prime order q.
+    let group = MultiplicativeGroup { modulus: big("34039174979") };
+    let (g, h, q) = (big("4"), big("33943447514"), big("17019587489"));
+    let x = big("9613885691");
+    assert_eq!(baby_step_giant_step(&group, &g, &h, &q), Some(x.clone()));
+    assert_eq!(pollard_rho_log(&group, &g, &h, &q, 8, 1 << 20, &mut Rng::new(1)), Some(x));
+    assert_eq!(pollard_rho_log(&group, &g, &h, &q, 1, 16, &mut Rng::new(1)), None);
+    assert_eq!(baby_step_giant_step(&group, &g, &big("34039174978"), &q), None);
+    assert_eq!(pohlig_hellman(&group, &g, &big("34039174978"), &[(q, 1)]), None);
+    // Orders with no table to build.
+    assert_eq!(baby_step_giant_step(&group, &g, &h, &BigUint::from(0)), None);
+    assert_eq!(baby_step_giant_step(&group, &g, &h, &(BigUint::from(1) << 130)), None);
+
+    // In the additive group the same question is a division.
+    let group = AdditiveGroup { modulus: big("1000003") };
+    let h = big("123456");
+    assert_eq!(
+        pohlig_hellman(&group, &big("5"), &h, &[(big("1000003"), 1)]),
+        Some(h * big("600002") % big("1000003"))
+    );
+}
+
+#[test]
+fn test_primitive_roots_and_square_roots() {
+    use crypto_converter::bigint::BigUint;
+    use crypto_converter::number_theory::jacobi;
+    use crypto_converter::residues::{
+        cipolla, has_primitive_root, hensel_sqrt, legendre, primitive_root, primitive_roots,
+        tonelli_shanks,
+    };
+
+    let output = run_with_args(&["qr_table", "11"]);
+    let expected_output = "\
+Quadratic Residues (mod 11)
+     a   1   2   3   4   5   6   7   8   9  10   0
+   a^2   1   4   9   5   3   3   5   9   4   1   0
+ (a/n)   1  -1   1   1   1  -1  -1  -1   1  -1   0
+Residues: 1 3 4 5 9
+Non-residues: 2 6 7 8 10";
+    assert_eq!(output.trim_end(), expected_output);
+    let output = run_with_args(&["math", "hensel", "2 7 3"]);
+    assert_eq!(output.trim(), "x^2 ≡ 2 (mod 343) for x ≡ 108 or 235");
+    assert_eq!(run_with_args(&["math", "hensel", "49 7 3"]), "");
+    assert_eq!(run_with_args(&["math", "hensel", "1 3 4294967295"]), "");
+    let output = run_with_args(&["math", "proot", "18446744073709551557"]);
+    assert_eq!(output.trim(), "The smallest primitive root modulo 18446744073709551557 is 2");
+    let output = run_with_args(&["math", "proots", "18"]);
+    assert_eq!(output.trim(), "5 11\nCount: 2");
+
+    assert_eq!(jacobi(1001u32, 9907), -1);
+    assert_eq!(jacobi(30u32, 35), 0);
+    assert_eq!(legendre(5u32, 11), 1);
+    assert_eq!(primitive_root(1_000_000_007u64), Some(5));
+    assert_eq!(primitive_root(2u32 * 25), Some(3));
+    assert!(!has_primitive_root(8u32) && !has_primitive_root(15u32));
+    assert!(has_primitive_root(2 * 2_147_483_647u64 * 2_147_483_647));
+    assert!(!has_primitive_root(4_294_967_279u64 * 4_294_967_291));
+    assert_eq!(primitive_roots(7u32), Some(vec![3, 5]));
+    assert_eq!(primitive_roots(1_000u32), Some(vec![]));
+    assert_eq!(primitive_roots((BigUint::from(1) << 64) + BigUint::from(13)), None);
+
+    // Both square-root methods against a search, for every residue mod small primes,
+    // including p = 1 mod 8 where Tonelli-Shanks needs several corrections.
+    for p in [3u64, 5, 7, 13, 17, 41, 73, 97, 113, 257, 641] {
+        for a in 0..p {
+            let expected = (0..p).find(|x| x * x % p == a);
+            assert_eq!(tonelli_shanks(a, p), expected, "{} mod {}", a, p);
+            assert_eq!(cipolla(a, p), expected, "{} mod {}", a, p);
+            assert_eq!(legendre(a, p), jacobi(a, p));
+        }
+    }
+
+    // A square root modulo the Mersenne prime 2^127 - 1.
+    let p = (BigUint::from(1) << 127) - BigUint::from(1);
+    let a = BigUint::from(1_234_567);
+    let r = tonelli_shanks(BigUint::from(2), p.clone()).unwrap();
+    assert_eq!(r.clone() * r % p.clone(), BigUint::from(2));
+    assert_eq!(tonelli_shanks(a.clone(), p.clone()), cipolla(a, p));
+
+    let r = hensel_sqrt(10u64, 13, 5).unwrap();
+    assert_eq!(r * r % 371_293, 10);
+    assert_eq!(hensel_sqrt(2u64, 13, 3), None);
+}
+
+#[test]
+fn test_cayley_tables() {
+    let output = run_with_args(&["cayley", "5", "units=yes"]);
+    let expected_output = "\
+Multiplication Table (mod 5)
+ ×  |[1] 2  3  4 
+----+------------
+[1] |[1] 2  3  4 
+ 2  | 2  4 [1] 3 
+ 3  | 3 [1] 4  2 
+ 4  | 4  3  2 [1]";
+    assert_eq!(output.trim_end(), expected_output.trim_end());
+
+    // The powers equal to 1 fall on the multiples of each order.
+    let output = run_with_args(&["cayley", "5", "op=pow units=yes format=csv"]);
+    assert_eq!(
+        output.trim(),
+        "^,0,1,2,3,4\n1,1,1,1,1,1\n2,1,2,4,3,1\n3,1,3,4,2,1\n4,1,4,1,4,1"
+    );
+    let output = run_with_args(&["cayley", "4", "op=add format=markdown"]);
+    let expected_output = "\
+| + | **0** | 1 | 2 | 3 |
+|---|---|---|---|---|
+| **0** | **0** | 1 | 2 | 3 |
+| 1 | 1 | 2 | 3 | **0** |
+| 2 | 2 | 3 | **0** | 1 |
+| 3 | 3 | **0** | 1 | 2 |";
+    assert_eq!(output.trim(), expected_output);
+    let output = run_with_args(&["cayley", "3", "units=yes format=latex"]);
+    let expected_output = "\
+\\begin{tabular}{c|cc}
+$\\times$ & \\textbf{1} & 2 \\\\
+\\hline
+\\textbf{1} & \\textbf{1} & 2 \\\\
+2 & 2 & \\textbf{1} \\\\
+\\end{tabular}";
+    assert_eq!(output.trim(), expected_output);
+    let output = run_with_args(&["cayley", "3", "units=yes format=html"]);
+    let expected_output = "\
+<table class=\"cayley\">
+<tr><th>×</th><th class=\"identity\">1</th><th>2</th></tr>
+<tr><th class=\"identity\">1</th><td class=\"inverse\">1</td><td>2</td></tr>
+<tr><th>2</th><td>2</td><td class=\"inverse\">1</td></tr>
+</table>";
+    assert_eq!(output.trim(), expected_output);
+    let output = run_with_args(&["cayley", "3", "units=yes format=html highlight=no"]);
+    assert!(!output.contains("class=\"inverse\""));
+}
+
+#[test]
+fn test_continued_fractions_and_wiener_attack() {
+    use crypto_converter::bigint::BigUint;
+    use crypto_converter::contfrac::{
+        convergents, expansion, wiener_attack, within_boneh_durfee_bound, within_wiener_bound,
+    };
+    use crypto_converter::primes::random_prime;
+    use crypto_converter::rng::Rng;
+    use crypto_converter::{gcd, mod_inverse};
+
+    let output = run_with_args(&["math", "cf", "415 93"]);
+    assert_eq!(
+        output.trim(),
+        "415/93 = [4; 2, 6, 7]\n4/1\n9/2\n58/13\n415/93"
+    );
+    let output = run_with_args(&["math", "wiener", "17993 90581"]);
+    let expected_output = "\
+Convergent k/d = 1/5
+φ(n) = (ed - 1)/k = 89964
+n = 379 * 239
+d = 5
+Wiener: d < n^(1/4)/3 ≈ 2^2.5: yes
+Boneh-Durfee: d < n^0.292 ≈ 2^4.8: yes";
+    assert_eq!(output.trim(), expected_output);
+    assert_eq!(run_with_args(&["math", "wiener", "3 0"]), "");
+    assert_eq!(run_with_args(&["math", "wiener", "90581 90581"]), "");
+
+    assert_eq!(expansion(649u64, 200), [3, 4, 12, 4]);
+    assert_eq!(
+        convergents(&[3u64, 4, 12, 4]),
+        [(3, 1), (13, 4), (159, 49), (649, 200)]
+    );
+    assert_eq!(expansion(0u32, 7), [0]);
+    // The last convergent is the fraction itself, in lowest terms.
+    let (a, b) = (BigUint::from(u64::MAX), BigUint::from(u64::MAX - 2));
+    let last = convergents(&expansion(a.clone(), b.clone())).pop();
+    assert_eq!(last, Some((a, b)));
+
+    // A 1024-bit modulus with a 200-bit d falls to the attack, and one with e = 65537,
+    // whose d is as large as n, does not.
+    let mut rng = Rng::new(50);
+    let p = random_prime(512, &mut rng);
+    let q = random_prime(512, &mut rng);
+    let n = p.clone() * q.clone();
+    let phi = (p.clone() - BigUint::from(1)) * (q.clone() - BigUint::from(1));
+    let d = loop {
+        let d = BigUint::random_bits(200, &mut rng);
+        if gcd(d.clone(), phi.clone()) == BigUint::from(1) {
+            break d;
+        }
+    };
+    let e = mod_inverse(d.clone(), phi.clone()).unwrap();
+    assert!(within_wiener_bound(&d, &n) && within_boneh_durfee_bound(&d, &n));
+    let key = wiener_attack(&e, &n).unwrap();
+    assert_eq!(key.d, d);
+    assert_eq!(key.phi, phi);
+    assert_eq!(key.p.clone() * key.q.clone(), n);
+    assert!(wiener_attack(&BigUint::from(65537), &n).is_none());
+
+    // Between the two bounds: too large for Wiener, small enough for Boneh-Durfee.
+    let d = BigUint::from(1) << 280;
+    assert!(!within_wiener_bound(&d, &n) && within_boneh_durfee_bound(&d, &n));
+    let output = run_with_args(&["math", "dbound", &format!("{} {}", d, n)]);
+    assert!(output.contains("Wiener: d < n^(1/4)/3 ≈ 2^254.3: no"));
+    assert!(output.contains("Boneh-Durfee: d < n^0.292 ≈ 2^298.9: yes"));
+}